@@ -0,0 +1,281 @@
+//! Generates the bundled htmx catalog from `src/htmx/catalog.json`, which
+//! lists every attribute with its documentation page, values and the htmx
+//! releases that added, deprecated or removed it, and the attributes and
+//! values each extension brings. Following an htmx release means editing
+//! the json and the markdown pages, not the code.
+
+use std::{env, fs, path::Path};
+
+use serde::Deserialize;
+
+const CATALOG: &str = "src/htmx/catalog.json";
+const DOCS: &str = "src/htmx";
+
+#[derive(Deserialize)]
+struct Catalog {
+    version: String,
+    #[serde(default)]
+    prefixes: Vec<Prefix>,
+    attributes: Vec<Attribute>,
+    #[serde(default)]
+    extensions: Vec<Extension>,
+}
+
+/// Attribute names made of a prefix and a free part, like `hx-on:click`
+#[derive(Deserialize)]
+struct Prefix {
+    name: String,
+    since: String,
+}
+
+#[derive(Deserialize)]
+struct Attribute {
+    name: String,
+    doc: String,
+    since: Option<String>,
+    deprecated: Option<Deprecated>,
+    #[serde(default)]
+    values: Vec<Value>,
+}
+
+/// An extension loaded with `hx-ext`, its attributes only mean something
+/// below the element loading it
+#[derive(Deserialize)]
+struct Extension {
+    name: String,
+    /// Families of attributes, like the `hx-target-404` ones
+    #[serde(default)]
+    prefixes: Vec<ExtensionPrefix>,
+    attributes: Vec<Attribute>,
+    /// Values it brings to htmx attributes, like the `multi:` swap
+    #[serde(default)]
+    values: Vec<ExtensionValue>,
+}
+
+#[derive(Deserialize)]
+struct ExtensionPrefix {
+    name: String,
+    doc: String,
+}
+
+#[derive(Deserialize)]
+struct ExtensionValue {
+    attribute: String,
+    name: String,
+    doc: String,
+}
+
+#[derive(Deserialize)]
+struct Deprecated {
+    since: String,
+    removed: Option<String>,
+    replacement: String,
+}
+
+#[derive(Deserialize)]
+struct Value {
+    name: String,
+    doc: String,
+    since: Option<String>,
+}
+
+fn include_doc(doc: &str) -> String {
+    let path = Path::new(DOCS).join(doc);
+    assert!(
+        path.is_file(),
+        "{} lists {:?}, which doesn't exist",
+        CATALOG,
+        path
+    );
+    println!("cargo:rerun-if-changed={}", path.display());
+
+    return format!(
+        "include_str!(concat!(env!(\"CARGO_MANIFEST_DIR\"), {:?}))",
+        format!("/{}", path.display())
+    );
+}
+
+fn option(value: &Option<String>) -> String {
+    return match value {
+        Some(value) => format!("Some({:?})", value),
+        None => "None".to_string(),
+    };
+}
+
+fn generate(catalog: &Catalog) -> String {
+    let attributes: Vec<String> = catalog
+        .attributes
+        .iter()
+        .map(|attribute| {
+            format!(
+                "    ({:?}, {}),\n",
+                attribute.name,
+                include_doc(&attribute.doc)
+            )
+        })
+        .collect();
+
+    let values: Vec<String> = catalog
+        .attributes
+        .iter()
+        .filter(|attribute| !attribute.values.is_empty())
+        .map(|attribute| {
+            let values: Vec<String> = attribute
+                .values
+                .iter()
+                .map(|value| format!("        ({:?}, {}),\n", value.name, include_doc(&value.doc)))
+                .collect();
+            return format!(
+                "    ({:?}, &[\n{}    ]),\n",
+                attribute.name,
+                values.concat()
+            );
+        })
+        .collect();
+
+    let deprecations: Vec<String> = catalog
+        .attributes
+        .iter()
+        .filter_map(|attribute| {
+            let deprecated = attribute.deprecated.as_ref()?;
+            return Some(format!(
+                "    Deprecation {{ name: {:?}, since: {:?}, removed: {}, replacement: {:?} }},\n",
+                attribute.name,
+                deprecated.since,
+                option(&deprecated.removed),
+                deprecated.replacement
+            ));
+        })
+        .collect();
+
+    let addition = |name: &str, value: Option<&str>, since: &str| {
+        return format!(
+            "    Addition {{ name: {:?}, value: {}, since: {:?} }},\n",
+            name,
+            option(&value.map(str::to_string)),
+            since
+        );
+    };
+    let additions: Vec<String> = catalog
+        .prefixes
+        .iter()
+        .map(|prefix| addition(&prefix.name, None, &prefix.since))
+        .chain(catalog.attributes.iter().flat_map(|attribute| {
+            let name = attribute
+                .since
+                .as_deref()
+                .map(|since| addition(&attribute.name, None, since));
+            let values = attribute.values.iter().filter_map(|value| {
+                let since = value.since.as_deref()?;
+                return Some(addition(&attribute.name, Some(&value.name), since));
+            });
+            return name.into_iter().chain(values).collect::<Vec<_>>();
+        }))
+        .collect();
+
+    let extension_attributes: Vec<String> = catalog
+        .extensions
+        .iter()
+        .flat_map(|extension| {
+            return extension.attributes.iter().map(|attribute| {
+                return format!(
+                    "    ({:?}, {:?}, {}),\n",
+                    extension.name,
+                    attribute.name,
+                    include_doc(&attribute.doc)
+                );
+            });
+        })
+        .collect();
+
+    let extension_prefixes: Vec<String> = catalog
+        .extensions
+        .iter()
+        .flat_map(|extension| {
+            return extension.prefixes.iter().map(|prefix| {
+                return format!(
+                    "    ({:?}, {:?}, {}),\n",
+                    extension.name,
+                    prefix.name,
+                    include_doc(&prefix.doc)
+                );
+            });
+        })
+        .collect();
+
+    let extension_values: Vec<String> = catalog
+        .extensions
+        .iter()
+        .flat_map(|extension| {
+            return extension.attributes.iter().flat_map(|attribute| {
+                return attribute.values.iter().map(|value| {
+                    return format!(
+                        "    ({:?}, {:?}, {:?}, {}),\n",
+                        extension.name,
+                        attribute.name,
+                        value.name,
+                        include_doc(&value.doc)
+                    );
+                });
+            });
+        })
+        .collect();
+
+    let extension_core_values: Vec<String> = catalog
+        .extensions
+        .iter()
+        .flat_map(|extension| {
+            return extension.values.iter().map(|value| {
+                return format!(
+                    "    ({:?}, {:?}, {:?}, {}),\n",
+                    extension.name,
+                    value.attribute,
+                    value.name,
+                    include_doc(&value.doc)
+                );
+            });
+        })
+        .collect();
+
+    return format!(
+        "// Generated by build.rs from {catalog_file}, edit that instead\n\n\
+         /// The htmx release the bundled attribute documentation describes\n\
+         pub const CATALOG_VERSION: &str = {version:?};\n\n\
+         const ATTRIBUTES: [(&str, &str); {attributes_len}] = [\n{attributes}];\n\n\
+         const ATTRIBUTE_VALUES: [(&str, &[(&str, &str)]); {values_len}] = [\n{values}];\n\n\
+         pub const DEPRECATED_ATTRIBUTES: [Deprecation; {deprecations_len}] = [\n{deprecations}];\n\n\
+         pub const ADDED_ATTRIBUTES: [Addition; {additions_len}] = [\n{additions}];\n\n\
+         const EXTENSION_ATTRIBUTES: [(&str, &str, &str); {extension_attributes_len}] = [\n{extension_attributes}];\n\n\
+         const EXTENSION_PREFIXES: [(&str, &str, &str); {extension_prefixes_len}] = [\n{extension_prefixes}];\n\n\
+         const EXTENSION_VALUES: [(&str, &str, &str, &str); {extension_values_len}] = [\n{extension_values}];\n\n\
+         const EXTENSION_CORE_VALUES: [(&str, &str, &str, &str); {extension_core_values_len}] = [\n{extension_core_values}];\n",
+        catalog_file = CATALOG,
+        version = catalog.version,
+        attributes_len = attributes.len(),
+        attributes = attributes.concat(),
+        values_len = values.len(),
+        values = values.concat(),
+        deprecations_len = deprecations.len(),
+        deprecations = deprecations.concat(),
+        additions_len = additions.len(),
+        additions = additions.concat(),
+        extension_attributes_len = extension_attributes.len(),
+        extension_attributes = extension_attributes.concat(),
+        extension_prefixes_len = extension_prefixes.len(),
+        extension_prefixes = extension_prefixes.concat(),
+        extension_values_len = extension_values.len(),
+        extension_values = extension_values.concat(),
+        extension_core_values_len = extension_core_values.len(),
+        extension_core_values = extension_core_values.concat(),
+    );
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", CATALOG);
+
+    let text = fs::read_to_string(CATALOG).expect("read the htmx catalog");
+    let catalog: Catalog = serde_json::from_str(&text).expect("parse the htmx catalog");
+
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR set by cargo")).join("catalog.rs");
+    fs::write(out, generate(&catalog)).expect("write the generated catalog");
+}
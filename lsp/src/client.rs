@@ -0,0 +1,110 @@
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    io,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Mutex,
+    },
+};
+
+use crossbeam_channel::Sender;
+use log::{error, kv::Key, kv::Value};
+use lsp_server::{Message, Notification, Request, RequestId};
+use lsp_types::{notification::LogMessage, LogMessageParams, MessageType};
+use structured_logger::Writer;
+
+/// Channel to the editor for the messages the server starts on its own,
+/// responses to client requests still go through the main loop.
+pub static CLIENT: Mutex<Option<Sender<Message>>> = Mutex::new(None);
+static NEXT_REQUEST_ID: AtomicI32 = AtomicI32::new(0);
+
+pub fn init_client(sender: Sender<Message>) {
+    *CLIENT.lock().expect("client mutex poisoned") = Some(sender);
+}
+
+/// Drops our end of the channel, the io threads only finish once every
+/// sender is gone.
+pub fn close_client() {
+    *CLIENT.lock().expect("client mutex poisoned") = None;
+}
+
+fn send(msg: Message) {
+    // Cloned so the lock isn't held while logging a failure, logs may be
+    // routed back through this very function
+    let sender = CLIENT.lock().expect("client mutex poisoned").clone();
+    let Some(sender) = sender else {
+        return;
+    };
+
+    if let Err(e) = sender.send(msg) {
+        error!("failed to send message to client: {:?}", e);
+    }
+}
+
+pub fn send_notification<N: lsp_types::notification::Notification>(params: N::Params) {
+    send(Message::Notification(Notification::new(
+        N::METHOD.to_string(),
+        params,
+    )));
+}
+
+/// Fires a request at the client, its response shows up in the main loop
+/// as a regular message and is ignored.
+pub fn send_request<R: lsp_types::request::Request>(params: R::Params) {
+    let id = NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed);
+    let id = RequestId::from(format!("htmx-lsp/{}", id));
+
+    send(Message::Request(Request::new(
+        id,
+        R::METHOD.to_string(),
+        params,
+    )));
+}
+
+thread_local! {
+    static IS_LOGGING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Log writer that shows the server logs in the editor LSP log panel
+/// through `window/logMessage`, optionally still writing them to `inner`.
+pub struct ClientLogWriter {
+    inner: Option<Box<dyn Writer>>,
+}
+
+impl ClientLogWriter {
+    pub fn new(inner: Option<Box<dyn Writer>>) -> Self {
+        return Self { inner };
+    }
+}
+
+fn message_type(level: &str) -> MessageType {
+    return match level {
+        "ERROR" => MessageType::ERROR,
+        "WARN" => MessageType::WARNING,
+        "INFO" => MessageType::INFO,
+        _ => MessageType::LOG,
+    };
+}
+
+impl Writer for ClientLogWriter {
+    fn write_log(&self, value: &BTreeMap<Key, Value>) -> Result<(), io::Error> {
+        if let Some(inner) = &self.inner {
+            inner.write_log(value)?;
+        }
+
+        // A failing send logs an error, which would land here again
+        if IS_LOGGING.with(|is_logging| is_logging.replace(true)) {
+            return Ok(());
+        }
+
+        let field = |name: &str| value.get(name).map(|v| v.to_string()).unwrap_or_default();
+        send_notification::<LogMessage>(LogMessageParams {
+            typ: message_type(&field("level")),
+            message: field("message"),
+        });
+
+        IS_LOGGING.with(|is_logging| is_logging.set(false));
+        return Ok(());
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use lsp_types::{CodeLens, Command, Location, Url};
+
+use crate::{http::send_request_lenses, ids::document_ids, index::workspace_documents};
+
+/// Selectors that make a request act on the element they point at.
+const TARGETING_ATTRIBUTES: [&str; 2] = ["hx-target", "hx-include"];
+
+/// Where each id is targeted from, across the workspace folder of `uri`.
+fn targeting_locations(uri: &Url) -> HashMap<String, Vec<Location>> {
+    let mut locations: HashMap<String, Vec<Location>> = HashMap::new();
+
+    for (uri, text) in workspace_documents(uri) {
+        for reference in document_ids(&text).references {
+            if TARGETING_ATTRIBUTES.contains(&reference.attribute.as_str()) {
+                let location = reference.location(uri.clone(), &text);
+                locations.entry(reference.id).or_default().push(location);
+            }
+        }
+    }
+
+    return locations;
+}
+
+fn title(count: usize) -> String {
+    return match count {
+        1 => "targeted by 1 request".to_string(),
+        n => format!("targeted by {} requests", n),
+    };
+}
+
+/// A "targeted by N requests" lens above every element whose id is used
+/// by a request, opening the list of those requests.
+pub fn code_lenses(uri: &Url, source: &str) -> Vec<CodeLens> {
+    let targeting = targeting_locations(uri);
+
+    let targeted = document_ids(source)
+        .definitions
+        .into_iter()
+        .filter_map(|definition| {
+            let locations = targeting.get(&definition.id)?;
+            let range = definition.location(uri.clone(), source).range;
+
+            return Some(CodeLens {
+                range,
+                command: Some(Command {
+                    title: title(locations.len()),
+                    command: "editor.action.showReferences".to_string(),
+                    arguments: Some(vec![
+                        serde_json::json!(uri),
+                        serde_json::json!(range.start),
+                        serde_json::json!(locations),
+                    ]),
+                }),
+                data: None,
+            });
+        });
+
+    return targeted.chain(send_request_lenses(source)).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::title;
+
+    #[test]
+    fn test_lens_titles() {
+        assert_eq!(title(1), "targeted by 1 request");
+        assert_eq!(title(3), "targeted by 3 requests");
+    }
+}
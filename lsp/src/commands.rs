@@ -0,0 +1,89 @@
+use anyhow::{anyhow, Result};
+use lsp_types::{request::ShowDocument, ShowDocumentParams, Url};
+use serde_json::Value;
+
+use crate::{
+    client::send_request,
+    htmx::htmx_name,
+    http::{execute_send_request, SEND_REQUEST_COMMAND},
+    scaffolds::{execute_insert_scaffold, INSERT_SCAFFOLD_COMMAND},
+};
+
+pub const OPEN_DOCUMENTATION_COMMAND: &str = "htmx-lsp.openDocumentation";
+
+/// A server side command run through `workspace/executeCommand`, its
+/// result is sent back as the response.
+pub struct ServerCommand {
+    pub name: &'static str,
+    pub run: fn(Vec<Value>) -> Result<Value>,
+}
+
+pub const COMMANDS: &[ServerCommand] = &[
+    ServerCommand {
+        name: SEND_REQUEST_COMMAND,
+        run: execute_send_request,
+    },
+    ServerCommand {
+        name: OPEN_DOCUMENTATION_COMMAND,
+        run: open_documentation,
+    },
+    ServerCommand {
+        name: INSERT_SCAFFOLD_COMMAND,
+        run: execute_insert_scaffold,
+    },
+];
+
+/// Names advertised in the `executeCommandProvider` capability.
+pub fn command_names() -> Vec<String> {
+    return COMMANDS
+        .iter()
+        .map(|command| command.name.to_string())
+        .collect();
+}
+
+pub fn execute_command(name: &str, arguments: Vec<Value>) -> Result<Value> {
+    let command = COMMANDS
+        .iter()
+        .find(|command| command.name == name)
+        .ok_or_else(|| anyhow!("unknown command: {}", name))?;
+
+    return (command.run)(arguments);
+}
+
+/// Opens the htmx.org reference of an attribute in the browser.
+fn open_documentation(arguments: Vec<Value>) -> Result<Value> {
+    let attribute = arguments
+        .first()
+        .and_then(|a| a.as_str())
+        .and_then(htmx_name)
+        .ok_or_else(|| anyhow!("{} expects an hx-* attribute", OPEN_DOCUMENTATION_COMMAND))?;
+
+    let uri = Url::parse(&format!("https://htmx.org/attributes/{}/", attribute))?;
+    send_request::<ShowDocument>(ShowDocumentParams {
+        uri,
+        external: Some(true),
+        take_focus: None,
+        selection: None,
+    });
+
+    return Ok(Value::Null);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{command_names, execute_command};
+
+    #[test]
+    fn test_rejects_unknown_commands_and_arguments() {
+        assert!(command_names().contains(&"htmx-lsp.sendRequest".to_string()));
+
+        let unknown = execute_command("htmx-lsp.nope", vec![]);
+        assert_eq!(
+            unknown.unwrap_err().to_string(),
+            "unknown command: htmx-lsp.nope"
+        );
+
+        let invalid = execute_command("htmx-lsp.openDocumentation", vec!["class".into()]);
+        assert!(invalid.is_err());
+    }
+}
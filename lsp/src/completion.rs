@@ -0,0 +1,1182 @@
+use std::sync::{Mutex, OnceLock};
+
+use lsp_types::{
+    ClientCapabilities, CompletionItem, CompletionItemKind, CompletionItemTag, CompletionList,
+    CompletionTextEdit, Documentation, InsertReplaceEdit, InsertTextFormat, MarkupContent,
+    MarkupKind, Range, TextDocumentPositionParams, TextEdit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{get_config, SnippetConfig},
+    dom::Dom,
+    encoding::{byte_offset_to_position, position_to_byte_offset},
+    htmx::{
+        attribute_extension, completion_reference, deprecation, htmx_name, is_core_attribute,
+        project_version, snippet_reference, HxCompletion, Reference,
+    },
+    text_store::{get_text_document, get_version},
+    tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+    uri::uri_key,
+    usage::Usage,
+};
+
+/// Where completion pops up on its own: attribute prefixes, right after `=`,
+/// on opening quotes, between space separated values and after colons
+pub const TRIGGER_CHARACTERS: [&str; 6] = ["-", "=", "\"", "'", " ", ":"];
+
+/// Longer lists are cut and marked incomplete, the client asks again with
+/// a longer prefix as the user types
+pub const MAX_COMPLETION_ITEMS: usize = 100;
+
+/// What the client completion UI can do, read once from its capabilities.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CompletionSupport {
+    pub snippets: bool,
+    /// Documentation can be sent as markdown instead of plain text
+    pub markdown: bool,
+    /// Items can carry both an insert and a replace range
+    pub insert_replace: bool,
+}
+
+impl CompletionSupport {
+    pub fn from_capabilities(capabilities: &ClientCapabilities) -> Self {
+        let item = capabilities
+            .text_document
+            .as_ref()
+            .and_then(|text_document| text_document.completion.as_ref())
+            .and_then(|completion| completion.completion_item.as_ref());
+
+        return Self {
+            snippets: item.and_then(|item| item.snippet_support).unwrap_or(false),
+            markdown: item
+                .and_then(|item| item.documentation_format.as_ref())
+                .is_some_and(|formats| formats.contains(&MarkupKind::Markdown)),
+            insert_replace: item
+                .and_then(|item| item.insert_replace_support)
+                .unwrap_or(false),
+        };
+    }
+
+    pub fn documentation(&self, desc: String) -> Documentation {
+        if !self.markdown {
+            return Documentation::String(desc);
+        }
+
+        return Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: desc,
+        });
+    }
+}
+
+pub static COMPLETION_SUPPORT: OnceLock<CompletionSupport> = OnceLock::new();
+pub fn init_completion_support(capabilities: &ClientCapabilities) {
+    _ = COMPLETION_SUPPORT.set(CompletionSupport::from_capabilities(capabilities));
+}
+
+pub fn completion_support() -> CompletionSupport {
+    return COMPLETION_SUPPORT.get().copied().unwrap_or_default();
+}
+
+/// Attached to every item so `completionItem/resolve` can find its docs,
+/// `attribute` is set when the item is a value of that attribute.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CompletionData {
+    pub attribute: Option<String>,
+}
+
+fn is_prefix_boundary(c: char) -> bool {
+    return c.is_whitespace() || matches!(c, '"' | '\'' | '=' | ',' | '<' | '>' | '{' | '}');
+}
+
+/// The part of the word being typed that is before the cursor.
+pub fn typed_prefix(source: &str, byte: usize) -> &str {
+    let before = &source[..byte.min(source.len())];
+    let start = before.rfind(is_prefix_boundary).map_or(0, |idx| idx + 1);
+
+    return &before[start..];
+}
+
+/// The word under the cursor, `insert` ends at the cursor while `replace`
+/// also covers the rest of the word after it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionToken {
+    pub prefix: String,
+    pub insert: Range,
+    pub replace: Range,
+    /// The value has no quotes yet, completing right after `=`
+    pub quote: bool,
+}
+
+fn word_bounds(source: &str, byte: usize) -> (usize, usize) {
+    let start = byte - typed_prefix(source, byte).len();
+    let end = source[byte..]
+        .find(is_prefix_boundary)
+        .map_or(source.len(), |idx| byte + idx);
+
+    return (start, end);
+}
+
+fn span_token(source: &str, start: usize, byte: usize, end: usize) -> CompletionToken {
+    let start_position = byte_offset_to_position(source, start);
+    return CompletionToken {
+        prefix: source[start..byte].to_string(),
+        insert: Range::new(start_position, byte_offset_to_position(source, byte)),
+        replace: Range::new(start_position, byte_offset_to_position(source, end)),
+        quote: false,
+    };
+}
+
+/// The word under the cursor, kept inside the attribute name or value node
+/// tree-sitter captures there so the `/>` after a name or the quote after
+/// a value are never replaced. Outside attributes the word alone decides.
+pub fn token_at(source: &str, byte: usize) -> CompletionToken {
+    let (start, end) = word_bounds(source, byte);
+    let capture = parse_html(source).and_then(|tree| {
+        return query_attributes(tree.root_node(), source)
+            .into_iter()
+            .flat_map(|attribute| [Some(attribute.name_range), attribute.value_range])
+            .flatten()
+            .find(|range| range.start_byte <= byte && byte <= range.end_byte);
+    });
+
+    return match capture {
+        Some(range) => span_token(
+            source,
+            start.max(range.start_byte),
+            byte,
+            end.min(range.end_byte),
+        ),
+        None => span_token(source, start, byte, end),
+    };
+}
+
+/// Where a completion is asked for. Within a word the candidates can turn
+/// on what is typed of it, like the modifier values after `swap:`, so the
+/// cursor itself is the place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionKey {
+    pub uri: String,
+    pub version: i32,
+    pub byte: usize,
+}
+
+/// Candidates of the last completion: the items, the attribute they are
+/// values of and the tag of the element.
+pub type CachedCompletion = (Vec<HxCompletion>, Option<String>, Option<String>);
+
+/// The last completion, asking again at the same place of the same version
+/// is answered without parsing the document again
+static COMPLETION_CACHE: Mutex<Option<(CompletionKey, CachedCompletion)>> = Mutex::new(None);
+
+pub fn completion_key(params: &TextDocumentPositionParams) -> Option<CompletionKey> {
+    let uri = &params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, params.position)?;
+    if !text.is_char_boundary(byte) {
+        return None;
+    }
+
+    return Some(CompletionKey {
+        uri: uri_key(uri),
+        version: get_version(uri)?,
+        byte,
+    });
+}
+
+pub fn cached_completion(key: &CompletionKey) -> Option<CachedCompletion> {
+    let cache = COMPLETION_CACHE
+        .lock()
+        .expect("completion cache mutex poisoned");
+    return cache
+        .as_ref()
+        .filter(|(cached, _)| cached == key)
+        .map(|(_, completion)| completion.clone());
+}
+
+pub fn cache_completion(key: CompletionKey, completion: CachedCompletion) {
+    *COMPLETION_CACHE
+        .lock()
+        .expect("completion cache mutex poisoned") = Some((key, completion));
+}
+
+/// Settings and workspace files change the candidates of a version too.
+pub fn clear_completion_cache() {
+    *COMPLETION_CACHE
+        .lock()
+        .expect("completion cache mutex poisoned") = None;
+}
+
+/// The attribute whose `=` was just typed and the token its quoted value
+/// goes in, taking the place of the empty quotes on type formatting adds.
+pub fn unquoted_value_token(source: &str, byte: usize) -> Option<(String, CompletionToken)> {
+    let before = source.get(..byte)?.strip_suffix('=')?;
+    let attribute = typed_prefix(before, before.len());
+    if attribute.is_empty() {
+        return None;
+    }
+
+    let after = &source[byte..];
+    let end = match after.get(..2) {
+        Some("\"\"") | Some("''") => byte + 2,
+        _ if after.starts_with(|c: char| !c.is_whitespace() && c != '>' && c != '/') => {
+            return None;
+        }
+        _ => byte,
+    };
+
+    let cursor = byte_offset_to_position(source, byte);
+    let token = CompletionToken {
+        prefix: String::new(),
+        insert: Range::new(cursor, cursor),
+        replace: Range::new(cursor, byte_offset_to_position(source, end)),
+        quote: true,
+    };
+
+    return Some((attribute.to_string(), token));
+}
+
+pub fn equals_completion(params: &TextDocumentPositionParams) -> Option<(String, CompletionToken)> {
+    let text = get_text_document(params.text_document.uri.clone())?;
+    let byte = position_to_byte_offset(&text, params.position)?;
+
+    return unquoted_value_token(&text, byte);
+}
+
+pub fn completion_token(params: &TextDocumentPositionParams) -> Option<CompletionToken> {
+    let text = get_text_document(params.text_document.uri.clone())?;
+    let byte = position_to_byte_offset(&text, params.position)?;
+    if !text.is_char_boundary(byte) {
+        return None;
+    }
+
+    return Some(token_at(&text, byte));
+}
+
+/// The tag of the element the position is in, the attributes it usually
+/// takes are ranked first.
+pub fn completion_tag(params: &TextDocumentPositionParams) -> Option<String> {
+    let text = get_text_document(params.text_document.uri.clone())?;
+    let byte = position_to_byte_offset(&text, params.position)?;
+
+    let dom = Dom::parse(&text);
+    let idx = dom.element_at(byte)?;
+    return Some(dom.elements[idx].tag.clone());
+}
+
+/// Attributes each tag usually takes
+const TAG_ATTRIBUTES: [(&str, &[&str]); 7] = [
+    (
+        "form",
+        &[
+            "hx-post",
+            "hx-put",
+            "hx-patch",
+            "hx-encoding",
+            "hx-validate",
+            "hx-params",
+        ],
+    ),
+    ("a", &["hx-get", "hx-boost", "hx-push-url"]),
+    (
+        "button",
+        &["hx-post", "hx-delete", "hx-confirm", "hx-disabled-elt"],
+    ),
+    (
+        "input",
+        &["hx-trigger", "hx-get", "hx-post", "hx-include", "hx-sync"],
+    ),
+    ("select", &["hx-trigger", "hx-get", "hx-include"]),
+    ("textarea", &["hx-trigger", "hx-post", "hx-include"]),
+    ("body", &["hx-boost", "hx-ext", "hx-indicator"]),
+];
+
+/// Values each tag usually takes, by attribute
+const TAG_VALUES: [(&str, &str, &[&str]); 6] = [
+    (
+        "input",
+        "hx-trigger",
+        &["keyup", "input", "change", "changed", "delay:"],
+    ),
+    (
+        "textarea",
+        "hx-trigger",
+        &["keyup", "input", "changed", "delay:"],
+    ),
+    ("select", "hx-trigger", &["change"]),
+    ("form", "hx-trigger", &["submit"]),
+    ("a", "hx-trigger", &["click", "mouseenter"]),
+    ("button", "hx-trigger", &["click"]),
+];
+
+/// Whether `item` is what elements with the `tag` usually take.
+fn is_usual_for(tag: &str, item: &HxCompletion, attribute: Option<&str>) -> bool {
+    return match attribute {
+        Some(attribute) => TAG_VALUES.iter().any(|(other, other_attribute, values)| {
+            return *other == tag
+                && *other_attribute == attribute
+                && values.contains(&item.name.as_str());
+        }),
+        None => {
+            let name = htmx_name(&item.name).unwrap_or(&item.name);
+            TAG_ATTRIBUTES
+                .iter()
+                .any(|(other, attributes)| *other == tag && attributes.contains(&name))
+        }
+    };
+}
+
+/// Replaces the word under the cursor with `new_text`, letting the client
+/// pick between inserting and replacing when it can.
+fn token_edit(token: &CompletionToken, new_text: String) -> CompletionTextEdit {
+    let new_text = match token.quote {
+        true => format!("\"{}\"", new_text),
+        false => new_text,
+    };
+
+    if completion_support().insert_replace {
+        return CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+            new_text,
+            insert: token.insert,
+            replace: token.replace,
+        });
+    }
+
+    return CompletionTextEdit::Edit(TextEdit::new(token.replace, new_text));
+}
+
+/// Small lists are sent whole and filtered by the client, big ones are
+/// narrowed down to the typed prefix and capped, returning whether items
+/// were left out.
+pub fn cap_completions(items: Vec<HxCompletion>, prefix: &str) -> (Vec<HxCompletion>, bool) {
+    if items.len() <= MAX_COMPLETION_ITEMS {
+        return (items, false);
+    }
+
+    let prefix = prefix.to_lowercase();
+    let mut items: Vec<_> = items
+        .into_iter()
+        .filter(|item| item.name.to_lowercase().starts_with(&prefix))
+        .collect();
+
+    let is_incomplete = items.len() > MAX_COMPLETION_ITEMS;
+    items.truncate(MAX_COMPLETION_ITEMS);
+
+    return (items, is_incomplete);
+}
+
+/// Attribute sets scaffolding a whole interaction, label, description and
+/// snippet body
+pub const SNIPPET_BUNDLES: [(&str, &str, &str); 5] = [
+    (
+        "hx-get+target+swap",
+        "Load content into another element",
+        "hx-get=\"${1:/path}\" hx-target=\"${2:#result}\" hx-swap=\"${3:innerHTML}\"",
+    ),
+    (
+        "hx-post+target+swap",
+        "Submit and replace with the response",
+        "hx-post=\"${1:/path}\" hx-target=\"${2:this}\" hx-swap=\"${3:outerHTML}\"",
+    ),
+    (
+        "hx-get+trigger (active search)",
+        "Search as the user types",
+        "hx-get=\"${1:/search}\" hx-trigger=\"${2:input changed delay:500ms}\" hx-target=\"${3:#results}\"",
+    ),
+    (
+        "hx-delete+confirm",
+        "Delete after confirming, removing the row",
+        "hx-delete=\"${1:/path}\" hx-confirm=\"${2:Are you sure?}\" hx-target=\"${3:closest tr}\" hx-swap=\"${4:outerHTML}\"",
+    ),
+    (
+        "hx-get+trigger (lazy load)",
+        "Replace a placeholder once the page loads",
+        "hx-get=\"${1:/path}\" hx-trigger=\"${2:load}\" hx-swap=\"${3:outerHTML}\"",
+    ),
+];
+
+/// Only sent to clients with snippet support, after the attribute names.
+/// The `snippets` of the settings for the `tag` come after the bundled
+/// ones, the first line of their documentation as description.
+fn snippet_bundles(snippets: &[SnippetConfig], tag: Option<&str>) -> Vec<CompletionItem> {
+    let configured = snippets
+        .iter()
+        .filter(|snippet| snippet.applies_to(tag))
+        .map(|snippet| {
+            let description = snippet
+                .documentation
+                .as_deref()
+                .and_then(|doc| doc.lines().next())
+                .unwrap_or("Snippet of the settings");
+            return (
+                snippet.label.clone(),
+                description.to_string(),
+                snippet.insert_text.clone(),
+            );
+        });
+
+    return SNIPPET_BUNDLES
+        .iter()
+        .map(|(label, description, body)| {
+            return (label.to_string(), description.to_string(), body.to_string());
+        })
+        .chain(configured)
+        .map(|(label, description, body)| CompletionItem {
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(description),
+            sort_text: Some(ranked(0, 0, format!("3{}", label))),
+            filter_text: Some(label.clone()),
+            insert_text: Some(body),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            label,
+            ..Default::default()
+        })
+        .collect();
+}
+
+/// How well `name` matches the `typed` part of it, lower being better.
+/// Names starting with it score 0, the ones holding its characters in
+/// order, like `hx-target` for `hxtg`, score by the characters skipped
+/// past the first one matched.
+fn fuzzy_score(name: &str, typed: &str) -> Option<usize> {
+    if name.starts_with(typed) {
+        return Some(0);
+    }
+
+    let mut chars = name.chars();
+    let mut skipped: Option<usize> = None;
+    for wanted in typed.chars() {
+        loop {
+            let char = chars.next()?;
+            if char.eq_ignore_ascii_case(&wanted) {
+                skipped = skipped.or(Some(0));
+                break;
+            }
+            if let Some(skipped) = skipped.as_mut() {
+                *skipped += 1;
+            }
+        }
+    }
+
+    return Some(1 + skipped.unwrap_or(0));
+}
+
+/// The sort text putting the closest matches to what was typed first, then
+/// what the workspace writes most often, the `rest` ordering the items
+/// matching and used as much.
+fn ranked(score: usize, count: usize, rest: String) -> String {
+    return format!("{:03}{:04}{}", score.min(999), 9999 - count.min(9999), rest);
+}
+
+/// Kind, detail and sort text of an item, the closest matches of the typed
+/// name and what the workspace uses most sort first, then what the `tag`
+/// usually takes, then bundled attributes before custom ones while values
+/// keep the order they are bundled in.
+fn describe(
+    item: &HxCompletion,
+    attribute: Option<&str>,
+    tag: Option<&str>,
+    idx: usize,
+    score: usize,
+    usage: &Usage,
+) -> (CompletionItemKind, String, String) {
+    let usual = tag.is_some_and(|tag| is_usual_for(tag, item, attribute));
+    let count = match attribute {
+        Some(attribute) => usage.value(attribute, &item.name),
+        None => usage.attribute(&item.name),
+    };
+
+    let (kind, detail, sort_text) = match attribute {
+        Some(attribute) => (
+            CompletionItemKind::ENUM_MEMBER,
+            format!("{} value", attribute),
+            format!("{}{:03}", if usual { 0 } else { 1 }, idx),
+        ),
+        None if htmx_name(&item.name).is_some_and(|name| name.starts_with("hx-on:")) => (
+            CompletionItemKind::EVENT,
+            "hx-on event handler".to_string(),
+            format!("1{}", item.name),
+        ),
+        None if is_core_attribute(&item.name) => (
+            CompletionItemKind::PROPERTY,
+            "htmx attribute".to_string(),
+            format!("{}{}", if usual { 0 } else { 1 }, item.name),
+        ),
+        None => match attribute_extension(&item.name) {
+            Some(extension) => (
+                CompletionItemKind::PROPERTY,
+                format!("{} extension attribute", extension),
+                format!("1{}", item.name),
+            ),
+            None => (
+                CompletionItemKind::PROPERTY,
+                "custom attribute".to_string(),
+                format!("2{}", item.name),
+            ),
+        },
+    };
+    return (kind, detail, ranked(score, count, sort_text));
+}
+
+pub fn to_completion_list(
+    items: Vec<HxCompletion>,
+    attribute: Option<String>,
+    is_incomplete: bool,
+    token: Option<CompletionToken>,
+    tag: Option<&str>,
+    usage: &Usage,
+) -> CompletionList {
+    // Attribute names come with `=""` and the cursor between the quotes,
+    // like HTML servers do for their attributes
+    let with_value = attribute.is_none() && completion_support().snippets;
+    // Values replace the token under the cursor instead of being inserted
+    // into the middle of it
+    let value_token = token.as_ref().filter(|_| attribute.is_some());
+    // Names replace the whole typed name, so completing in the middle of
+    // `hx-tr` leaves nothing behind and clients splitting words on the
+    // colon of `hx-on:` don't double it. Only the names starting with the
+    // part before the cursor are sent, or when none does the fuzzy matches,
+    // like `hx-target` for `hxtg`, for clients filtering on prefixes only.
+    let name_token = token.as_ref().filter(|_| attribute.is_none());
+    let typed = name_token.map_or("", |token| token.prefix.as_str());
+    let version = project_version();
+    let fuzzy = !items.iter().any(|x| x.name.starts_with(typed));
+
+    let items = items
+        .iter()
+        .filter_map(|x| match fuzzy_score(&x.name, typed)? {
+            0 => Some((x, 0)),
+            score => fuzzy.then_some((x, score)),
+        })
+        .enumerate()
+        .map(|(idx, (x, score))| {
+            let (kind, mut detail, sort_text) =
+                describe(x, attribute.as_deref(), tag, idx, score, usage);
+            let deprecated = attribute
+                .is_none()
+                .then(|| deprecation(&x.name, version.as_deref()))
+                .flatten();
+            if let Some(deprecated) = deprecated {
+                detail = format!("deprecated, use {}", deprecated.replacement);
+            }
+            let insert_text = match with_value {
+                true => Some(format!("{}=\"$1\"", x.name)),
+                false => x.insert_text.clone(),
+            };
+            let text_edit = match (value_token, name_token) {
+                (Some(token), _) => Some(token_edit(
+                    token,
+                    insert_text.clone().unwrap_or_else(|| x.name.clone()),
+                )),
+                (None, Some(token)) => Some(token_edit(
+                    token,
+                    insert_text.clone().unwrap_or_else(|| x.name.clone()),
+                )),
+                _ => None,
+            };
+            return CompletionItem {
+                label: x.name.clone(),
+                kind: Some(kind),
+                detail: Some(detail),
+                sort_text: Some(sort_text),
+                // The whole name, so "hx-ta" and "ta" both match whatever
+                // the client takes the word to be. Fuzzy matches are kept
+                // by what was typed and asked again as typing goes on.
+                filter_text: match score {
+                    0 => Some(x.name.clone()),
+                    _ => Some(typed.to_string()),
+                },
+                deprecated: Some(deprecated.is_some()),
+                tags: deprecated.map(|_| vec![CompletionItemTag::DEPRECATED]),
+                insert_text,
+                insert_text_format: with_value.then_some(InsertTextFormat::SNIPPET),
+                text_edit,
+                ..Default::default()
+            };
+        })
+        .collect::<Vec<_>>();
+    // Fuzzy matches only hold while the client asks again for every key
+    let is_incomplete = is_incomplete
+        || items
+            .iter()
+            .any(|item| item.filter_text.as_ref() != Some(&item.label));
+
+    // Snippets take the place of the typed name like the names do
+    let mut items = items;
+    if with_value && !typed.contains(':') {
+        items.extend(
+            snippet_bundles(&get_config().snippets, tag)
+                .into_iter()
+                .filter(|bundle| bundle.label.starts_with(typed))
+                .map(|bundle| CompletionItem {
+                    text_edit: name_token.map(|token| {
+                        return token_edit(token, bundle.insert_text.clone().unwrap_or_default());
+                    }),
+                    ..bundle
+                }),
+        );
+    }
+
+    let data = serde_json::to_value(CompletionData { attribute }).ok();
+    return CompletionList {
+        is_incomplete,
+        items: items
+            .into_iter()
+            .map(|item| CompletionItem {
+                data: data.clone(),
+                ..item
+            })
+            .collect(),
+    };
+}
+
+/// The reference of a bundled snippet, or of one of the settings with its
+/// whole documentation.
+fn snippet_documentation(snippets: &[SnippetConfig], label: &str) -> Option<Reference> {
+    if let Some((_, description, body)) = SNIPPET_BUNDLES.iter().find(|(name, _, _)| *name == label)
+    {
+        return Some(snippet_reference(description, body));
+    }
+
+    let snippet = snippets.iter().find(|snippet| snippet.label == label)?;
+    let description = snippet.documentation.as_deref().unwrap_or(label);
+    return Some(snippet_reference(description, &snippet.insert_text));
+}
+
+pub fn resolve_completion_item(mut item: CompletionItem) -> CompletionItem {
+    let data: CompletionData = item
+        .data
+        .clone()
+        .and_then(|data| serde_json::from_value(data).ok())
+        .unwrap_or_default();
+
+    let reference = match item.kind {
+        Some(CompletionItemKind::SNIPPET) => {
+            snippet_documentation(&get_config().snippets, &item.label)
+        }
+        _ => None,
+    };
+    let reference = reference.unwrap_or_else(|| {
+        return completion_reference(
+            data.attribute.as_deref(),
+            &item.label,
+            item.detail.as_deref(),
+        );
+    });
+
+    let support = completion_support();
+    let desc = match support.markdown {
+        true => reference.markdown(),
+        false => reference.plain_text(),
+    };
+    item.documentation = Some(support.documentation(desc));
+
+    return item;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cache_completion, cached_completion, cap_completions, clear_completion_cache,
+        completion_key, fuzzy_score, resolve_completion_item, snippet_bundles,
+        snippet_documentation, to_completion_list, token_at, typed_prefix, unquoted_value_token,
+        CompletionSupport, MAX_COMPLETION_ITEMS, SNIPPET_BUNDLES,
+    };
+    use crate::{
+        config::{init_config, HtmxConfig, SnippetConfig},
+        htmx::{init_hx_tags, HxCompletion},
+        text_store::{init_text_store, TEXT_STORE},
+        usage::{document_usage, Usage},
+    };
+    use lsp_types::{
+        ClientCapabilities, CompletionItemKind, CompletionItemTag, CompletionTextEdit,
+        Documentation, MarkupKind, Position, Range, TextDocumentIdentifier,
+        TextDocumentPositionParams, Url,
+    };
+
+    fn item(name: &str) -> HxCompletion {
+        return HxCompletion {
+            name: name.to_string(),
+            desc: String::new(),
+            insert_text: None,
+        };
+    }
+
+    /// The labels of the list in the order the client shows them.
+    fn sorted(list: lsp_types::CompletionList) -> Vec<String> {
+        let mut items = list.items;
+        items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        return items.into_iter().map(|item| item.label).collect();
+    }
+
+    #[test]
+    fn test_items_are_resolved_with_their_documentation() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let items = vec![HxCompletion {
+            desc: "full docs".to_string(),
+            ..item("outerHTML")
+        }];
+        let list = to_completion_list(
+            items,
+            Some("hx-swap".to_string()),
+            false,
+            None,
+            None,
+            &Usage::default(),
+        );
+        let item = list.items[0].clone();
+        assert_eq!(item.documentation, None);
+
+        // Without markdown support in the client the docs are plain text
+        let Some(Documentation::String(docs)) = resolve_completion_item(item).documentation else {
+            panic!("expected plain text documentation");
+        };
+        assert!(docs.contains("replaces the entire target element"));
+        assert!(docs.contains(r#"<div hx-swap="outerHTML"></div>"#));
+        assert!(docs.ends_with("https://htmx.org/attributes/hx-swap/"));
+    }
+
+    #[test]
+    fn test_reads_client_completion_capabilities() {
+        let capabilities: ClientCapabilities = serde_json::from_value(serde_json::json!({
+            "textDocument": { "completion": { "completionItem": {
+                "snippetSupport": true,
+                "documentationFormat": ["markdown", "plaintext"],
+            }}}
+        }))
+        .expect("valid capabilities");
+
+        let support = CompletionSupport::from_capabilities(&capabilities);
+        assert!(support.snippets && support.markdown);
+
+        let Documentation::MarkupContent(docs) = support.documentation("docs".to_string()) else {
+            panic!("expected markdown documentation");
+        };
+        assert_eq!(docs.kind, MarkupKind::Markdown);
+
+        let support = CompletionSupport::from_capabilities(&ClientCapabilities::default());
+        assert!(!support.snippets && !support.markdown);
+    }
+
+    #[test]
+    fn test_caps_long_lists_to_the_typed_prefix() {
+        let items: Vec<_> = (0..MAX_COMPLETION_ITEMS * 2)
+            .map(|idx| item(&format!("#item-{}", idx)))
+            .collect();
+
+        let (capped, is_incomplete) = cap_completions(items.clone(), "#item");
+        assert_eq!(capped.len(), MAX_COMPLETION_ITEMS);
+        assert!(is_incomplete);
+
+        let (refined, is_incomplete) = cap_completions(items, "#item-19");
+        assert_eq!(refined.len(), 11);
+        assert!(!is_incomplete);
+
+        let (few, is_incomplete) = cap_completions(refined, "");
+        assert_eq!(few.len(), 11);
+        assert!(!is_incomplete);
+    }
+
+    #[test]
+    fn test_typed_prefix() {
+        let text = r#"<div hx-target="closest #res"#;
+
+        assert_eq!(typed_prefix(text, text.len()), "#res");
+        assert_eq!(typed_prefix(text, 8), "hx-");
+        assert_eq!(typed_prefix(text, 16), "");
+    }
+
+    #[test]
+    fn test_ranks_what_the_element_usually_takes_first() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let names = vec![item("hx-get"), item("hx-post"), item("hx-target")];
+        let list = to_completion_list(
+            names.clone(),
+            None,
+            false,
+            None,
+            Some("form"),
+            &Usage::default(),
+        );
+        assert_eq!(sorted(list), vec!["hx-post", "hx-get", "hx-target"]);
+        let list = to_completion_list(names, None, false, None, Some("a"), &Usage::default());
+        assert_eq!(sorted(list), vec!["hx-get", "hx-post", "hx-target"]);
+
+        let values = vec![item("click"), item("load"), item("changed")];
+        let list = to_completion_list(
+            values,
+            Some("hx-trigger".to_string()),
+            false,
+            None,
+            Some("input"),
+            &Usage::default(),
+        );
+        assert_eq!(sorted(list), vec!["changed", "click", "load"]);
+    }
+
+    #[test]
+    fn test_ranks_what_the_workspace_uses_most_first() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        let usage = document_usage(
+            r##"<div hx-target="#a" hx-swap="outerHTML"></div>
+<div hx-target="#b" hx-swap="outerHTML swap:1s"></div>
+<div hx-get="/c" hx-swap="beforeend"></div>"##,
+        );
+
+        let names = vec![item("hx-get"), item("hx-post"), item("hx-target")];
+        let list = to_completion_list(names, None, false, None, Some("form"), &usage);
+        assert_eq!(sorted(list), vec!["hx-target", "hx-get", "hx-post"]);
+
+        let values = vec![item("innerHTML"), item("outerHTML"), item("beforeend")];
+        let list = to_completion_list(
+            values.clone(),
+            Some("hx-swap".to_string()),
+            false,
+            None,
+            None,
+            &usage,
+        );
+        assert_eq!(sorted(list), vec!["outerHTML", "beforeend", "innerHTML"]);
+
+        // Without usage the bundled order stays
+        let list = to_completion_list(
+            values,
+            Some("hx-swap".to_string()),
+            false,
+            None,
+            None,
+            &Usage::default(),
+        );
+        assert_eq!(sorted(list), vec!["innerHTML", "outerHTML", "beforeend"]);
+    }
+
+    #[test]
+    fn test_value_items_replace_the_token_under_the_cursor() {
+        init_config(HtmxConfig::default());
+        let text = r#"<div hx-swap="innerHTML"></div>"#;
+        let cursor = text.find("erHTML").expect("has the value");
+
+        let token = token_at(text, cursor);
+        assert_eq!(token.prefix, "inn");
+        assert_eq!(
+            token.insert,
+            Range::new(Position::new(0, 14), Position::new(0, 17))
+        );
+        assert_eq!(
+            token.replace,
+            Range::new(Position::new(0, 14), Position::new(0, 23))
+        );
+
+        let items = vec![item("outerHTML")];
+        let list = to_completion_list(
+            items,
+            Some("hx-swap".to_string()),
+            false,
+            Some(token),
+            None,
+            &Usage::default(),
+        );
+
+        let Some(CompletionTextEdit::Edit(edit)) = &list.items[0].text_edit else {
+            panic!("expected a replacing text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(0, 14), Position::new(0, 23))
+        );
+        assert_eq!(edit.new_text, "outerHTML");
+    }
+
+    #[test]
+    fn test_tokens_stay_inside_the_captured_attribute() {
+        let text = r#"<input hx-t/>"#;
+        let token = token_at(text, "<input hx-t".len());
+        assert_eq!(token.prefix, "hx-t");
+        assert_eq!(
+            token.replace,
+            Range::new(Position::new(0, 7), Position::new(0, 11))
+        );
+
+        // Outside attributes the word decides
+        let text = "<div > hx-t/";
+        let token = token_at(text, text.len() - 1);
+        assert_eq!(
+            token.replace,
+            Range::new(Position::new(0, 7), Position::new(0, 12))
+        );
+    }
+
+    #[test]
+    fn test_hx_on_names_replace_the_whole_typed_name() {
+        init_config(HtmxConfig::default());
+        let text = r#"<div hx-on:cl></div>"#;
+        let token = token_at(text, text.find("></").expect("has the name"));
+        assert_eq!(token.prefix, "hx-on:cl");
+
+        let items = vec![item("hx-on:click")];
+        let list = to_completion_list(items, None, false, Some(token), None, &Usage::default());
+
+        assert_eq!(list.items[0].kind, Some(CompletionItemKind::EVENT));
+        let Some(CompletionTextEdit::Edit(edit)) = &list.items[0].text_edit else {
+            panic!("expected a replacing text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(0, 5), Position::new(0, 13))
+        );
+        assert_eq!(edit.new_text, "hx-on:click");
+    }
+
+    #[test]
+    fn test_partial_names_are_filtered_and_replaced_mid_word() {
+        init_config(HtmxConfig::default());
+        let text = r#"<div hx-tr class="a"></div>"#;
+        let token = token_at(text, "<div hx-t".len());
+        assert_eq!(token.prefix, "hx-t");
+
+        let items = ["hx-target", "hx-trigger", "hx-get"]
+            .into_iter()
+            .map(item)
+            .collect();
+        let list = to_completion_list(items, None, false, Some(token), None, &Usage::default());
+
+        let labels: Vec<_> = list.items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["hx-target", "hx-trigger"]);
+        let Some(CompletionTextEdit::Edit(edit)) = &list.items[1].text_edit else {
+            panic!("expected a replacing text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(0, 5), Position::new(0, 10))
+        );
+        assert_eq!(edit.new_text, "hx-trigger");
+    }
+
+    #[test]
+    fn test_fuzzy_names_when_none_starts_with_the_typed_part() {
+        init_config(HtmxConfig::default());
+        assert_eq!(fuzzy_score("hx-target", "hx-t"), Some(0));
+        assert_eq!(fuzzy_score("hx-target", "hxtg"), Some(4));
+        assert_eq!(fuzzy_score("hx-trigger", "hxtg"), Some(4));
+        assert_eq!(fuzzy_score("hx-swap", "HXS"), Some(2));
+        assert_eq!(fuzzy_score("hx-get", "hxtg"), None);
+
+        let text = r#"<div hxtg></div>"#;
+        let token = token_at(text, "<div hxtg".len());
+        let items = ["hx-get", "hx-trigger", "hx-target"]
+            .into_iter()
+            .map(item)
+            .collect();
+        let mut list = to_completion_list(items, None, false, Some(token), None, &Usage::default());
+        assert!(list.is_incomplete);
+
+        list.items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        let labels: Vec<_> = list.items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, vec!["hx-target", "hx-trigger"]);
+        assert_eq!(list.items[0].filter_text.as_deref(), Some("hxtg"));
+        let Some(CompletionTextEdit::Edit(edit)) = &list.items[0].text_edit else {
+            panic!("expected a replacing text edit");
+        };
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(0, 5), Position::new(0, 9))
+        );
+        assert_eq!(edit.new_text, "hx-target");
+    }
+
+    #[test]
+    fn test_completions_are_cached_per_version_and_cursor() {
+        init_text_store();
+        let uri = Url::parse("file:///cached.html").expect("valid uri");
+        let open = |text: &str, version: i32| {
+            let mut store = TEXT_STORE
+                .get()
+                .expect("text store initialized")
+                .lock()
+                .expect("text store mutex");
+            store.texts.insert(uri.to_string(), text.to_string());
+            store.versions.insert(uri.to_string(), version);
+        };
+        let key = |character: u32| {
+            return completion_key(&TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, character),
+            })
+            .expect("open document");
+        };
+
+        // `swap` and `swap:` of the same word get candidates of their own
+        open(r#"<div hx-swap="swap:"></div>"#, 1);
+        let strategy = key(18);
+        assert_eq!(strategy, key(18));
+        assert_ne!(strategy, key(19));
+
+        let items = vec![item("swap:")];
+        cache_completion(
+            strategy.clone(),
+            (
+                items.clone(),
+                Some("hx-swap".to_string()),
+                Some("div".to_string()),
+            ),
+        );
+        assert_eq!(
+            cached_completion(&key(18)).map(|(items, _, _)| items),
+            Some(items)
+        );
+        assert_eq!(cached_completion(&key(19)), None);
+
+        open(r#"<div hx-swap="swap:1s"></div>"#, 2);
+        assert_eq!(cached_completion(&key(18)), None);
+        assert_eq!(
+            cached_completion(&strategy).map(|(_, attribute, _)| attribute),
+            Some(Some("hx-swap".to_string()))
+        );
+        clear_completion_cache();
+        assert_eq!(cached_completion(&strategy), None);
+    }
+
+    #[test]
+    fn test_bundled_attributes_sort_before_custom_ones() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let items = vec![item("hx-custom"), item("hx-get")];
+        let list = to_completion_list(items, None, false, None, None, &Usage::default());
+
+        let mut items = list.items;
+        items.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        let summary: Vec<_> = items
+            .iter()
+            .map(|item| (item.label.as_str(), item.detail.as_deref(), item.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "hx-get",
+                    Some("htmx attribute"),
+                    Some(CompletionItemKind::PROPERTY)
+                ),
+                (
+                    "hx-custom",
+                    Some("custom attribute"),
+                    Some(CompletionItemKind::PROPERTY)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deprecated_attributes_are_tagged() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let items = vec![item("hx-ws")];
+        let item =
+            to_completion_list(items, None, false, None, None, &Usage::default()).items[0].clone();
+
+        assert_eq!(item.tags, Some(vec![CompletionItemTag::DEPRECATED]));
+        assert_eq!(item.deprecated, Some(true));
+        assert_eq!(
+            item.detail.as_deref(),
+            Some("deprecated, use the ws extension")
+        );
+    }
+
+    #[test]
+    fn test_snippet_bundles_have_tab_stops() {
+        for item in snippet_bundles(&[], None) {
+            let body = item.insert_text.expect("bundles insert a body");
+
+            assert!(item.label.starts_with("hx-"));
+            assert!(body.starts_with(&item.label[..item.label.find('+').unwrap()]));
+            assert!(body.contains("${1:") && body.contains("${2:"));
+        }
+    }
+
+    #[test]
+    fn test_snippets_of_the_settings_follow_the_bundled_ones() {
+        let snippets = vec![
+            SnippetConfig {
+                label: "csrf".to_string(),
+                insert_text: "hx-headers='{\"X-CSRF-Token\": \"${1:token}\"}'".to_string(),
+                documentation: Some(
+                    "Send the CSRF token\nThe backend rejects posts without it".to_string(),
+                ),
+                tags: vec!["form".to_string()],
+            },
+            SnippetConfig {
+                label: "spinner".to_string(),
+                insert_text: "hx-indicator=\"${1:#spinner}\"".to_string(),
+                ..Default::default()
+            },
+        ];
+        let labels = |tag: Option<&str>| {
+            return snippet_bundles(&snippets, tag)
+                .into_iter()
+                .map(|item| (item.label, item.detail.unwrap_or_default()))
+                .skip(SNIPPET_BUNDLES.len())
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(
+            labels(Some("form")),
+            vec![
+                ("csrf".to_string(), "Send the CSRF token".to_string()),
+                ("spinner".to_string(), "Snippet of the settings".to_string()),
+            ]
+        );
+        assert_eq!(
+            labels(Some("div")),
+            vec![("spinner".to_string(), "Snippet of the settings".to_string())]
+        );
+
+        let reference = snippet_documentation(&snippets, "csrf").expect("documented");
+        assert_eq!(
+            reference.summary,
+            "Send the CSRF token\nThe backend rejects posts without it"
+        );
+        assert_eq!(
+            reference.example,
+            "<div hx-headers='{\"X-CSRF-Token\": \"token\"}'></div>"
+        );
+        assert_eq!(
+            snippet_documentation(&snippets, "spinner").map(|reference| reference.summary),
+            Some("spinner".to_string())
+        );
+    }
+
+    #[test]
+    fn test_values_after_equals_come_with_quotes() {
+        let text = r#"<div hx-swap=""></div>"#;
+        let (attribute, token) = unquoted_value_token(text, 13).expect("right after =");
+
+        assert_eq!(attribute, "hx-swap");
+        assert!(token.quote);
+        assert_eq!(
+            token.replace,
+            Range::new(Position::new(0, 13), Position::new(0, 15))
+        );
+
+        let text = r#"<div hx-swap=></div>"#;
+        let (_, token) = unquoted_value_token(text, 13).expect("right after =");
+        assert_eq!(
+            token.replace,
+            Range::new(Position::new(0, 13), Position::new(0, 13))
+        );
+
+        assert_eq!(
+            unquoted_value_token(r#"<div hx-swap=inner></div>"#, 13),
+            None
+        );
+        assert_eq!(unquoted_value_token(r#"<div hx-swap></div>"#, 12), None);
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::htmx::HxCompletion;
+
+/// Server settings, sent by the editor as `initializationOptions` and
+/// updated at runtime through `workspace/didChangeConfiguration`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HtmxConfig {
+    /// Turns hx-* completion on or off
+    pub enable_completion: bool,
+
+    /// Turns htmx diagnostics on or off
+    pub enable_diagnostics: bool,
+
+    /// The htmx version used by the project, eg "1.9.6"
+    pub htmx_version: Option<String>,
+
+    /// Language ids the server should answer for, empty means all of them
+    pub enabled_languages: Vec<String>,
+
+    /// Extra attributes offered alongside the bundled hx-* ones
+    pub custom_attributes: Vec<HxCompletion>,
+
+    /// Base url request paths like hx-get="/items" are linked against
+    pub base_url: Option<String>,
+
+    /// Directory, relative to the workspace folder, where request paths are
+    /// looked up as template files when no base url is set
+    pub routes_directory: Option<String>,
+
+    /// JSON file, relative to the workspace folder, listing the backend
+    /// routes offered when completing request urls
+    pub routes_manifest: Option<String>,
+
+    /// Rewrites htmx attributes when saving, off unless set
+    pub normalize_on_save: Option<NormalizeConfig>,
+
+    /// How range formatting lays out the JSON of hx-vals and hx-headers
+    pub json_style: JsonStyle,
+
+    /// Snippets of the team offered with the bundled ones
+    pub snippets: Vec<SnippetConfig>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JsonStyle {
+    #[default]
+    Pretty,
+    Compact,
+}
+
+/// Whether htmx attributes are written as `hx-*` or `data-hx-*`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DataPrefix {
+    Always,
+    Never,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NormalizeConfig {
+    /// Quote every value with double quotes
+    pub quotes: bool,
+
+    /// Sort the hx-* attributes of an element in a canonical order
+    pub order_attributes: bool,
+
+    /// Add or remove the data- prefix of htmx attributes, kept when unset
+    pub data_prefix: Option<DataPrefix>,
+}
+
+/// A snippet of attributes the team uses over and over, like the header of
+/// their CSRF token.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SnippetConfig {
+    pub label: String,
+
+    /// Snippet syntax, `${1:/path}` placeholders included
+    pub insert_text: String,
+
+    pub documentation: Option<String>,
+
+    /// Tags the snippet is offered on, all of them when empty
+    pub tags: Vec<String>,
+}
+
+impl SnippetConfig {
+    pub fn applies_to(&self, tag: Option<&str>) -> bool {
+        return self.tags.is_empty() || tag.is_some_and(|tag| self.tags.iter().any(|t| t == tag));
+    }
+}
+
+impl Default for HtmxConfig {
+    fn default() -> Self {
+        return Self {
+            enable_completion: true,
+            enable_diagnostics: true,
+            htmx_version: None,
+            enabled_languages: vec![],
+            custom_attributes: vec![],
+            base_url: None,
+            routes_directory: None,
+            routes_manifest: None,
+            normalize_on_save: None,
+            json_style: JsonStyle::Pretty,
+            snippets: vec![],
+        };
+    }
+}
+
+// Editors usually namespace the settings under the server name
+const SETTINGS_SECTIONS: [&str; 2] = ["htmx-lsp", "htmx"];
+
+impl HtmxConfig {
+    pub fn from_initialization_options(options: Option<serde_json::Value>) -> Self {
+        let options = match options {
+            Some(options) => options,
+            None => return Self::default(),
+        };
+
+        return match serde_json::from_value(options) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("invalid initialization options, using defaults: {:?}", e);
+                Self::default()
+            }
+        };
+    }
+
+    /// Returns a copy of the config with the keys present in `settings`
+    /// overriding the current values, keys left out are kept as they are.
+    pub fn merge_settings(&self, settings: serde_json::Value) -> Self {
+        let settings = SETTINGS_SECTIONS
+            .iter()
+            .find_map(|section| settings.get(section).cloned())
+            .unwrap_or(settings);
+
+        let serde_json::Value::Object(settings) = settings else {
+            return self.clone();
+        };
+
+        let mut current = match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(current)) => current,
+            _ => return self.clone(),
+        };
+        current.extend(settings);
+
+        return match serde_json::from_value(serde_json::Value::Object(current)) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("invalid configuration settings, keeping current: {:?}", e);
+                self.clone()
+            }
+        };
+    }
+
+    pub fn is_language_enabled(&self, language_id: &str) -> bool {
+        return self.enabled_languages.is_empty()
+            || self.enabled_languages.iter().any(|l| l == language_id);
+    }
+}
+
+pub static CONFIG: OnceLock<Arc<Mutex<HtmxConfig>>> = OnceLock::new();
+pub fn init_config(config: HtmxConfig) {
+    _ = CONFIG.set(Arc::new(Mutex::new(config)));
+}
+
+pub fn get_config() -> HtmxConfig {
+    return CONFIG
+        .get()
+        .expect("config not initialized")
+        .lock()
+        .expect("config mutex poisoned")
+        .clone();
+}
+
+pub fn set_config(config: HtmxConfig) {
+    *CONFIG
+        .get()
+        .expect("config not initialized")
+        .lock()
+        .expect("config mutex poisoned") = config;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HtmxConfig, SnippetConfig};
+    use crate::htmx::HxCompletion;
+
+    #[test]
+    fn test_parses_initialization_options() {
+        let options = serde_json::json!({
+            "htmxVersion": "1.9.6",
+            "enabledLanguages": ["html", "django-html"],
+            "customAttributes": [{ "name": "hx-foo", "description": "does foo" }],
+        });
+
+        let config = HtmxConfig::from_initialization_options(Some(options));
+
+        assert_eq!(
+            config,
+            HtmxConfig {
+                enable_completion: true,
+                enable_diagnostics: true,
+                htmx_version: Some("1.9.6".to_string()),
+                enabled_languages: vec!["html".to_string(), "django-html".to_string()],
+                custom_attributes: vec![HxCompletion {
+                    name: "hx-foo".to_string(),
+                    desc: "does foo".to_string(),
+                    insert_text: None,
+                }],
+                ..HtmxConfig::default()
+            }
+        );
+        assert!(config.is_language_enabled("django-html"));
+        assert!(!config.is_language_enabled("markdown"));
+    }
+
+    #[test]
+    fn test_parses_team_snippets() {
+        let options = serde_json::json!({
+            "snippets": [{
+                "label": "csrf",
+                "insertText": "hx-headers='{\"X-CSRF-Token\": \"${1:token}\"}'",
+                "tags": ["form"],
+            }],
+        });
+
+        let config = HtmxConfig::from_initialization_options(Some(options));
+
+        assert_eq!(
+            config.snippets,
+            vec![SnippetConfig {
+                label: "csrf".to_string(),
+                insert_text: "hx-headers='{\"X-CSRF-Token\": \"${1:token}\"}'".to_string(),
+                documentation: None,
+                tags: vec!["form".to_string()],
+            }]
+        );
+        assert!(config.snippets[0].applies_to(Some("form")));
+        assert!(!config.snippets[0].applies_to(Some("div")));
+        assert!(SnippetConfig::default().applies_to(None));
+    }
+
+    #[test]
+    fn test_falls_back_to_defaults() {
+        let config = HtmxConfig::from_initialization_options(Some(serde_json::json!(42)));
+
+        assert_eq!(config, HtmxConfig::default());
+        assert!(config.is_language_enabled("html"));
+    }
+
+    #[test]
+    fn test_merges_changed_settings() {
+        let config = HtmxConfig {
+            htmx_version: Some("1.9.6".to_string()),
+            ..Default::default()
+        };
+
+        let config = config.merge_settings(serde_json::json!({
+            "htmx-lsp": { "enableCompletion": false }
+        }));
+
+        assert!(!config.enable_completion);
+        assert_eq!(config.htmx_version, Some("1.9.6".to_string()));
+
+        let config = config.merge_settings(serde_json::json!({ "enableCompletion": true }));
+
+        assert!(config.enable_completion);
+    }
+
+    #[test]
+    fn test_keeps_config_on_invalid_settings() {
+        let config = HtmxConfig::default();
+
+        assert_eq!(
+            config.merge_settings(serde_json::json!({ "enableCompletion": "nope" })),
+            config
+        );
+        assert_eq!(config.merge_settings(serde_json::Value::Null), config);
+    }
+}
@@ -0,0 +1,82 @@
+/// Class names the selectors of a stylesheet use, in order of appearance.
+/// Comments, strings and `url()` are skipped, numbers like `1.5em` are not
+/// mistaken for classes since class names can't start with a digit.
+pub fn css_classes(text: &str) -> Vec<String> {
+    let mut classes: Vec<String> = vec![];
+    let bytes = text.as_bytes();
+    let mut idx = 0;
+
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'/' if bytes.get(idx + 1) == Some(&b'*') => {
+                idx = text[idx + 2..]
+                    .find("*/")
+                    .map_or(bytes.len(), |end| idx + end + 4);
+                continue;
+            }
+            quote @ (b'"' | b'\'') => {
+                idx = text[idx + 1..]
+                    .find(quote as char)
+                    .map_or(bytes.len(), |end| idx + end + 2);
+                continue;
+            }
+            b'(' if text[..idx].ends_with("url") => {
+                idx = text[idx..]
+                    .find(')')
+                    .map_or(bytes.len(), |end| idx + end + 1);
+                continue;
+            }
+            b'.' if !follows_number(&bytes[..idx]) => {
+                let name: String = text[idx + 1..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                    .collect();
+                let starts_with_digit = name.starts_with(|c: char| c.is_ascii_digit());
+                if !name.is_empty() && !starts_with_digit && !classes.contains(&name) {
+                    classes.push(name.clone());
+                }
+                idx += name.len() + 1;
+                continue;
+            }
+            _ => {}
+        }
+
+        idx += 1;
+    }
+
+    return classes;
+}
+
+/// Whether the dot after `before` is the decimal point of a number, as
+/// opposed to `h1.title` or `.a.b`.
+fn follows_number(before: &[u8]) -> bool {
+    let word = before
+        .iter()
+        .rev()
+        .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'-' || **byte == b'_');
+    let mut digits = word.peekable();
+
+    return digits.peek().is_some() && digits.all(u8::is_ascii_digit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::css_classes;
+
+    #[test]
+    fn test_classes_of_the_selectors() {
+        let text = r#"
+/* .commented { } */
+.htmx-indicator { opacity: 0; transition: opacity 500ms ease-in; }
+.htmx-request .htmx-indicator, .spinner.big { opacity: 1.5; }
+@media (max-width: 600px) { .spinner { background: url(img/bars.svg); } }
+a[href=".nope"] { margin: .5em; }
+h1.title { line-height: 1.2; }
+"#;
+
+        assert_eq!(
+            css_classes(text),
+            vec!["htmx-indicator", "htmx-request", "spinner", "big", "title"]
+        );
+    }
+}
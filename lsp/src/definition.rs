@@ -0,0 +1,50 @@
+use lsp_types::{GotoDefinitionResponse, Location, TextDocumentPositionParams, Url};
+
+use crate::{
+    encoding::position_to_byte_offset, ids::document_ids, index::workspace_documents,
+    text_store::get_text_document, uri::uri_key,
+};
+
+/// Locations of the elements declaring `id`, the current document is
+/// searched first and the rest of the workspace only when it has none.
+pub fn id_definitions(uri: &Url, source: &str, id: &str) -> Vec<Location> {
+    let find = |uri: &Url, source: &str| -> Vec<Location> {
+        return document_ids(source)
+            .definitions
+            .into_iter()
+            .filter(|definition| definition.id == id)
+            .map(|definition| definition.location(uri.clone(), source))
+            .collect();
+    };
+
+    let local = find(uri, source);
+    if !local.is_empty() {
+        return local;
+    }
+
+    return workspace_documents(uri)
+        .into_iter()
+        .filter(|(other, _)| uri_key(other) != uri_key(uri))
+        .flat_map(|(other, text)| find(&other, &text))
+        .collect();
+}
+
+pub fn hx_definition(text_params: TextDocumentPositionParams) -> Option<GotoDefinitionResponse> {
+    let uri = text_params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    // The cursor may sit on the "#" right before the id name
+    let ids = document_ids(&text);
+    let reference = ids
+        .references
+        .iter()
+        .find(|reference| reference.contains(byte + 1))?;
+
+    let locations = id_definitions(&uri, &text, &reference.id);
+    if locations.is_empty() {
+        return None;
+    }
+
+    return Some(GotoDefinitionResponse::Array(locations));
+}
@@ -0,0 +1,846 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use lsp_types::{
+    notification::PublishDiagnostics, Diagnostic, DiagnosticSeverity, DiagnosticTag,
+    PublishDiagnosticsParams, TextDocumentIdentifier, Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::{send_notification, send_request},
+    config::{get_config, HtmxConfig},
+    dom::Dom,
+    encoding::{byte_offset_to_position, ts_range_to_lsp},
+    htmx::{
+        attribute_extension, canonical_name, classes_problem, deprecation, head_values, htmx_name,
+        in_head, is_available, is_known_attribute, known_attribute_names, loaded_extensions,
+        multi_swap_problem, project_version, swap_problem, version_note, HEAD_SUPPORT,
+    },
+    text_store::{get_text_document, get_version},
+    tree_sitter::parse_html,
+    tree_sitter_querier::{query_attributes, AttributeNode},
+};
+
+pub const DIAGNOSTIC_SOURCE: &str = "htmx-lsp";
+
+// lsp-types doesn't know about the 3.17 pull diagnostics yet
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiagnosticParams {
+    pub text_document: TextDocumentIdentifier,
+    pub previous_result_id: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(tag = "kind")]
+pub enum DocumentDiagnosticReport {
+    #[serde(rename = "full")]
+    Full {
+        #[serde(rename = "resultId")]
+        result_id: String,
+        items: Vec<Diagnostic>,
+    },
+    #[serde(rename = "unchanged")]
+    Unchanged {
+        #[serde(rename = "resultId")]
+        result_id: String,
+    },
+}
+
+fn boolean_value(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    if canonical_name(&attribute.name) != "hx-boost" {
+        return None;
+    }
+
+    // htmx compares against "true", anything else silently disables boosting
+    let value = attribute.value.as_deref().unwrap_or("");
+    if value == "true" || value == "false" {
+        return None;
+    }
+
+    let range = attribute.value_range.unwrap_or(attribute.name_range);
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!("{} only accepts \"true\" or \"false\"", attribute.name),
+        ..Default::default()
+    });
+}
+
+fn encoding_value(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    if canonical_name(&attribute.name) != "hx-encoding" {
+        return None;
+    }
+
+    // Any other encoding leaves the request urlencoded
+    let value = attribute.value.as_deref().unwrap_or("").trim();
+    if value.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+
+    let range = attribute.value_range.unwrap_or(attribute.name_range);
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!("{} only accepts \"multipart/form-data\"", attribute.name),
+        ..Default::default()
+    });
+}
+
+/// Attributes and hx-swap styles the project htmx version doesn't have,
+/// or only keeps around for compatibility.
+fn version_support(
+    attribute: &AttributeNode,
+    source: &str,
+    version: Option<&str>,
+) -> Option<Diagnostic> {
+    if let Some(note) = version_note(&attribute.name, None, version) {
+        // Deprecated attributes still work, they only get a hint
+        let deprecated = is_available(&attribute.name, None, version)
+            && deprecation(&attribute.name, version).is_some();
+        let (severity, tags) = match deprecated {
+            true => (
+                DiagnosticSeverity::HINT,
+                Some(vec![DiagnosticTag::DEPRECATED]),
+            ),
+            false => (DiagnosticSeverity::WARNING, None),
+        };
+
+        return Some(Diagnostic {
+            range: ts_range_to_lsp(source, attribute.name_range),
+            severity: Some(severity),
+            source: Some(DIAGNOSTIC_SOURCE.to_string()),
+            message: note,
+            tags,
+            ..Default::default()
+        });
+    }
+
+    if canonical_name(&attribute.name) != "hx-swap" {
+        return None;
+    }
+
+    let range = attribute.value_range?;
+    let style = attribute.value.as_deref()?.split_whitespace().next()?;
+    let note = version_note(&attribute.name, Some(style), version)?;
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: note,
+        ..Default::default()
+    });
+}
+
+/// Whether `code` is a status the response-targets extension looks up: a
+/// full code, a prefix ending with `*`, one padded with `x` like `4xx`, or
+/// `error`.
+fn is_status_pattern(code: &str) -> bool {
+    if code == "error" {
+        return true;
+    }
+
+    let digits = code.trim_end_matches(['*', 'x']);
+    let wildcard = &code[digits.len()..];
+    let valid_digits = digits.len() <= 3
+        && digits.chars().all(|c| c.is_ascii_digit())
+        && digits
+            .chars()
+            .next()
+            .is_none_or(|c| ('1'..='5').contains(&c));
+
+    return valid_digits
+        && match wildcard {
+            "" => digits.len() == 3,
+            "*" => digits.len() < 3,
+            wildcard => wildcard.chars().all(|c| c == 'x') && code.len() == 3,
+        };
+}
+
+fn status_pattern(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    let code = canonical_name(&attribute.name).strip_prefix("hx-target-")?;
+    if is_status_pattern(code) {
+        return None;
+    }
+
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, attribute.name_range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!(
+            "{} never matches a response, use a status like 404, 4* or 4xx, or error",
+            attribute.name
+        ),
+        ..Default::default()
+    });
+}
+
+/// `multi:` swaps of the multi-swap extension that htmx can't carry out.
+fn multi_swap(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    if canonical_name(&attribute.name) != "hx-swap" {
+        return None;
+    }
+
+    let swaps = attribute.value.as_deref()?.strip_prefix("multi:")?;
+    let problem = multi_swap_problem(swaps)?;
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, attribute.value_range?),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!("{}: {}", attribute.name, problem),
+        ..Default::default()
+    });
+}
+
+/// hx-swap strategies and modifiers htmx can't read, at the word of the
+/// value they are about.
+fn swap_value(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    if canonical_name(&attribute.name) != "hx-swap" {
+        return None;
+    }
+
+    let start = attribute.value_range?.start_byte;
+    let (word, problem) = swap_problem(attribute.value.as_deref()?)?;
+    return Some(Diagnostic {
+        range: lsp_types::Range::new(
+            byte_offset_to_position(source, start + word.start),
+            byte_offset_to_position(source, start + word.end),
+        ),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!("{}: {}", attribute.name, problem),
+        ..Default::default()
+    });
+}
+
+/// Class operations of the class-tools extension that htmx can't run.
+fn class_operations(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    if !matches!(attribute.name.as_str(), "classes" | "data-classes") {
+        return None;
+    }
+
+    let problem = classes_problem(attribute.value.as_deref()?)?;
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, attribute.value_range?),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!("{}: {}", attribute.name, problem),
+        ..Default::default()
+    });
+}
+
+/// Extension attributes outside of the elements loading their extension,
+/// the extension may still be loaded by a layout the document ends up in.
+fn missing_extension(attribute: &AttributeNode, source: &str, dom: &Dom) -> Option<Diagnostic> {
+    let extension = attribute_extension(&attribute.name)?;
+    let idx = dom.element_at(attribute.name_range.start_byte)?;
+    if loaded_extensions(dom, idx)
+        .iter()
+        .any(|loaded| loaded == extension)
+    {
+        return None;
+    }
+
+    let message = match extension {
+        HEAD_SUPPORT => format!(
+            "{} only means something on the <head> of a response and the elements inside it",
+            attribute.name
+        ),
+        _ => format!(
+            "{} comes from the {} extension, no hx-ext=\"{}\" loads it here",
+            attribute.name, extension, extension
+        ),
+    };
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, attribute.name_range),
+        severity: Some(DiagnosticSeverity::INFORMATION),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    });
+}
+
+/// `hx-head` values head-support ignores where they are, `<head>` takes
+/// merge or append and the elements inside it re-eval.
+fn head_value(attribute: &AttributeNode, source: &str, dom: &Dom) -> Option<Diagnostic> {
+    if canonical_name(&attribute.name) != "hx-head" {
+        return None;
+    }
+    let idx = dom.element_at(attribute.name_range.start_byte)?;
+    if !in_head(dom, idx) {
+        return None;
+    }
+
+    let on_head = dom.elements[idx].tag == "head";
+    let values = head_values(on_head);
+    if values.contains(&attribute.value.as_deref().unwrap_or("").trim()) {
+        return None;
+    }
+
+    let element = match on_head {
+        true => "<head>",
+        false => "the elements of <head>",
+    };
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(
+            source,
+            attribute.value_range.unwrap_or(attribute.name_range),
+        ),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message: format!(
+            "{} on {} only takes {}",
+            attribute.name,
+            element,
+            values.join(" or ")
+        ),
+        ..Default::default()
+    });
+}
+
+/// Edits turning `a` into `b`, one for each character inserted, removed or
+/// changed.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let changed = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = changed.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    return row[b.len()];
+}
+
+/// hx-* attributes no htmx release, extension or setting knows, mostly
+/// typos like `hx-tigger`, with the attribute they likely meant.
+fn unknown_attribute(attribute: &AttributeNode, source: &str) -> Option<Diagnostic> {
+    let name = htmx_name(&attribute.name)?;
+    if is_known_attribute(name) {
+        return None;
+    }
+
+    let closest = known_attribute_names()
+        .into_iter()
+        .map(|known| (edit_distance(name, &known), known))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    let data = &attribute.name[..attribute.name.len() - name.len()];
+    let message = match closest {
+        Some((_, known)) => format!(
+            "`{}` is not an htmx attribute, did you mean {}{}?",
+            attribute.name, data, known
+        ),
+        None => format!("`{}` is not an htmx attribute", attribute.name),
+    };
+
+    return Some(Diagnostic {
+        range: ts_range_to_lsp(source, attribute.name_range),
+        severity: Some(DiagnosticSeverity::WARNING),
+        source: Some(DIAGNOSTIC_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    });
+}
+
+pub fn document_diagnostics(source: &str) -> Vec<Diagnostic> {
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+    let version = project_version();
+    let dom = Dom::parse(source);
+
+    return query_attributes(tree.root_node(), source)
+        .iter()
+        .flat_map(|attribute| {
+            // An attribute can break several rules at once, each gets reported
+            return [
+                boolean_value(attribute, source),
+                encoding_value(attribute, source),
+                version_support(attribute, source, version.as_deref()),
+                status_pattern(attribute, source),
+                class_operations(attribute, source),
+                multi_swap(attribute, source),
+                swap_value(attribute, source),
+                missing_extension(attribute, source, &dom),
+                head_value(attribute, source, &dom),
+                unknown_attribute(attribute, source),
+            ];
+        })
+        .flatten()
+        .collect();
+}
+
+/// Diagnostics depend on the text and the htmx version of the project, so
+/// their hash doubles as result id.
+fn result_id(source: &str, config: &HtmxConfig) -> String {
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    project_version().hash(&mut hasher);
+    // Settings like custom_attributes change what the same text reports
+    serde_json::to_string(config)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    return format!("{:x}", hasher.finish());
+}
+
+pub fn document_diagnostic_report(
+    source: &str,
+    previous_result_id: Option<String>,
+) -> DocumentDiagnosticReport {
+    let result_id = result_id(source, &get_config());
+
+    if previous_result_id.as_ref() == Some(&result_id) {
+        return DocumentDiagnosticReport::Unchanged { result_id };
+    }
+
+    return DocumentDiagnosticReport::Full {
+        result_id,
+        items: document_diagnostics(source),
+    };
+}
+
+// Quiet time after the last change before a document is analyzed, bounded
+// so diagnostics still show up while someone types non stop
+const DEBOUNCE: Duration = Duration::from_millis(200);
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+pub enum WorkspaceDiagnosticRefresh {}
+
+impl lsp_types::request::Request for WorkspaceDiagnosticRefresh {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "workspace/diagnostic/refresh";
+}
+
+static REFRESH_SUPPORT: OnceLock<bool> = OnceLock::new();
+pub fn init_diagnostic_refresh(supported: bool) {
+    _ = REFRESH_SUPPORT.set(supported);
+}
+
+/// Asks a client pulling diagnostics to pull them again for every document,
+/// the reports it holds may be outdated without their text changing.
+pub fn refresh_diagnostics() {
+    if REFRESH_SUPPORT.get().copied().unwrap_or(false) {
+        send_request::<WorkspaceDiagnosticRefresh>(());
+    }
+}
+
+/// Only set when the client doesn't pull diagnostics itself.
+static SCHEDULER: OnceLock<Sender<Url>> = OnceLock::new();
+
+pub fn init_push_diagnostics() {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    if SCHEDULER.set(sender).is_ok() {
+        thread::spawn(move || publish_loop(receiver));
+    }
+}
+
+/// Queues `uri` to have its diagnostics published once edits settle down.
+pub fn schedule_diagnostics(uri: Url) {
+    if let Some(scheduler) = SCHEDULER.get() {
+        _ = scheduler.send(uri);
+    }
+}
+
+fn publish_loop(receiver: Receiver<Url>) {
+    while let Ok(uri) = receiver.recv() {
+        let started = Instant::now();
+        let mut uris = HashSet::from([uri]);
+
+        while started.elapsed() < MAX_DELAY {
+            match receiver.recv_timeout(DEBOUNCE) {
+                Ok(uri) => _ = uris.insert(uri),
+                Err(_) => break,
+            }
+        }
+
+        uris.into_iter().for_each(publish_diagnostics);
+    }
+}
+
+fn publish_diagnostics(uri: Url) {
+    let version = get_version(&uri);
+
+    // Closed documents and disabled diagnostics publish an empty list,
+    // which clears whatever the editor is still showing
+    let diagnostics = match get_text_document(uri.clone()) {
+        Some(text) if get_config().enable_diagnostics => document_diagnostics(&text),
+        _ => vec![],
+    };
+
+    send_notification::<PublishDiagnostics>(PublishDiagnosticsParams {
+        uri,
+        diagnostics,
+        version,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        document_diagnostic_report, document_diagnostics, edit_distance, is_status_pattern,
+        version_support, DocumentDiagnosticReport,
+    };
+    use crate::{
+        config::{init_config, HtmxConfig},
+        htmx::init_hx_tags,
+        tree_sitter::parse_html,
+        tree_sitter_querier::query_attributes,
+    };
+    use lsp_types::{DiagnosticSeverity, DiagnosticTag, Position, Range};
+
+    #[test]
+    fn test_flags_non_boolean_hx_boost() {
+        init_config(HtmxConfig::default());
+        let text = r##"<body hx-boost="yes"><a hx-boost="false"></a><nav hx-boost></nav></body>"##;
+
+        let diagnostics = document_diagnostics(text);
+        let ranges: Vec<_> = diagnostics.iter().map(|d| d.range).collect();
+
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new(Position::new(0, 16), Position::new(0, 19)),
+                Range::new(Position::new(0, 50), Position::new(0, 58)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_other_encodings_than_multipart() {
+        init_config(HtmxConfig::default());
+        let text =
+            r##"<form hx-encoding="multipart/form-data"><div hx-encoding="json"></div></form>"##;
+
+        let diagnostics = document_diagnostics(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 58), Position::new(0, 62))
+        );
+        assert!(diagnostics[0].message.contains("multipart/form-data"));
+    }
+
+    #[test]
+    fn test_unchanged_report_for_same_result_id() {
+        init_config(HtmxConfig::default());
+        let text = r##"<body hx-boost="yes"></body>"##;
+
+        let DocumentDiagnosticReport::Full { result_id, items } =
+            document_diagnostic_report(text, None)
+        else {
+            panic!("expected a full report");
+        };
+        assert_eq!(items.len(), 1);
+
+        assert_eq!(
+            document_diagnostic_report(text, Some(result_id.clone())),
+            DocumentDiagnosticReport::Unchanged { result_id }
+        );
+
+        let quiet = HtmxConfig {
+            enable_diagnostics: false,
+            ..HtmxConfig::default()
+        };
+        assert_ne!(
+            super::result_id(text, &HtmxConfig::default()),
+            super::result_id(text, &quiet)
+        );
+    }
+
+    #[test]
+    fn test_flags_attributes_the_htmx_version_lacks() {
+        let text = r##"<div hx-sse="connect:/events" hx-vars="a:1" hx-inherit="*" hx-swap="textContent"></div>"##;
+        let tree = parse_html(text).expect("parsed");
+        let attributes = query_attributes(tree.root_node(), text);
+        let check = |version| {
+            return attributes
+                .iter()
+                .filter_map(|attribute| version_support(attribute, text, version))
+                .map(|d| (d.message, d.severity, d.tags))
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(
+            check(Some("1.9.6")),
+            vec![
+                (
+                    "`hx-sse` is deprecated since htmx 1.7, use the sse extension".to_string(),
+                    Some(DiagnosticSeverity::HINT),
+                    Some(vec![DiagnosticTag::DEPRECATED])
+                ),
+                (
+                    "`hx-vars` is deprecated since htmx 1.3, use hx-vals".to_string(),
+                    Some(DiagnosticSeverity::HINT),
+                    Some(vec![DiagnosticTag::DEPRECATED])
+                ),
+                (
+                    "`hx-inherit` needs htmx 2.0, the project uses 1.9.6".to_string(),
+                    Some(DiagnosticSeverity::WARNING),
+                    None
+                ),
+                (
+                    "`textContent` needs htmx 2.0, the project uses 1.9.6".to_string(),
+                    Some(DiagnosticSeverity::WARNING),
+                    None
+                ),
+            ]
+        );
+        assert_eq!(
+            check(Some("2.0.1"))[0],
+            (
+                "`hx-sse` was removed in htmx 2.0, use the sse extension".to_string(),
+                Some(DiagnosticSeverity::WARNING),
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_status_patterns_of_response_targets() {
+        for code in ["404", "4*", "40*", "*", "4xx", "40x", "error"] {
+            assert!(is_status_pattern(code), "{}", code);
+        }
+        for code in [
+            "", "4", "4044", "404*", "4xxx", "x", "6xx", "4x*", "errors", "4*4",
+        ] {
+            assert!(!is_status_pattern(code), "{}", code);
+        }
+
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text =
+            r##"<div hx-ext="response-targets"><a hx-target-4x="#e" hx-target-5*="#e"></a></div>"##;
+        let messages: Vec<_> = document_diagnostics(text)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "hx-target-4x never matches a response, use a status like 404, 4* or 4xx, or error"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_extension_attributes_without_their_extension() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text =
+            r##"<div hx-ext="path-deps"><ul path-deps="/list"></ul></div><p remove-me="1s"></p>"##;
+
+        let diagnostics = document_diagnostics(text);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].severity,
+            Some(DiagnosticSeverity::INFORMATION)
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "remove-me comes from the remove-me extension, no hx-ext=\"remove-me\" loads it here"
+        );
+    }
+
+    #[test]
+    fn test_flags_unknown_htmx_attributes() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text = r##"<div hx-tigger="click" data-hx-taget="#a" hx-frobnicate="1" hx-on-click="go()"
+  hx-on::after-request="done()" hx-target-404="#e" hx-get="/" x-data="{}"></div>"##;
+
+        let diagnostics = document_diagnostics(text);
+        let found: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.range, d.message.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Range::new(Position::new(0, 5), Position::new(0, 14)),
+                    "`hx-tigger` is not an htmx attribute, did you mean hx-trigger?"
+                ),
+                (
+                    Range::new(Position::new(0, 23), Position::new(0, 36)),
+                    "`data-hx-taget` is not an htmx attribute, did you mean data-hx-target?"
+                ),
+                (
+                    Range::new(Position::new(0, 42), Position::new(0, 55)),
+                    "`hx-frobnicate` is not an htmx attribute"
+                ),
+                (
+                    Range::new(Position::new(1, 32), Position::new(1, 45)),
+                    "hx-target-404 comes from the response-targets extension, no hx-ext=\"response-targets\" loads it here"
+                ),
+            ]
+        );
+
+        assert_eq!(edit_distance("hx-tigger", "hx-trigger"), 1);
+        assert_eq!(edit_distance("hx-swpa", "hx-swap"), 2);
+        assert_eq!(edit_distance("", "hx"), 2);
+    }
+
+    #[test]
+    fn test_flags_head_values_out_of_place() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text = r#"<head hx-head="re-eval"><script hx-head="re-eval"></script><link hx-head="merge"></head>
+<body><div hx-head="merge"></div></body>"#;
+        let messages: Vec<_> = document_diagnostics(text)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "hx-head on <head> only takes merge or append",
+                "hx-head on the elements of <head> only takes re-eval",
+                "hx-head only means something on the <head> of a response and the elements inside it",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_multi_swaps_htmx_cant_carry_out() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let messages = |value: &str| {
+            let text = format!(
+                r#"<div hx-ext="multi-swap"><a hx-get="/" hx-swap="{}"></a></div>"#,
+                value
+            );
+            return document_diagnostics(&text)
+                .into_iter()
+                .map(|d| d.message)
+                .collect::<Vec<_>>();
+        };
+
+        assert!(messages("multi:#id1,#id2:outerHTML,#id3:beforeend").is_empty());
+        assert_eq!(
+            messages("multi:#id1, #id2"),
+            vec!["hx-swap: multi-swap only reads the value up to its first space"]
+        );
+        assert_eq!(
+            messages("multi:.item"),
+            vec!["hx-swap: `.item` is not an id selector, multi-swap only swaps elements by id"]
+        );
+        assert_eq!(
+            messages("multi:#id1:replace"),
+            vec!["hx-swap: `replace` is not a swap strategy, use one of hx-swap like outerHTML"]
+        );
+        assert_eq!(
+            messages("multi:#id1,,#id2"),
+            vec!["hx-swap: a selector between the commas is empty"]
+        );
+    }
+
+    #[test]
+    fn test_flags_swap_values_htmx_cant_read() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text = r##"<a hx-swap="innerHtml"></a><a hx-swap="replace"></a>
+<a data-hx-swap="outerHTML  swap:1s settle:fast"></a><a hx-swap="scroll:#list:top show:none"></a>
+<a hx-swap="morph:outerHTML transition:true ignoreTitle:yes"></a><a hx-swap="textContent"></a>"##;
+
+        let found: Vec<_> = document_diagnostics(text)
+            .into_iter()
+            .map(|d| (d.range, d.message))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Range::new(Position::new(0, 12), Position::new(0, 21)),
+                    "hx-swap: `innerHtml` is not a swap strategy, did you mean innerHTML?"
+                        .to_string()
+                ),
+                (
+                    Range::new(Position::new(0, 39), Position::new(0, 46)),
+                    "hx-swap: `replace` is not a swap strategy, use one of innerHTML, outerHTML, afterbegin, afterend, beforebegin, beforeend, delete, none, textContent"
+                        .to_string()
+                ),
+                (
+                    Range::new(Position::new(1, 36), Position::new(1, 47)),
+                    "data-hx-swap: `settle:fast` needs a time like 100ms or 1s".to_string()
+                ),
+                (
+                    Range::new(Position::new(2, 44), Position::new(2, 59)),
+                    "hx-swap: `ignoreTitle:yes` needs true or false".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flags_class_operations_htmx_cant_run() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let messages = |value: &str| {
+            let text = format!(
+                r#"<div hx-ext="class-tools"><a classes="{}"></a></div>"#,
+                value
+            );
+            return document_diagnostics(&text)
+                .into_iter()
+                .map(|d| d.message)
+                .collect::<Vec<_>>();
+        };
+
+        assert!(messages("remove bar:1s, add foo & toggle blink:500ms").is_empty());
+        assert_eq!(
+            messages("grow foo"),
+            vec!["classes: `grow` is not a class operation, use add, remove or toggle"]
+        );
+        assert_eq!(
+            messages("add foo:soon"),
+            vec!["classes: `soon` is not a delay, use a time like 100ms or 1s"]
+        );
+        assert_eq!(
+            messages("add foo,"),
+            vec!["classes: an operation between the separators is empty"]
+        );
+        assert_eq!(
+            messages("add"),
+            vec!["classes: `add` needs the class to add"]
+        );
+    }
+
+    #[test]
+    fn test_reports_every_rule_an_attribute_breaks() {
+        init_config(HtmxConfig::default());
+        init_hx_tags();
+        let text = r#"<a classes="grow foo"></a>"#;
+
+        let messages: Vec<_> = document_diagnostics(text)
+            .into_iter()
+            .map(|d| d.message)
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "classes: `grow` is not a class operation, use add, remove or toggle",
+                "classes comes from the class-tools extension, no hx-ext=\"class-tools\" loads it here",
+            ]
+        );
+    }
+}
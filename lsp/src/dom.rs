@@ -0,0 +1,314 @@
+use tree_sitter::{Node, Range};
+
+use crate::{
+    htmx::canonical_name,
+    tree_sitter::parse_html,
+    tree_sitter_querier::{query_attributes, AttributeNode},
+};
+
+/// An element of the document with the attributes of its start tag.
+#[derive(Debug)]
+pub struct Element {
+    pub tag: String,
+    pub tag_range: Range,
+    pub start_tag_range: Range,
+    pub range: Range,
+    pub attributes: Vec<AttributeNode>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl Element {
+    pub fn attribute(&self, name: &str) -> Option<&AttributeNode> {
+        return self
+            .attributes
+            .iter()
+            .find(|attribute| canonical_name(&attribute.name) == name);
+    }
+
+    pub fn attribute_value(&self, name: &str) -> Option<&str> {
+        return self.attribute(name)?.value.as_deref().map(str::trim);
+    }
+
+    pub fn id(&self) -> Option<&str> {
+        return self.attribute_value("id").filter(|id| !id.is_empty());
+    }
+
+    pub fn classes(&self) -> impl Iterator<Item = &str> {
+        return self
+            .attribute_value("class")
+            .unwrap_or("")
+            .split_whitespace();
+    }
+
+    /// Short css like description, eg. `div#results` or `li.item`.
+    pub fn selector(&self) -> String {
+        if let Some(id) = self.id() {
+            return format!("{}#{}", self.tag, id);
+        }
+
+        return match self.classes().next() {
+            Some(class) => format!("{}.{}", self.tag, class),
+            None => self.tag.clone(),
+        };
+    }
+
+    /// Matches a css selector list, compound selectors are checked against
+    /// their last part only so `ul .item` is treated as `.item`.
+    pub fn matches(&self, selector: &str) -> bool {
+        return selector.split(',').any(|selector| {
+            let compound = selector
+                .split(|c: char| c.is_whitespace() || c == '>' || c == '+' || c == '~')
+                .rfind(|part| !part.is_empty())
+                .unwrap_or("");
+            return !compound.is_empty() && self.matches_compound(compound);
+        });
+    }
+
+    fn matches_compound(&self, compound: &str) -> bool {
+        let mut parts = vec![];
+        let mut start = 0;
+        for (idx, c) in compound.char_indices() {
+            if idx > 0 && matches!(c, '#' | '.' | '[') && !compound[start..idx].contains('[') {
+                parts.push(&compound[start..idx]);
+                start = idx;
+            } else if c == ']' {
+                parts.push(&compound[start..=idx]);
+                start = idx + 1;
+            }
+        }
+        parts.push(&compound[start..]);
+
+        return parts
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .all(|part| {
+                if let Some(id) = part.strip_prefix('#') {
+                    return self.id() == Some(id);
+                }
+                if let Some(class) = part.strip_prefix('.') {
+                    return self.classes().any(|c| c == class);
+                }
+                if let Some(attribute) = part.strip_prefix('[') {
+                    let attribute = attribute.trim_end_matches(']');
+                    return match attribute.split_once('=') {
+                        Some((name, value)) => {
+                            let value = value.trim_matches(['"', '\'']);
+                            self.attribute_value(name.trim()) == Some(value)
+                        }
+                        None => self.attribute(attribute.trim()).is_some(),
+                    };
+                }
+
+                return part == "*" || part.eq_ignore_ascii_case(&self.tag);
+            });
+    }
+
+    /// How the element is referred to in hints, eg. `<div id=page>`.
+    pub fn opening(&self) -> String {
+        return match self.id() {
+            Some(id) => format!("<{} id={}>", self.tag, id),
+            None => format!("<{}>", self.tag),
+        };
+    }
+}
+
+/// A simple model of the document elements in document order, good enough
+/// to reason about htmx inheritance and targets.
+#[derive(Debug, Default)]
+pub struct Dom {
+    pub elements: Vec<Element>,
+}
+
+fn is_element(node: &Node<'_>) -> bool {
+    return matches!(node.kind(), "element" | "script_element" | "style_element");
+}
+
+impl Dom {
+    pub fn parse(source: &str) -> Self {
+        let mut dom = Dom::default();
+        if let Some(tree) = parse_html(source) {
+            dom.collect(tree.root_node(), source, None);
+        }
+
+        return dom;
+    }
+
+    fn collect(&mut self, node: Node<'_>, source: &str, parent: Option<usize>) {
+        let mut cursor = node.walk();
+
+        for child in node.named_children(&mut cursor) {
+            if !is_element(&child) {
+                self.collect(child, source, parent);
+                continue;
+            }
+
+            let Some(tag) = child
+                .child(0)
+                .filter(|n| n.kind() == "start_tag" || n.kind() == "self_closing_tag")
+            else {
+                self.collect(child, source, parent);
+                continue;
+            };
+            let Some(tag_name) = tag.child(1).filter(|n| n.kind() == "tag_name") else {
+                self.collect(child, source, parent);
+                continue;
+            };
+
+            let idx = self.elements.len();
+            self.elements.push(Element {
+                tag: source[tag_name.byte_range()].to_lowercase(),
+                tag_range: tag_name.range(),
+                start_tag_range: tag.range(),
+                range: child.range(),
+                attributes: query_attributes(tag, source),
+                parent,
+                children: vec![],
+            });
+            if let Some(parent) = parent {
+                self.elements[parent].children.push(idx);
+            }
+
+            self.collect(child, source, Some(idx));
+        }
+    }
+
+    pub fn ancestors(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        return std::iter::successors(self.elements[idx].parent, |&parent| {
+            self.elements[parent].parent
+        });
+    }
+
+    /// The innermost element containing `byte`.
+    pub fn element_at(&self, byte: usize) -> Option<usize> {
+        // Preorder puts descendants after their ancestors
+        return (0..self.elements.len()).rev().find(|&idx| {
+            let range = self.elements[idx].range;
+            return range.start_byte <= byte && byte < range.end_byte;
+        });
+    }
+
+    pub fn is_descendant(&self, idx: usize, ancestor: usize) -> bool {
+        return self.ancestors(idx).any(|idx| idx == ancestor);
+    }
+
+    /// The first element of the document matching `selector`.
+    pub fn query(&self, selector: &str) -> Option<usize> {
+        return (0..self.elements.len()).find(|&idx| self.elements[idx].matches(selector));
+    }
+
+    fn siblings(&self, idx: usize) -> &[usize] {
+        return match self.elements[idx].parent {
+            Some(parent) => &self.elements[parent].children,
+            None => &[],
+        };
+    }
+
+    /// Resolves a target using htmx extended selectors: `this`, `closest`,
+    /// `find`, `next` and `previous`, anything else is a document query.
+    pub fn resolve_target(&self, idx: usize, target: &str) -> Option<usize> {
+        let target = target.trim();
+        let (keyword, selector) = match target.split_once(char::is_whitespace) {
+            Some((keyword, selector)) => (keyword, selector.trim()),
+            None => (target, ""),
+        };
+
+        return match (keyword, selector) {
+            ("this", "") => Some(idx),
+            ("closest", selector) => std::iter::once(idx)
+                .chain(self.ancestors(idx))
+                .find(|&ancestor| self.elements[ancestor].matches(selector)),
+            ("find", selector) => (idx + 1..self.elements.len())
+                .take_while(|&other| self.is_descendant(other, idx))
+                .find(|&other| self.elements[other].matches(selector)),
+            ("next", "") => {
+                let siblings = self.siblings(idx);
+                let position = siblings.iter().position(|&sibling| sibling == idx)?;
+                siblings.get(position + 1).copied()
+            }
+            ("previous" | "prev", "") => {
+                let siblings = self.siblings(idx);
+                let position = siblings.iter().position(|&sibling| sibling == idx)?;
+                siblings.get(position.checked_sub(1)?).copied()
+            }
+            ("next", selector) => (idx + 1..self.elements.len())
+                .filter(|&other| !self.is_descendant(other, idx))
+                .find(|&other| self.elements[other].matches(selector)),
+            ("previous" | "prev", selector) => (0..idx)
+                .rev()
+                .filter(|&other| !self.is_descendant(idx, other))
+                .find(|&other| self.elements[other].matches(selector)),
+            _ => self.query(target),
+        };
+    }
+
+    /// The closest ancestor setting `attribute`, with its value.
+    pub fn inherited(&self, idx: usize, attribute: &str) -> Option<(usize, &str)> {
+        return self.ancestors(idx).find_map(|ancestor| {
+            let value = self.elements[ancestor].attribute_value(attribute)?;
+            return Some((ancestor, value));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Dom;
+
+    #[test]
+    fn test_builds_element_tree() {
+        let text = r##"<div id="page" hx-target="#list">
+  <ul id="list"><li>a</li></ul>
+  <button hx-get="/more">more</button>
+</div>"##;
+
+        let dom = Dom::parse(text);
+
+        let tags: Vec<_> = dom.elements.iter().map(|e| e.tag.as_str()).collect();
+        assert_eq!(tags, vec!["div", "ul", "li", "button"]);
+        assert_eq!(dom.elements[0].children, vec![1, 3]);
+        assert_eq!(dom.ancestors(2).collect::<Vec<_>>(), vec![1, 0]);
+        assert_eq!(dom.inherited(3, "hx-target"), Some((0, "#list")));
+        assert_eq!(dom.elements[0].opening(), "<div id=page>");
+        assert_eq!(dom.element_at(text.find("<li>").unwrap() + 4), Some(2));
+        assert_eq!(dom.element_at(text.find("more").unwrap()), Some(3));
+    }
+
+    #[test]
+    fn test_matches_selectors() {
+        let dom = Dom::parse(r##"<li id="a" class="item done" data-x="1"></li>"##);
+        let li = &dom.elements[0];
+
+        assert!(li.matches("li"));
+        assert!(li.matches("#a"));
+        assert!(li.matches("li.item.done"));
+        assert!(li.matches("ul > .done"));
+        assert!(li.matches("[data-x='1']"));
+        assert!(li.matches("p, li#a"));
+        assert!(!li.matches("li.other"));
+        assert!(!li.matches("div"));
+    }
+
+    #[test]
+    fn test_resolves_extended_selectors() {
+        let text = r##"<table><tr class="row">
+  <td><button hx-get="/a">a</button><span class="spinner"></span></td>
+</tr></table>
+<div id="results"><p class="x"></p></div>"##;
+        let dom = Dom::parse(text);
+        let button = dom.elements.iter().position(|e| e.tag == "button").unwrap();
+        let selector = |target: &str| {
+            let idx = dom.resolve_target(button, target)?;
+            return Some(dom.elements[idx].selector());
+        };
+
+        assert_eq!(selector("this").as_deref(), Some("button"));
+        assert_eq!(selector("closest tr").as_deref(), Some("tr.row"));
+        assert_eq!(selector("next").as_deref(), Some("span.spinner"));
+        assert_eq!(selector("next .x").as_deref(), Some("p.x"));
+        assert_eq!(selector("previous tr").as_deref(), None);
+        assert_eq!(selector("previous table").as_deref(), None);
+        assert_eq!(selector("#results").as_deref(), Some("div#results"));
+        assert_eq!(selector("find p").as_deref(), None);
+    }
+}
@@ -0,0 +1,323 @@
+//! HTML written inside JavaScript and TypeScript, in `html` tagged template
+//! literals and in strings holding markup, served like a template once the
+//! rest of the script is blanked out.
+
+use std::ops::Range;
+
+use lsp_types::Url;
+
+const SCRIPT_LANGUAGES: [&str; 2] = ["javascript", "typescript"];
+
+// JSX files write their markup as code, it is parsed as it is
+const SCRIPT_EXTENSIONS: [&str; 6] = ["js", "mjs", "cjs", "ts", "mts", "cts"];
+
+/// Whether the document is a script whose HTML lives in its strings, by the
+/// language the client opened it with or else by its extension.
+pub fn is_script(uri: &Url, language_id: Option<&str>) -> bool {
+    if let Some(language_id) = language_id {
+        return SCRIPT_LANGUAGES.contains(&language_id);
+    }
+
+    return uri
+        .path()
+        .rsplit_once('.')
+        .is_some_and(|(_, extension)| SCRIPT_EXTENSIONS.contains(&extension));
+}
+
+// Keywords after which a `/` starts a regular expression, not a division
+const REGEX_KEYWORDS: [&str; 12] = [
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "yield",
+    "await",
+];
+
+/// Whether a tag opens in the text of a string.
+fn has_tag(text: &str) -> bool {
+    return text
+        .as_bytes()
+        .windows(2)
+        .any(|pair| pair[0] == b'<' && (pair[1].is_ascii_alphabetic() || pair[1] == b'/'));
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    idx: usize,
+    html: Vec<u8>,
+}
+
+impl Scanner<'_> {
+    fn peek(&self, offset: usize) -> Option<u8> {
+        return self.bytes.get(self.idx + offset).copied();
+    }
+
+    fn skip_past(&mut self, end: &str) {
+        self.idx = match self.source[self.idx..].find(end) {
+            Some(found) => self.idx + found + end.len(),
+            None => self.bytes.len(),
+        };
+    }
+
+    /// Copies the markup of `range` into the HTML, the backslashes of its
+    /// escapes left blank.
+    fn keep(&mut self, range: Range<usize>) {
+        for idx in range {
+            if self.bytes[idx] != b'\\' {
+                self.html[idx] = self.bytes[idx];
+            }
+        }
+    }
+
+    /// Script code up to its end, or up to the `}` closing a template
+    /// expression when `nested`. Strings with markup, and the ones joined
+    /// to them with `+`, are kept.
+    fn script(&mut self, nested: bool) {
+        let mut depth = 0;
+        // Where the last string with markup ended
+        let mut chain: Option<usize> = None;
+
+        while self.idx < self.bytes.len() {
+            let start = self.idx;
+            match self.bytes[start] {
+                b'/' if self.peek(1) == Some(b'/') => self.skip_past("\n"),
+                b'/' if self.peek(1) == Some(b'*') => self.skip_past("*/"),
+                b'/' if self.is_regex_start(start) => self.regex(),
+                b'{' => {
+                    depth += 1;
+                    self.idx += 1;
+                }
+                b'}' if nested && depth == 0 => return,
+                b'}' => {
+                    depth -= 1;
+                    self.idx += 1;
+                }
+                quote @ (b'"' | b'\'' | b'`') => {
+                    let tagged = quote == b'`' && self.is_html_tag(start);
+                    let pieces = match quote {
+                        b'`' => self.template(),
+                        _ => vec![self.string(quote)],
+                    };
+
+                    let joined = chain.is_some_and(|end| {
+                        let between = &self.source[end..start];
+                        return between.contains('+') && !between.contains(';');
+                    });
+                    let markup = pieces
+                        .iter()
+                        .any(|piece| has_tag(&self.source[piece.clone()]));
+                    if tagged || markup || joined {
+                        pieces.into_iter().for_each(|piece| self.keep(piece));
+                        chain = Some(self.idx);
+                    } else {
+                        chain = None;
+                    }
+                }
+                _ => self.idx += 1,
+            }
+        }
+    }
+
+    /// Whether the template literal opening at `start` is tagged `html`,
+    /// like the ones of lit and htm.
+    fn is_html_tag(&self, start: usize) -> bool {
+        let before = self.source[..start].trim_end();
+        let tag_start = before
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.'))
+            .map_or(0, |idx| idx + 1);
+        let tag = &before[tag_start..];
+        return tag == "html" || tag.ends_with(".html");
+    }
+
+    /// Whether the `/` at `start` opens a regular expression literal rather
+    /// than dividing, by what comes before it.
+    fn is_regex_start(&self, start: usize) -> bool {
+        let before = self.source[..start].trim_end();
+        let Some(last) = before.chars().last() else {
+            return true;
+        };
+        if last == ')' || last == ']' {
+            return false;
+        }
+        if !(last.is_ascii_alphanumeric() || last == '_' || last == '$') {
+            return true;
+        }
+
+        let word_start = before
+            .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+            .map_or(0, |idx| idx + 1);
+        return REGEX_KEYWORDS.contains(&&before[word_start..]);
+    }
+
+    /// Skips the regular expression literal at the cursor with its flags,
+    /// quotes in it open no string. One not closed on its line is taken
+    /// for a division.
+    fn regex(&mut self) {
+        let start = self.idx;
+        self.idx += 1;
+        let mut class = false;
+        while self.idx < self.bytes.len() {
+            match self.bytes[self.idx] {
+                b'\\' => self.idx += 2,
+                b'\n' => {
+                    self.idx = start + 1;
+                    return;
+                }
+                b'[' => {
+                    class = true;
+                    self.idx += 1;
+                }
+                b']' => {
+                    class = false;
+                    self.idx += 1;
+                }
+                b'/' if !class => {
+                    self.idx += 1;
+                    while self.peek(0).is_some_and(|byte| byte.is_ascii_alphabetic()) {
+                        self.idx += 1;
+                    }
+                    return;
+                }
+                _ => self.idx += 1,
+            }
+        }
+    }
+
+    /// The text of the quoted string at the cursor, which ends at the line
+    /// when the closing quote is missing.
+    fn string(&mut self, quote: u8) -> Range<usize> {
+        self.idx += 1;
+        let start = self.idx;
+        while self.idx < self.bytes.len() {
+            match self.bytes[self.idx] {
+                b'\\' => self.idx += 2,
+                b'\n' => return start..self.idx,
+                byte if byte == quote => {
+                    self.idx += 1;
+                    return start..self.idx - 1;
+                }
+                _ => self.idx += 1,
+            }
+        }
+
+        self.idx = self.bytes.len();
+        return start..self.idx;
+    }
+
+    /// The text pieces of the template literal at the cursor, between its
+    /// `${}` expressions, which are read as script.
+    fn template(&mut self) -> Vec<Range<usize>> {
+        self.idx += 1;
+        let mut pieces = vec![];
+        let mut start = self.idx;
+        while self.idx < self.bytes.len() {
+            match self.bytes[self.idx] {
+                b'\\' => self.idx += 2,
+                b'`' => {
+                    pieces.push(start..self.idx);
+                    self.idx += 1;
+                    return pieces;
+                }
+                b'$' if self.peek(1) == Some(b'{') => {
+                    pieces.push(start..self.idx);
+                    self.idx += 2;
+                    self.script(true);
+                    self.idx += 1;
+                    start = self.idx.min(self.bytes.len());
+                }
+                _ => self.idx += 1,
+            }
+        }
+
+        self.idx = self.bytes.len();
+        pieces.push(start..self.idx);
+        return pieces;
+    }
+}
+
+/// The HTML of the strings of a script at the place they are written, the
+/// rest blanked out. Newlines and non-ASCII characters stay, so offsets
+/// and positions of the HTML are the ones of the script.
+pub fn embedded_html(source: &str) -> String {
+    let html: Vec<u8> = source
+        .bytes()
+        .map(|byte| match byte.is_ascii() && byte != b'\n' {
+            true => b' ',
+            false => byte,
+        })
+        .collect();
+
+    let mut scanner = Scanner {
+        source,
+        bytes: source.as_bytes(),
+        idx: 0,
+        html,
+    };
+    scanner.script(false);
+
+    // Only ASCII bytes were blanked, the UTF-8 of the rest is untouched
+    return String::from_utf8(scanner.html).unwrap_or_default();
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Url;
+
+    use super::{embedded_html, is_script};
+
+    #[test]
+    fn test_keeps_the_html_of_templates_and_strings_in_place() {
+        let source = r#"const row = html`<tr hx-get="${url}" hx-swap="outerHTML"></tr>`;
+const css = css`.a { color: red }`;
+el.innerHTML = '<div hx-post="' + path + '">' + "é" + label;"#;
+        let html = embedded_html(source);
+
+        assert_eq!(html.len(), source.len());
+        let lines: Vec<&str> = html.lines().collect();
+        assert_eq!(
+            lines[0],
+            r#"                 <tr hx-get="      " hx-swap="outerHTML"></tr>  "#
+        );
+        assert_eq!(lines[1].trim(), "");
+        assert_eq!(lines[2].trim(), r#"<div hx-post="            ">     é"#);
+
+        // Backslashes of escapes are left out, untagged templates count by
+        // their markup
+        let html = embedded_html(r#"x = "<a hx-boost=\"true\">"; y = `<p>${`<b hx-get=/b>`}</p>`"#);
+        assert_eq!(
+            html,
+            r#"     <a hx-boost= "true ">        <p>   <b hx-get=/b>  </p> "#
+        );
+    }
+
+    #[test]
+    fn test_scripts_by_language_or_extension() {
+        let uri = |path: &str| Url::parse(&format!("file:///app/{}", path)).expect("valid uri");
+
+        assert!(is_script(&uri("rows.js"), None));
+        assert!(is_script(&uri("rows.ts"), Some("typescript")));
+        assert!(!is_script(&uri("rows.jsx"), None));
+        assert!(!is_script(&uri("index.html"), None));
+        assert!(!is_script(&uri("rows.js"), Some("html")));
+    }
+
+    #[test]
+    fn test_quotes_in_regular_expressions_open_no_string() {
+        let source =
+            r#"const q = /'/g; const t = s.replace(/[`/]/, ""); el.innerHTML = '<a hx-get="/a">';"#;
+        let html = embedded_html(source);
+        assert_eq!(html.trim(), r#"<a hx-get="/a">"#);
+
+        // Divisions are no regular expressions
+        let source = r#"const half = total / 2; el.innerHTML = '<b hx-get="/b">' + count / 2;"#;
+        assert_eq!(embedded_html(source).trim(), r#"<b hx-get="/b">"#);
+    }
+}
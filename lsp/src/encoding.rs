@@ -0,0 +1,64 @@
+use std::sync::OnceLock;
+
+use lsp_types::{ClientCapabilities, Position, PositionEncodingKind};
+use tree_sitter::Point;
+use util::{byte_pos_to_line_col, get_text_byte_offset, PositionEncoding};
+
+pub static POSITION_ENCODING: OnceLock<PositionEncoding> = OnceLock::new();
+
+/// Picks UTF-8 when the client offers it since tree-sitter works with byte
+/// columns, otherwise falls back to the mandatory UTF-16.
+pub fn init_position_encoding(capabilities: &ClientCapabilities) -> PositionEncodingKind {
+    let supports_utf8 = capabilities
+        .general
+        .as_ref()
+        .and_then(|general| general.position_encodings.as_ref())
+        .map(|encodings| encodings.contains(&PositionEncodingKind::UTF8))
+        .unwrap_or(false);
+
+    let (encoding, kind) = if supports_utf8 {
+        (PositionEncoding::Utf8, PositionEncodingKind::UTF8)
+    } else {
+        (PositionEncoding::Utf16, PositionEncodingKind::UTF16)
+    };
+
+    _ = POSITION_ENCODING.set(encoding);
+    return kind;
+}
+
+pub fn position_encoding() -> PositionEncoding {
+    return POSITION_ENCODING.get().copied().unwrap_or_default();
+}
+
+pub fn position_to_byte_offset(source: &str, position: Position) -> Option<usize> {
+    return get_text_byte_offset(
+        source,
+        position.line as usize,
+        position.character as usize,
+        position_encoding(),
+    );
+}
+
+pub fn byte_offset_to_position(source: &str, byte_offset: usize) -> Position {
+    return byte_pos_to_line_col(source, byte_offset, position_encoding());
+}
+
+/// Converts an LSP position into a tree-sitter point, whose column is
+/// counted in bytes.
+pub fn position_to_point(source: &str, position: Position) -> Point {
+    let row = position.line as usize;
+    let line_start = get_text_byte_offset(source, row, 0, position_encoding());
+
+    return match (line_start, position_to_byte_offset(source, position)) {
+        (Some(line_start), Some(offset)) => Point::new(row, offset - line_start),
+        _ => Point::new(row, position.character as usize),
+    };
+}
+
+/// Converts a tree-sitter range into an LSP range of `source`.
+pub fn ts_range_to_lsp(source: &str, range: tree_sitter::Range) -> lsp_types::Range {
+    return lsp_types::Range {
+        start: byte_offset_to_position(source, range.start_byte),
+        end: byte_offset_to_position(source, range.end_byte),
+    };
+}
@@ -0,0 +1,329 @@
+//! Requests beyond the protocol for editor plugins, namespaced under `htmx/`.
+
+use std::collections::HashMap;
+
+use lsp_types::{Range, TextDocumentIdentifier, TextDocumentPositionParams, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::get_config,
+    dom::Dom,
+    encoding::{position_to_byte_offset, ts_range_to_lsp},
+    htmx::{project_version, HxCompletion, CATALOG_VERSION, HX_ATTRIBUTE_VALUES, HX_TAGS},
+    index::INDEX,
+    links::route_template,
+    symbols::{document_endpoints, workspace_endpoints, Endpoint},
+    text_store::{get_text_document, TEXT_STORE},
+};
+
+pub const RESOLVE_TARGET: &str = "htmx/resolveTarget";
+pub const REQUEST_FLOW: &str = "htmx/requestFlow";
+pub const STATUS: &str = "htmx/status";
+
+/// Where the target of an element comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TargetSource {
+    Attribute,
+    Inherited,
+    /// Without hx-target the element swaps itself
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolveTargetResult {
+    pub range: Range,
+    pub selector: String,
+    pub source: TargetSource,
+}
+
+pub fn resolve_target(source: &str, byte: usize) -> Option<ResolveTargetResult> {
+    let dom = Dom::parse(source);
+    let idx = dom.element_at(byte)?;
+
+    let own = dom.elements[idx].attribute_value("hx-target");
+    let (target, from) = match own {
+        Some(target) => (dom.resolve_target(idx, target)?, TargetSource::Attribute),
+        None => match dom.inherited(idx, "hx-target") {
+            // Inherited targets resolve relative to the element declaring them
+            Some((ancestor, target)) => (
+                dom.resolve_target(ancestor, target)?,
+                TargetSource::Inherited,
+            ),
+            None => (idx, TargetSource::Default),
+        },
+    };
+
+    let element = &dom.elements[target];
+    return Some(ResolveTargetResult {
+        range: ts_range_to_lsp(source, element.range),
+        selector: element.selector(),
+        source: from,
+    });
+}
+
+pub fn hx_resolve_target(text_params: TextDocumentPositionParams) -> Option<ResolveTargetResult> {
+    let text = get_text_document(text_params.text_document.uri)?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    return resolve_target(&text, byte);
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestFlowParams {
+    /// Only this document, the whole workspace when left out
+    pub text_document: Option<TextDocumentIdentifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FlowNode {
+    Element {
+        id: String,
+        uri: Url,
+        range: Range,
+        label: String,
+    },
+    Endpoint {
+        id: String,
+        method: String,
+        url: String,
+    },
+    Template {
+        id: String,
+        uri: Url,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FlowEdgeKind {
+    /// An element issues a request to an endpoint
+    Requests,
+    /// An endpoint answers with a template
+    Renders,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlowEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: FlowEdgeKind,
+}
+
+/// What calls what: elements, the endpoints they request and the templates
+/// answering them when a routes directory is configured.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct RequestFlow {
+    pub nodes: Vec<FlowNode>,
+    pub edges: Vec<FlowEdge>,
+}
+
+impl RequestFlow {
+    fn add_node(&mut self, node: FlowNode) {
+        if !self.nodes.contains(&node) {
+            self.nodes.push(node);
+        }
+    }
+
+    fn add_edge(&mut self, from: String, to: String, kind: FlowEdgeKind) {
+        let edge = FlowEdge { from, to, kind };
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+}
+
+pub fn request_flow(
+    documents: Vec<(Url, Vec<Endpoint>)>,
+    template: impl Fn(&Url, &Endpoint) -> Option<Url>,
+) -> RequestFlow {
+    let mut flow = RequestFlow::default();
+
+    for (uri, endpoints) in documents {
+        for (idx, endpoint) in endpoints.iter().enumerate() {
+            let element = format!("element:{}#{}", uri, idx);
+            let request = format!("endpoint:{} {}", endpoint.method, endpoint.url);
+
+            flow.add_node(FlowNode::Element {
+                id: element.clone(),
+                uri: uri.clone(),
+                range: endpoint.range,
+                label: endpoint.label(),
+            });
+            flow.add_node(FlowNode::Endpoint {
+                id: request.clone(),
+                method: endpoint.method.clone(),
+                url: endpoint.url.clone(),
+            });
+            flow.add_edge(element, request.clone(), FlowEdgeKind::Requests);
+
+            if let Some(template) = template(&uri, endpoint) {
+                let id = format!("template:{}", template);
+                flow.add_node(FlowNode::Template {
+                    id: id.clone(),
+                    uri: template,
+                });
+                flow.add_edge(request, id, FlowEdgeKind::Renders);
+            }
+        }
+    }
+
+    return flow;
+}
+
+pub fn hx_request_flow(params: RequestFlowParams) -> RequestFlow {
+    let documents = match params.text_document {
+        Some(document) => {
+            let text = get_text_document(document.uri.clone()).unwrap_or_default();
+            vec![(document.uri, document_endpoints(&text))]
+        }
+        None => workspace_endpoints(),
+    };
+
+    return request_flow(documents, |uri, endpoint| {
+        return route_template(uri, &endpoint.url);
+    });
+}
+
+/// Rough heap usage in bytes, counting the stored strings only.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryEstimate {
+    pub text_store: usize,
+    pub index: usize,
+    pub catalog: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStatus {
+    pub server_version: String,
+    pub catalog_version: String,
+    /// The htmx version of the project, from the htmxVersion setting or
+    /// its htmx script tag
+    pub htmx_version: Option<String>,
+    pub completion_enabled: bool,
+    pub diagnostics_enabled: bool,
+    pub open_documents: usize,
+    pub indexed_files: usize,
+    pub catalog_attributes: usize,
+    pub memory: MemoryEstimate,
+}
+
+fn texts_size(texts: &HashMap<String, String>) -> usize {
+    return texts.iter().map(|(uri, text)| uri.len() + text.len()).sum();
+}
+
+fn catalog_size(items: &[HxCompletion]) -> usize {
+    return items
+        .iter()
+        .map(|item| item.name.len() + item.desc.len())
+        .sum();
+}
+
+pub fn hx_status() -> ServerStatus {
+    let config = get_config();
+    let mut memory = MemoryEstimate::default();
+
+    let open_documents = match TEXT_STORE.get() {
+        Some(text_store) => {
+            let text_store = text_store.lock().expect("text store mutex poisoned");
+            memory.text_store = texts_size(&text_store.texts);
+            text_store.texts.len()
+        }
+        None => 0,
+    };
+
+    let indexed_files = match INDEX.get() {
+        Some(index) => {
+            let index = index.lock().expect("index mutex poisoned");
+            memory.index = texts_size(&index.files);
+            index.files.len()
+        }
+        None => 0,
+    };
+
+    let tags = HX_TAGS.get().map(Vec::as_slice).unwrap_or_default();
+    memory.catalog = catalog_size(tags)
+        + catalog_size(&config.custom_attributes)
+        + HX_ATTRIBUTE_VALUES.get().map_or(0, |values| {
+            return values.values().map(|items| catalog_size(items)).sum();
+        });
+
+    return ServerStatus {
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        catalog_version: CATALOG_VERSION.to_string(),
+        htmx_version: project_version(),
+        completion_enabled: config.enable_completion,
+        diagnostics_enabled: config.enable_diagnostics,
+        open_documents,
+        indexed_files,
+        catalog_attributes: tags.len() + config.custom_attributes.len(),
+        memory,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{request_flow, resolve_target, texts_size, FlowEdgeKind, TargetSource};
+    use crate::symbols::document_endpoints;
+    use lsp_types::Url;
+
+    #[test]
+    fn test_resolves_own_inherited_and_default_targets() {
+        let text = r##"<div id="page" hx-target="this">
+  <button hx-get="/a">a</button>
+</div>
+<a hx-get="/b" hx-target="#page">b</a>
+<p hx-get="/c">c</p>"##;
+        let at = |needle: &str| {
+            let result = resolve_target(text, text.find(needle).unwrap()).unwrap();
+            return (result.selector, result.source);
+        };
+
+        assert_eq!(at(">a<"), ("div#page".to_string(), TargetSource::Inherited));
+        assert_eq!(at(">b<"), ("div#page".to_string(), TargetSource::Attribute));
+        assert_eq!(at(">c<"), ("p".to_string(), TargetSource::Default));
+    }
+
+    #[test]
+    fn test_builds_request_graph() {
+        let text = r##"<a hx-get="/items">a</a><form hx-post="/items/new"></form><b hx-get="/items"></b>"##;
+        let uri = Url::parse("file:///app/index.html").unwrap();
+        let template = Url::parse("file:///app/templates/items.html").unwrap();
+
+        let flow = request_flow(vec![(uri, document_endpoints(text))], |_, endpoint| {
+            return (endpoint.url == "/items").then(|| template.clone());
+        });
+
+        // Three elements, two endpoints and one template
+        assert_eq!(flow.nodes.len(), 6);
+        let renders: Vec<_> = flow
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == FlowEdgeKind::Renders)
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect();
+        assert_eq!(
+            renders,
+            vec![(
+                "endpoint:GET /items",
+                "template:file:///app/templates/items.html"
+            )]
+        );
+        assert_eq!(flow.edges.len(), 4);
+    }
+
+    #[test]
+    fn test_texts_size_counts_uris_and_texts() {
+        let texts = maplit::hashmap! {
+            "file:///a.html".to_string() => "<div></div>".to_string(),
+            "file:///b.html".to_string() => "".to_string(),
+        };
+
+        assert_eq!(texts_size(&texts), 14 + 11 + 14);
+    }
+}
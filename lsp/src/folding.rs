@@ -0,0 +1,71 @@
+use lsp_types::{FoldingRange, FoldingRangeKind};
+
+use crate::{dom::Dom, encoding::byte_offset_to_position, htmx::htmx_name};
+
+/// Elements with fewer hx-* attributes are readable without folding
+const MIN_HX_ATTRIBUTES: usize = 3;
+
+/// Folds the attribute list of start tags spanning several lines with many
+/// hx-* attributes, leaving the tag name visible.
+pub fn folding_ranges(source: &str) -> Vec<FoldingRange> {
+    return Dom::parse(source)
+        .elements
+        .iter()
+        .filter_map(|element| {
+            let hx_attributes: Vec<&str> = element
+                .attributes
+                .iter()
+                .map(|attribute| attribute.name.as_str())
+                .filter(|name| htmx_name(name).is_some())
+                .collect();
+            if hx_attributes.len() < MIN_HX_ATTRIBUTES {
+                return None;
+            }
+
+            let start = byte_offset_to_position(source, element.tag_range.end_byte);
+            let end = byte_offset_to_position(source, element.start_tag_range.end_byte);
+            if start.line == end.line {
+                return None;
+            }
+
+            return Some(FoldingRange {
+                start_line: start.line,
+                start_character: Some(start.character),
+                end_line: end.line,
+                end_character: Some(end.character),
+                kind: Some(FoldingRangeKind::Region),
+                collapsed_text: Some(format!(" {} ", hx_attributes.join(" "))),
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::folding_ranges;
+
+    #[test]
+    fn test_folds_dense_attribute_lists() {
+        let text = r##"<form
+  hx-post="/search"
+  hx-target="#results"
+  hx-swap="outerHTML">
+  <div
+    hx-get="/a"
+    hx-target="this"></div>
+</form>"##;
+
+        let ranges = folding_ranges(text);
+
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(
+            (ranges[0].start_line, ranges[0].start_character),
+            (0, Some(5))
+        );
+        assert_eq!((ranges[0].end_line, ranges[0].end_character), (3, Some(22)));
+        assert_eq!(
+            ranges[0].collapsed_text.as_deref(),
+            Some(" hx-post hx-target hx-swap ")
+        );
+    }
+}
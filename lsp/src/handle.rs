@@ -1,61 +1,465 @@
-use log::{debug, error, warn};
-use lsp_server::{Message, Notification, Request, RequestId};
-use lsp_types::{CompletionContext, CompletionParams, CompletionTriggerKind};
+use log::{debug, warn};
+use lsp_server::{ErrorCode, Message, Notification, Request, RequestId};
+use lsp_types::{
+    CodeLens, CodeLensParams, CompletionItem, CompletionParams, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
+    DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, DocumentSymbol,
+    DocumentSymbolParams, ExecuteCommandParams, FileChangeType, FoldingRange, FoldingRangeParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, InlayHint, InlayHintParams,
+    LinkedEditingRangeParams, LinkedEditingRanges, Location, PrepareRenameResponse, Range,
+    ReferenceParams, RenameParams, SelectionRange, SelectionRangeParams, SemanticTokens,
+    SemanticTokensParams, SymbolInformation, TextDocumentPositionParams, TextEdit, Url,
+    WillSaveTextDocumentParams, WorkspaceEdit, WorkspaceSymbolParams,
+};
 
 use crate::{
-    htmx::{hx_completion, HxCompletion},
-    text_store::TEXT_STORE,
+    code_lens::code_lenses,
+    commands::execute_command,
+    completion::{
+        cache_completion, cached_completion, cap_completions, clear_completion_cache,
+        completion_key, completion_tag, completion_token, equals_completion,
+        resolve_completion_item, CompletionToken,
+    },
+    config::{get_config, set_config},
+    definition::hx_definition,
+    diagnostics::{
+        document_diagnostic_report, refresh_diagnostics, schedule_diagnostics,
+        DocumentDiagnosticParams, DocumentDiagnosticReport,
+    },
+    extensions::{
+        hx_request_flow, hx_resolve_target, hx_status, RequestFlow, RequestFlowParams,
+        ResolveTargetResult, ServerStatus, REQUEST_FLOW, RESOLVE_TARGET, STATUS,
+    },
+    folding::folding_ranges,
+    hover::hx_hover,
+    htmx::{equals_completion_values, hx_completion, HxCompletion},
+    index::{index_file, index_text, index_workspace, indexed_uris, is_tracked, remove_file},
+    inlay_hints::inlay_hints,
+    json_format::format_json_values,
+    links::hx_document_links,
+    normalize::normalize_edits,
+    on_type::on_type_edits,
+    references::{hx_document_highlight, hx_linked_editing_range, hx_references},
+    rename::{hx_prepare_rename, hx_rename},
+    selection::selection_range,
+    semantic_tokens::semantic_tokens,
+    symbols::{document_symbols, workspace_symbols},
+    text_store::{
+        apply_content_change, get_language_id, get_text_document, is_script_document, TEXT_STORE,
+    },
+    uri::uri_key,
+    workspace::WORKSPACE,
 };
 
-#[derive(serde::Deserialize, Debug)]
-struct Text {
-    text: String,
+#[derive(Debug)]
+pub struct HtmxAttributeCompletion {
+    pub items: Vec<HxCompletion>,
+    /// Set when completing the value of this attribute
+    pub attribute: Option<String>,
+    pub is_incomplete: bool,
+    /// The word under the cursor the items take the place of
+    pub token: Option<CompletionToken>,
+    /// The tag of the element being completed, for ranking
+    pub tag: Option<String>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxCompletionResolve {
+    pub item: Box<CompletionItem>,
+    pub id: RequestId,
 }
 
-#[derive(serde::Deserialize, Debug)]
-struct TextDocumentLocation {
-    uri: String,
+#[derive(Debug)]
+pub struct HtmxDiagnostic {
+    pub report: DocumentDiagnosticReport,
+    pub id: RequestId,
 }
 
-#[derive(serde::Deserialize, Debug)]
-struct TextDocumentChanges {
-    #[serde(rename = "textDocument")]
-    text_document: TextDocumentLocation,
+#[derive(Debug)]
+pub struct HtmxHover {
+    pub hover: Hover,
+    pub id: RequestId,
+}
 
-    #[serde(rename = "contentChanges")]
-    content_changes: Vec<Text>,
+#[derive(Debug)]
+pub struct HtmxDocumentLink {
+    pub links: Vec<DocumentLink>,
+    pub id: RequestId,
 }
 
 #[derive(Debug)]
-pub struct HtmxAttributeCompletion {
-    pub items: Vec<HxCompletion>,
+pub struct HtmxDefinition {
+    pub definition: GotoDefinitionResponse,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxReferences {
+    pub locations: Vec<Location>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxDocumentHighlight {
+    pub highlights: Vec<DocumentHighlight>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxDocumentSymbol {
+    pub symbols: Vec<DocumentSymbol>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxWorkspaceSymbol {
+    pub symbols: Vec<SymbolInformation>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxSemanticTokens {
+    pub tokens: SemanticTokens,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxInlayHint {
+    pub hints: Vec<InlayHint>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxCodeLens {
+    pub lenses: Vec<CodeLens>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxLinkedEditingRange {
+    pub ranges: LinkedEditingRanges,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxSelectionRange {
+    pub ranges: Vec<SelectionRange>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxFoldingRange {
+    pub ranges: Vec<FoldingRange>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxExecuteCommand {
+    pub result: serde_json::Value,
+    pub id: RequestId,
+}
+
+/// A request that failed, answered with an error response.
+#[derive(Debug)]
+pub struct HtmxError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxTextEdits {
+    pub edits: Vec<TextEdit>,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxResolveTarget {
+    pub result: ResolveTargetResult,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxRequestFlow {
+    pub flow: RequestFlow,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxStatus {
+    pub status: ServerStatus,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxPrepareRename {
+    pub response: PrepareRenameResponse,
+    pub id: RequestId,
+}
+
+#[derive(Debug)]
+pub struct HtmxRename {
+    pub edit: WorkspaceEdit,
     pub id: RequestId,
 }
 
 #[derive(Debug)]
 pub enum HtmxResult {
-    // Diagnostic,
+    Diagnostic(HtmxDiagnostic),
     AttributeCompletion(HtmxAttributeCompletion),
+    CompletionResolve(HtmxCompletionResolve),
+    Hover(HtmxHover),
+    DocumentLink(HtmxDocumentLink),
+    Definition(HtmxDefinition),
+    References(HtmxReferences),
+    DocumentHighlight(HtmxDocumentHighlight),
+    DocumentSymbol(HtmxDocumentSymbol),
+    WorkspaceSymbol(HtmxWorkspaceSymbol),
+    SemanticTokens(HtmxSemanticTokens),
+    InlayHint(HtmxInlayHint),
+    CodeLens(HtmxCodeLens),
+    LinkedEditingRange(HtmxLinkedEditingRange),
+    SelectionRange(HtmxSelectionRange),
+    FoldingRange(HtmxFoldingRange),
+    ExecuteCommand(HtmxExecuteCommand),
+    TextEdits(HtmxTextEdits),
+    ResolveTarget(HtmxResolveTarget),
+    RequestFlow(HtmxRequestFlow),
+    Status(HtmxStatus),
+    Error(HtmxError),
+    PrepareRename(HtmxPrepareRename),
+    Rename(HtmxRename),
 }
 
 // ignore snakeCase
 #[allow(non_snake_case)]
 fn handle_didChange(noti: Notification) -> Option<HtmxResult> {
-    let text_document_changes: TextDocumentChanges = serde_json::from_value(noti.params).ok()?;
-    let uri = text_document_changes.text_document.uri;
-    let text = text_document_changes.content_changes[0].text.to_string();
+    let text_document_changes: DidChangeTextDocumentParams =
+        serde_json::from_value(noti.params).ok()?;
+    let text_document = text_document_changes.text_document;
+    let uri = uri_key(&text_document.uri);
+
+    let mut text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+
+    text_store
+        .versions
+        .insert(uri.clone(), text_document.version);
+    text_store
+        .uris
+        .entry(uri.clone())
+        .or_insert(text_document.uri.clone());
+
+    // Changes are relative to the document after the previous change was
+    // applied, so they must be replayed in the order the client sent them
+    let text = text_store.texts.entry(uri).or_default();
+    for change in text_document_changes.content_changes {
+        apply_content_change(text, change);
+    }
+
+    schedule_diagnostics(text_document.uri);
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didOpen(noti: Notification) -> Option<HtmxResult> {
+    let text_document_open: DidOpenTextDocumentParams = serde_json::from_value(noti.params).ok()?;
+    let text_document = text_document_open.text_document;
+    let uri = uri_key(&text_document.uri);
+
+    let mut text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+
+    text_store
+        .language_ids
+        .insert(uri.clone(), text_document.language_id);
+    text_store
+        .versions
+        .insert(uri.clone(), text_document.version);
+    text_store
+        .uris
+        .insert(uri.clone(), text_document.uri.clone());
+    text_store.texts.insert(uri, text_document.text);
+    drop(text_store);
 
-    if text_document_changes.content_changes.len() > 1 {
-        error!("more than one content change, please be wary");
+    // A reopened document starts over at its first version, with a text
+    // the cached candidates may not have been computed from
+    clear_completion_cache();
+    schedule_diagnostics(text_document.uri);
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didClose(noti: Notification) -> Option<HtmxResult> {
+    let text_document_close: DidCloseTextDocumentParams =
+        serde_json::from_value(noti.params).ok()?;
+
+    let uri = text_document_close.text_document.uri;
+    let key = uri_key(&uri);
+
+    let mut text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+
+    text_store.texts.remove(&key);
+    text_store.language_ids.remove(&key);
+    text_store.versions.remove(&key);
+    text_store.uris.remove(&key);
+    drop(text_store);
+    clear_completion_cache();
+
+    // Publishing for a document that is gone clears its diagnostics
+    schedule_diagnostics(uri);
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didSave(noti: Notification) -> Option<HtmxResult> {
+    let text_document_save: DidSaveTextDocumentParams = serde_json::from_value(noti.params).ok()?;
+
+    // We ask for the text on save since some clients are unreliable with
+    // their didChange notifications, the saved content is the source of truth
+    let uri = text_document_save.text_document.uri;
+    let key = uri_key(&uri);
+
+    if let Some(text) = text_document_save.text.clone() {
+        let mut text_store = TEXT_STORE
+            .get()
+            .expect("text store not initialized")
+            .lock()
+            .expect("text store mutex poisoned");
+
+        // The version the client sent no longer describes the text, it
+        // comes back with the next change
+        if text_store.texts.get(&key) != Some(&text) {
+            text_store.versions.remove(&key);
+        }
+        text_store.texts.insert(key, text);
     }
+    clear_completion_cache();
+
+    let in_workspace = WORKSPACE
+        .get()
+        .expect("workspace not initialized")
+        .lock()
+        .expect("workspace mutex poisoned")
+        .folder_for(&uri)
+        .is_some();
+    match uri.to_file_path() {
+        Ok(path) if in_workspace && is_tracked(&path) => match text_document_save.text {
+            Some(text) => index_text(&path, text),
+            None => index_file(&path),
+        },
+        _ => {}
+    }
+
+    schedule_diagnostics(uri);
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didChangeConfiguration(noti: Notification) -> Option<HtmxResult> {
+    let configuration_change: DidChangeConfigurationParams =
+        serde_json::from_value(noti.params).ok()?;
+
+    let config = get_config().merge_settings(configuration_change.settings);
+    debug!("configuration changed: {:?}", config);
+    set_config(config);
+    clear_completion_cache();
 
-    TEXT_STORE
+    // Settings like enableDiagnostics change what every open document reports
+    let text_store = TEXT_STORE
         .get()
         .expect("text store not initialized")
         .lock()
-        .expect("text store mutex poisoned")
+        .expect("text store mutex poisoned");
+    let uris: Vec<Url> = text_store
         .texts
-        .insert(uri, text);
+        .keys()
+        .filter_map(|key| text_store.uri(key))
+        .collect();
+    drop(text_store);
+
+    uris.into_iter().for_each(schedule_diagnostics);
+    refresh_diagnostics();
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didChangeWorkspaceFolders(noti: Notification) -> Option<HtmxResult> {
+    let folders_change: DidChangeWorkspaceFoldersParams =
+        serde_json::from_value(noti.params).ok()?;
+
+    let mut workspace = WORKSPACE
+        .get()
+        .expect("workspace not initialized")
+        .lock()
+        .expect("workspace mutex poisoned");
+
+    let added = folders_change.event.added.clone();
+    workspace.apply_change(folders_change.event);
+    debug!("workspace folders changed: {:?}", workspace.folders);
+
+    // Files of removed folders stay indexed when another folder holds them
+    let outside: Vec<Url> = indexed_uris()
+        .into_iter()
+        .filter(|uri| workspace.folder_for(uri).is_none())
+        .collect();
+    drop(workspace);
+    outside.iter().for_each(remove_file);
+    clear_completion_cache();
+
+    // Read off the main loop like the first indexing, without progress as
+    // the first indexing may still be reporting on its token
+    if !added.is_empty() {
+        std::thread::spawn(move || index_workspace(added, false));
+    }
+
+    return None;
+}
+
+#[allow(non_snake_case)]
+fn handle_didChangeWatchedFiles(noti: Notification) -> Option<HtmxResult> {
+    let watched_files: DidChangeWatchedFilesParams = serde_json::from_value(noti.params).ok()?;
+    clear_completion_cache();
+
+    let workspace = WORKSPACE
+        .get()
+        .expect("workspace not initialized")
+        .lock()
+        .expect("workspace mutex poisoned");
+
+    for change in watched_files.changes {
+        if change.typ == FileChangeType::DELETED {
+            remove_file(&change.uri);
+            continue;
+        }
+
+        if workspace.folder_for(&change.uri).is_none() {
+            continue;
+        }
+
+        match change.uri.to_file_path() {
+            Ok(path) if is_tracked(&path) => index_file(&path),
+            _ => {}
+        }
+    }
 
     return None;
 }
@@ -64,53 +468,393 @@ fn handle_didChange(noti: Notification) -> Option<HtmxResult> {
 fn handle_completion(req: Request) -> Option<HtmxResult> {
     let completion: CompletionParams = serde_json::from_value(req.params).ok()?;
 
-    error!("handle_completion: {:?}", completion);
+    debug!("handle_completion: {:?}", completion);
 
-    match completion.context {
-        Some(CompletionContext {
-            trigger_kind: CompletionTriggerKind::TRIGGER_CHARACTER,
-            ..
-        })
-        | Some(CompletionContext {
-            trigger_kind: CompletionTriggerKind::INVOKED,
-            ..
-        }) => {
-            let items = match hx_completion(completion.text_document_position) {
-                Some(items) => items,
-                None => {
-                    error!("EMPTY RESULTS OF COMPLETION");
-                    return None;
-                }
+    let config = get_config();
+    if !config.enable_completion {
+        return None;
+    }
+
+    let uri = &completion.text_document_position.text_document.uri;
+    if let Some(language_id) = get_language_id(uri) {
+        if !config.is_language_enabled(&language_id) {
+            debug!("completion disabled for language {}", language_id);
+            return None;
+        }
+    }
+
+    // Re-queries for incomplete lists and clients sending no context are
+    // answered like an invoked completion, narrowed to what is typed now
+    let trigger = completion
+        .context
+        .as_ref()
+        .and_then(|context| context.trigger_character.as_deref());
+
+    if trigger == Some("=") {
+        let (attribute, token) = equals_completion(&completion.text_document_position)?;
+        let (items, attribute) =
+            equals_completion_values(&completion.text_document_position, &attribute)?;
+        let (items, is_incomplete) = cap_completions(items, "");
+        return Some(HtmxResult::AttributeCompletion(HtmxAttributeCompletion {
+            items,
+            attribute,
+            is_incomplete,
+            token: Some(token),
+            tag: completion_tag(&completion.text_document_position),
+            id: req.id,
+        }));
+    }
+
+    let token = completion_token(&completion.text_document_position);
+    let key = completion_key(&completion.text_document_position);
+    let (items, attribute, tag) = match key.as_ref().and_then(cached_completion) {
+        Some(cached) => cached,
+        None => {
+            let tag = completion_tag(&completion.text_document_position);
+            let Some((items, attribute)) = hx_completion(completion.text_document_position) else {
+                debug!("EMPTY RESULTS OF COMPLETION");
+                return None;
             };
+            if let Some(key) = key {
+                cache_completion(key, (items.clone(), attribute.clone(), tag.clone()));
+            }
+            (items, attribute, tag)
+        }
+    };
+
+    debug!(
+        "handled result: {:?}: completion result: {:?}",
+        completion.context, items
+    );
+
+    let prefix = token.as_ref().map_or("", |token| token.prefix.as_str());
+    let (items, is_incomplete) = cap_completions(items, prefix);
+    return Some(HtmxResult::AttributeCompletion(HtmxAttributeCompletion {
+        items,
+        attribute,
+        is_incomplete,
+        token,
+        tag,
+        id: req.id,
+    }));
+}
+
+fn handle_completion_resolve(req: Request) -> Option<HtmxResult> {
+    let item: CompletionItem = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::CompletionResolve(HtmxCompletionResolve {
+        item: Box::new(resolve_completion_item(item)),
+        id: req.id,
+    }));
+}
+
+fn handle_hover(req: Request) -> Option<HtmxResult> {
+    let params: HoverParams = serde_json::from_value(req.params).ok()?;
+    let hover = hx_hover(params.text_document_position_params)?;
+
+    return Some(HtmxResult::Hover(HtmxHover { hover, id: req.id }));
+}
+
+fn handle_document_link(req: Request) -> Option<HtmxResult> {
+    let params: DocumentLinkParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::DocumentLink(HtmxDocumentLink {
+        links: hx_document_links(params.text_document.uri),
+        id: req.id,
+    }));
+}
+
+fn handle_definition(req: Request) -> Option<HtmxResult> {
+    let params: GotoDefinitionParams = serde_json::from_value(req.params).ok()?;
+    let definition = hx_definition(params.text_document_position_params)?;
+
+    return Some(HtmxResult::Definition(HtmxDefinition {
+        definition,
+        id: req.id,
+    }));
+}
+
+fn handle_references(req: Request) -> Option<HtmxResult> {
+    let params: ReferenceParams = serde_json::from_value(req.params).ok()?;
 
-            error!("handled result: {:?}: completion result: {:?}", completion.context, items);
+    return Some(HtmxResult::References(HtmxReferences {
+        locations: hx_references(params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_document_highlight(req: Request) -> Option<HtmxResult> {
+    let params: DocumentHighlightParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::DocumentHighlight(HtmxDocumentHighlight {
+        highlights: hx_document_highlight(params.text_document_position_params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_document_symbol(req: Request) -> Option<HtmxResult> {
+    let params: DocumentSymbolParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri)?;
+
+    return Some(HtmxResult::DocumentSymbol(HtmxDocumentSymbol {
+        symbols: document_symbols(&text),
+        id: req.id,
+    }));
+}
+
+fn handle_workspace_symbol(req: Request) -> Option<HtmxResult> {
+    let params: WorkspaceSymbolParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::WorkspaceSymbol(HtmxWorkspaceSymbol {
+        symbols: workspace_symbols(&params.query),
+        id: req.id,
+    }));
+}
+
+fn handle_semantic_tokens(req: Request) -> Option<HtmxResult> {
+    let params: SemanticTokensParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri)?;
+
+    return Some(HtmxResult::SemanticTokens(HtmxSemanticTokens {
+        tokens: semantic_tokens(&text),
+        id: req.id,
+    }));
+}
+
+fn handle_inlay_hint(req: Request) -> Option<HtmxResult> {
+    let params: InlayHintParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri)?;
 
-            return Some(HtmxResult::AttributeCompletion(HtmxAttributeCompletion {
-                items,
+    return Some(HtmxResult::InlayHint(HtmxInlayHint {
+        hints: inlay_hints(&text, params.range),
+        id: req.id,
+    }));
+}
+
+fn handle_code_lens(req: Request) -> Option<HtmxResult> {
+    let params: CodeLensParams = serde_json::from_value(req.params).ok()?;
+    let uri = params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+
+    return Some(HtmxResult::CodeLens(HtmxCodeLens {
+        lenses: code_lenses(&uri, &text),
+        id: req.id,
+    }));
+}
+
+fn handle_execute_command(req: Request) -> Option<HtmxResult> {
+    let params: ExecuteCommandParams = serde_json::from_value(req.params).ok()?;
+
+    return match execute_command(&params.command, params.arguments) {
+        Ok(result) => Some(HtmxResult::ExecuteCommand(HtmxExecuteCommand {
+            result,
+            id: req.id,
+        })),
+        Err(e) => {
+            warn!("command {} failed: {:?}", params.command, e);
+            Some(HtmxResult::Error(HtmxError {
+                code: ErrorCode::InvalidParams,
+                message: e.to_string(),
                 id: req.id,
-            }));
-        }
-        _ => {
-            error!("unhandled completion context: {:?}", completion.context);
-            return None;
+            }))
         }
     };
 }
 
+fn handle_linked_editing_range(req: Request) -> Option<HtmxResult> {
+    let params: LinkedEditingRangeParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::LinkedEditingRange(HtmxLinkedEditingRange {
+        ranges: hx_linked_editing_range(params.text_document_position_params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_selection_range(req: Request) -> Option<HtmxResult> {
+    let params: SelectionRangeParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri)?;
+
+    // The result has one entry per requested position, fall back to an
+    // empty range so the entries keep lining up
+    let ranges = params
+        .positions
+        .into_iter()
+        .map(|position| {
+            return selection_range(&text, position).unwrap_or(SelectionRange {
+                range: Range::new(position, position),
+                parent: None,
+            });
+        })
+        .collect();
+
+    return Some(HtmxResult::SelectionRange(HtmxSelectionRange {
+        ranges,
+        id: req.id,
+    }));
+}
+
+fn handle_folding_range(req: Request) -> Option<HtmxResult> {
+    let params: FoldingRangeParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri)?;
+
+    return Some(HtmxResult::FoldingRange(HtmxFoldingRange {
+        ranges: folding_ranges(&text),
+        id: req.id,
+    }));
+}
+
+#[allow(non_snake_case)]
+fn handle_willSaveWaitUntil(req: Request) -> Option<HtmxResult> {
+    let params: WillSaveTextDocumentParams = serde_json::from_value(req.params).ok()?;
+    let normalize = get_config().normalize_on_save?;
+    if is_script_document(&params.text_document.uri) {
+        return None;
+    }
+    let text = get_text_document(params.text_document.uri)?;
+
+    return Some(HtmxResult::TextEdits(HtmxTextEdits {
+        edits: normalize_edits(&text, &normalize),
+        id: req.id,
+    }));
+}
+
+fn handle_range_formatting(req: Request) -> Option<HtmxResult> {
+    let params: DocumentRangeFormattingParams = serde_json::from_value(req.params).ok()?;
+    if is_script_document(&params.text_document.uri) {
+        return None;
+    }
+    let text = get_text_document(params.text_document.uri)?;
+    let style = get_config().json_style;
+
+    return Some(HtmxResult::TextEdits(HtmxTextEdits {
+        edits: format_json_values(&text, params.range, &params.options, style),
+        id: req.id,
+    }));
+}
+
+fn handle_on_type_formatting(req: Request) -> Option<HtmxResult> {
+    let params: DocumentOnTypeFormattingParams = serde_json::from_value(req.params).ok()?;
+    let text_params = params.text_document_position;
+    if is_script_document(&text_params.text_document.uri) {
+        return None;
+    }
+    let text = get_text_document(text_params.text_document.uri)?;
+
+    return Some(HtmxResult::TextEdits(HtmxTextEdits {
+        edits: on_type_edits(&text, text_params.position, &params.ch),
+        id: req.id,
+    }));
+}
+
+fn handle_resolve_target(req: Request) -> Option<HtmxResult> {
+    let params: TextDocumentPositionParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::ResolveTarget(HtmxResolveTarget {
+        result: hx_resolve_target(params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_request_flow(req: Request) -> Option<HtmxResult> {
+    let params: RequestFlowParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::RequestFlow(HtmxRequestFlow {
+        flow: hx_request_flow(params),
+        id: req.id,
+    }));
+}
+
+fn handle_status(req: Request) -> Option<HtmxResult> {
+    return Some(HtmxResult::Status(HtmxStatus {
+        status: hx_status(),
+        id: req.id,
+    }));
+}
+
+fn handle_prepare_rename(req: Request) -> Option<HtmxResult> {
+    let params: TextDocumentPositionParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::PrepareRename(HtmxPrepareRename {
+        response: hx_prepare_rename(params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_rename(req: Request) -> Option<HtmxResult> {
+    let params: RenameParams = serde_json::from_value(req.params).ok()?;
+
+    return Some(HtmxResult::Rename(HtmxRename {
+        edit: hx_rename(params)?,
+        id: req.id,
+    }));
+}
+
+fn handle_diagnostic(req: Request) -> Option<HtmxResult> {
+    let params: DocumentDiagnosticParams = serde_json::from_value(req.params).ok()?;
+    let text = get_text_document(params.text_document.uri).unwrap_or_default();
+
+    let report = if get_config().enable_diagnostics {
+        document_diagnostic_report(&text, params.previous_result_id)
+    } else {
+        document_diagnostic_report("", params.previous_result_id)
+    };
+
+    return Some(HtmxResult::Diagnostic(HtmxDiagnostic {
+        report,
+        id: req.id,
+    }));
+}
+
 pub fn handle_request(req: Request) -> Option<HtmxResult> {
-    error!("handle_request");
+    debug!("handle_request");
     match req.method.as_str() {
         "textDocument/completion" => handle_completion(req),
+        "completionItem/resolve" => handle_completion_resolve(req),
+        "textDocument/hover" => handle_hover(req),
+        "textDocument/documentLink" => handle_document_link(req),
+        "textDocument/definition" => handle_definition(req),
+        "textDocument/references" => handle_references(req),
+        "textDocument/documentHighlight" => handle_document_highlight(req),
+        "textDocument/linkedEditingRange" => handle_linked_editing_range(req),
+        "textDocument/selectionRange" => handle_selection_range(req),
+        "textDocument/foldingRange" => handle_folding_range(req),
+        "textDocument/documentSymbol" => handle_document_symbol(req),
+        "workspace/symbol" => handle_workspace_symbol(req),
+        "textDocument/semanticTokens/full" => handle_semantic_tokens(req),
+        "textDocument/inlayHint" => handle_inlay_hint(req),
+        "textDocument/codeLens" => handle_code_lens(req),
+        "workspace/executeCommand" => handle_execute_command(req),
+        "textDocument/willSaveWaitUntil" => handle_willSaveWaitUntil(req),
+        "textDocument/rangeFormatting" => handle_range_formatting(req),
+        "textDocument/onTypeFormatting" => handle_on_type_formatting(req),
+        RESOLVE_TARGET => handle_resolve_target(req),
+        REQUEST_FLOW => handle_request_flow(req),
+        STATUS => handle_status(req),
+        "textDocument/prepareRename" => handle_prepare_rename(req),
+        "textDocument/rename" => handle_rename(req),
+        "textDocument/diagnostic" => handle_diagnostic(req),
+        // Known methods answer null when they have nothing to offer, the
+        // others aren't supported at all
         _ => {
             warn!("unhandled request: {:?}", req);
-            None
+            Some(HtmxResult::Error(HtmxError {
+                code: ErrorCode::MethodNotFound,
+                message: format!("unsupported method {}", req.method),
+                id: req.id,
+            }))
         }
     }
 }
 
 pub fn handle_notification(noti: Notification) -> Option<HtmxResult> {
     return match noti.method.as_str() {
+        "textDocument/didOpen" => handle_didOpen(noti),
         "textDocument/didChange" => handle_didChange(noti),
+        "textDocument/didSave" => handle_didSave(noti),
+        "textDocument/didClose" => handle_didClose(noti),
+        "workspace/didChangeConfiguration" => handle_didChangeConfiguration(noti),
+        "workspace/didChangeWorkspaceFolders" => handle_didChangeWorkspaceFolders(noti),
+        "workspace/didChangeWatchedFiles" => handle_didChangeWatchedFiles(noti),
         s => {
             debug!("unhandled notification: {:?}", s);
             None
@@ -122,3 +866,143 @@ pub fn handle_other(msg: Message) -> Option<HtmxResult> {
     warn!("unhandled message {:?}", msg);
     return None;
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_server::{ErrorCode, Request, RequestId};
+    use lsp_types::{CompletionContext, CompletionTriggerKind, Url};
+    use serde_json::json;
+
+    use super::{handle_completion, handle_request, HtmxResult};
+    use crate::{
+        completion::{to_completion_list, MAX_COMPLETION_ITEMS},
+        config::{init_config, HtmxConfig},
+        htmx::init_hx_tags,
+        index::init_index,
+        text_store::{init_text_store, TEXT_STORE},
+        uri::uri_key,
+        usage::Usage,
+    };
+
+    #[test]
+    fn test_requeries_of_incomplete_lists_narrow_them_down() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        init_index();
+
+        let ids: String = (0..MAX_COMPLETION_ITEMS * 2)
+            .map(|idx| format!(r#"<p id="item-{}"></p>"#, idx))
+            .collect();
+        let text = format!(
+            r##"<div hx-target="#item"></div><div hx-target="#item-19"></div>{}"##,
+            ids
+        );
+        let uri = Url::parse("file:///requery.html").expect("valid uri");
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(uri_key(&uri), text.clone());
+
+        let complete = |typed: &str, context: Option<CompletionContext>| {
+            let character = text.find(typed).expect("typed") + typed.len();
+            let params = json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 0, "character": character },
+                "context": context,
+            });
+            let req = Request::new(RequestId::from(1), "textDocument/completion".into(), params);
+            return match handle_completion(req) {
+                Some(HtmxResult::AttributeCompletion(completion)) => {
+                    Some((completion.items.len(), completion.is_incomplete))
+                }
+                _ => None,
+            };
+        };
+
+        let invoked = CompletionContext {
+            trigger_kind: CompletionTriggerKind::INVOKED,
+            trigger_character: None,
+        };
+        assert_eq!(
+            complete(r##""#item"##, Some(invoked)),
+            Some((MAX_COMPLETION_ITEMS, true))
+        );
+
+        let requery = CompletionContext {
+            trigger_kind: CompletionTriggerKind::TRIGGER_FOR_INCOMPLETE_COMPLETIONS,
+            trigger_character: None,
+        };
+        assert_eq!(complete("#item-19", Some(requery)), Some((11, false)));
+        assert_eq!(complete("#item-19", None), Some((11, false)));
+    }
+
+    #[test]
+    fn test_fuzzy_lists_are_asked_again_as_typing_goes_on() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        init_index();
+
+        let text = "<div hxtg></div><p hxtgt></p>";
+        let uri = Url::parse("file:///fuzzy.html").expect("valid uri");
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(uri_key(&uri), text.to_string());
+
+        let complete = |typed: &str, trigger_kind: CompletionTriggerKind| {
+            let character = text.find(typed).expect("typed") + typed.len();
+            let params = json!({
+                "textDocument": { "uri": uri },
+                "position": { "line": 0, "character": character },
+                "context": { "triggerKind": trigger_kind },
+            });
+            let req = Request::new(RequestId::from(1), "textDocument/completion".into(), params);
+            let Some(HtmxResult::AttributeCompletion(completion)) = handle_completion(req) else {
+                return None;
+            };
+            let list = to_completion_list(
+                completion.items,
+                completion.attribute,
+                completion.is_incomplete,
+                completion.token,
+                completion.tag.as_deref(),
+                &Usage::default(),
+            );
+            let labels: Vec<_> = list.items.into_iter().map(|item| item.label).collect();
+            return Some((
+                labels.contains(&"hx-target".to_string()),
+                list.is_incomplete,
+            ));
+        };
+
+        // The fuzzy list is incomplete, so asking again keeps offering it
+        assert_eq!(
+            complete("hxtg", CompletionTriggerKind::INVOKED),
+            Some((true, true))
+        );
+        assert_eq!(
+            complete(
+                "hxtgt",
+                CompletionTriggerKind::TRIGGER_FOR_INCOMPLETE_COMPLETIONS
+            ),
+            Some((true, true))
+        );
+    }
+
+    #[test]
+    fn test_unsupported_methods_are_not_found() {
+        let req = Request::new(RequestId::from(1), "textDocument/unknown".into(), json!({}));
+        let Some(HtmxResult::Error(error)) = handle_request(req) else {
+            panic!("expected an error for an unsupported method");
+        };
+        assert_eq!(error.code as i32, ErrorCode::MethodNotFound as i32);
+    }
+}
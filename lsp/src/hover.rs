@@ -0,0 +1,167 @@
+use lsp_types::{
+    Hover, HoverContents, MarkupContent, MarkupKind, Range, TextDocumentPositionParams,
+};
+
+use crate::{
+    encoding::{byte_offset_to_position, position_to_byte_offset, ts_range_to_lsp},
+    htmx::{attribute_extension, extensions_at, hx_documentation, project_version, version_note},
+    text_store::get_text_document,
+    tree_sitter::parse_html,
+    tree_sitter_querier::{query_attributes, AttributeNode},
+};
+
+fn markdown_hover(value: String, range: Range) -> Hover {
+    return Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(range),
+    };
+}
+
+/// Documentation for a single value token, modifiers like `delay:500ms` are
+/// documented under their `delay:` prefix and event filters like
+/// `click[ctrlKey]` under the bare event.
+fn token_documentation(attribute: &str, token: &str) -> Option<String> {
+    let modifier = token.find(':').map(|idx| &token[..=idx]);
+    let event = token.find('[').map(|idx| &token[..idx]);
+
+    return [Some(token), modifier, event]
+        .into_iter()
+        .flatten()
+        .find_map(|name| hx_documentation(Some(attribute), name));
+}
+
+fn value_hover(source: &str, attribute: &AttributeNode, byte: usize) -> Option<Hover> {
+    let (start, token) = attribute.value_token_at(byte)?;
+    let mut desc = token_documentation(&attribute.name, token)?;
+    if let Some(note) = version_note(&attribute.name, Some(token), project_version().as_deref()) {
+        desc = format!("{}\n\n**{}**", desc, note);
+    }
+
+    let range = Range {
+        start: byte_offset_to_position(source, start),
+        end: byte_offset_to_position(source, start + token.len()),
+    };
+    return Some(markdown_hover(desc, range));
+}
+
+/// Hover over an hx-* attribute name shows its bundled documentation, over
+/// one of its values the documentation of that specific token.
+pub fn hover_at(source: &str, byte: usize) -> Option<Hover> {
+    let tree = parse_html(source)?;
+    let attribute = query_attributes(tree.root_node(), source)
+        .into_iter()
+        .find(|attribute| {
+            let name = attribute.name_range;
+            let value = attribute.value_range.unwrap_or(name);
+            return name.start_byte <= byte && byte <= value.end_byte;
+        })?;
+
+    // Extension attributes mean nothing where their extension isn't loaded
+    if let Some(extension) = attribute_extension(&attribute.name) {
+        let loaded = extensions_at(source, attribute.name_range.start_byte);
+        if !loaded.iter().any(|loaded| loaded == extension) {
+            return None;
+        }
+    }
+
+    let name = attribute.name_range;
+    if byte <= name.end_byte {
+        let mut desc = hx_documentation(None, &attribute.name)?;
+        if let Some(note) = version_note(&attribute.name, None, project_version().as_deref()) {
+            desc = format!("{}\n\n**{}**", desc, note);
+        }
+        return Some(markdown_hover(desc, ts_range_to_lsp(source, name)));
+    }
+
+    return value_hover(source, &attribute, byte);
+}
+
+pub fn hx_hover(text_params: TextDocumentPositionParams) -> Option<Hover> {
+    let text = get_text_document(text_params.text_document.uri)?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    return hover_at(&text, byte);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::hover_at;
+    use crate::config::{init_config, HtmxConfig};
+    use crate::htmx::{hx_documentation, init_hx_tags};
+    use lsp_types::{HoverContents, Position, Range};
+
+    fn hover_text(text: &str, byte: usize) -> Option<(String, Option<Range>)> {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let hover = hover_at(text, byte)?;
+        let HoverContents::Markup(contents) = hover.contents else {
+            panic!("expected markdown contents");
+        };
+        return Some((contents.value, hover.range));
+    }
+
+    #[test]
+    fn test_hover_on_attribute_name() {
+        let text = r##"<div id="a" hx-swap="outerHTML"></div>"##;
+
+        let (value, range) = hover_text(text, 15).expect("hover on hx-swap");
+
+        assert!(value.starts_with("hx-swap"));
+        assert_eq!(
+            range,
+            Some(Range::new(Position::new(0, 12), Position::new(0, 19)))
+        );
+    }
+
+    #[test]
+    fn test_no_hover_on_plain_attributes() {
+        let text = r##"<div id="a" hx-swap="outerHTML"></div>"##;
+
+        assert_eq!(hover_text(text, 6), None);
+        assert_eq!(hover_text(text, 9), None);
+    }
+
+    #[test]
+    fn test_hover_on_value_token() {
+        let text = r##"<div hx-swap="outerHTML"></div>"##;
+
+        let (value, range) = hover_text(text, 16).expect("hover on outerHTML");
+
+        assert_eq!(Some(value), hx_documentation(Some("hx-swap"), "outerHTML"));
+        assert_eq!(
+            range,
+            Some(Range::new(Position::new(0, 14), Position::new(0, 23)))
+        );
+    }
+
+    #[test]
+    fn test_hover_on_modifier_token() {
+        let text = r##"<input hx-trigger="keyup changed delay:500ms">"##;
+        let delay = text.find("delay").expect("has delay");
+
+        let (value, range) = hover_text(text, delay + 8).expect("hover on delay");
+
+        assert_eq!(Some(value), hx_documentation(Some("hx-trigger"), "delay:"));
+        assert_eq!(
+            range,
+            Some(Range::new(
+                Position::new(0, delay as u32),
+                Position::new(0, delay as u32 + 11)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_hover_on_extension_attributes_once_loaded() {
+        let loaded = r##"<div hx-ext="remove-me"><p remove-me="1s"></p></div>"##;
+        let (value, _) = hover_text(loaded, 29).expect("hover on remove-me");
+        assert!(value.starts_with("The remove-me attribute"));
+
+        let plain = r##"<div><p remove-me="1s"></p></div>"##;
+        assert_eq!(hover_text(plain, 10), None);
+    }
+}
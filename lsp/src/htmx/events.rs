@@ -0,0 +1,254 @@
+//! DOM and htmx event names, for the places where events are written.
+
+use super::HxCompletion;
+
+/// Common DOM events, with what fires them
+pub const DOM_EVENTS: [(&str, &str); 14] = [
+    ("click", "the element is clicked"),
+    ("dblclick", "the element is double clicked"),
+    (
+        "change",
+        "the value of an input, select or textarea is committed",
+    ),
+    ("input", "the value of an input or textarea changes"),
+    ("submit", "a form is submitted"),
+    ("reset", "a form is reset"),
+    ("keydown", "a key is pressed"),
+    ("keyup", "a key is released"),
+    ("focus", "the element receives focus"),
+    ("blur", "the element loses focus"),
+    ("mouseenter", "the pointer moves onto the element"),
+    ("mouseleave", "the pointer moves off the element"),
+    ("load", "the element or page finished loading"),
+    ("scroll", "the element is scrolled"),
+];
+
+/// The htmx lifecycle events, in the camelCase htmx documents them with
+pub const HTMX_EVENTS: [(&str, &str); 32] = [
+    ("abort", "send it to an element to abort its request"),
+    (
+        "afterOnLoad",
+        "after an AJAX request has completed processing a successful response",
+    ),
+    ("afterProcessNode", "after htmx has initialized a node"),
+    ("afterRequest", "after an AJAX request has completed"),
+    ("afterSettle", "after the DOM has settled"),
+    ("afterSwap", "after the new content has been swapped in"),
+    (
+        "beforeCleanupElement",
+        "before htmx disables an element or removes it from the DOM",
+    ),
+    (
+        "beforeHistorySave",
+        "before the content is saved to the history cache",
+    ),
+    ("beforeOnLoad", "before any response processing occurs"),
+    ("beforeProcessNode", "before htmx initializes a node"),
+    ("beforeRequest", "before an AJAX request is made"),
+    ("beforeSend", "just before an AJAX request is sent"),
+    (
+        "beforeSwap",
+        "before a swap is done, allows you to configure the swap",
+    ),
+    (
+        "configRequest",
+        "before the request, allows you to customize parameters, headers",
+    ),
+    (
+        "confirm",
+        "after a trigger occurs on an element, allows you to cancel or confirm the request",
+    ),
+    (
+        "historyCacheMiss",
+        "on a cache miss in the history subsystem",
+    ),
+    (
+        "historyCacheMissError",
+        "on an unsuccessful remote retrieval of history content",
+    ),
+    (
+        "historyCacheMissLoad",
+        "on a successful remote retrieval of history content",
+    ),
+    (
+        "historyRestore",
+        "when htmx handles a history restoration action",
+    ),
+    ("load", "when new content is added to the DOM"),
+    (
+        "oobAfterSwap",
+        "after an out of band element has been swapped in",
+    ),
+    (
+        "oobBeforeSwap",
+        "before an out of band element swap is done",
+    ),
+    (
+        "oobErrorNoTarget",
+        "when an out of band element does not have a matching id",
+    ),
+    ("prompt", "after a prompt is shown"),
+    ("pushedIntoHistory", "after an url is pushed into history"),
+    (
+        "responseError",
+        "when an HTTP response error (non-200 or 300 response code) occurs",
+    ),
+    (
+        "sendError",
+        "when a network error prevents an HTTP request from happening",
+    ),
+    ("swapError", "when an error occurs during the swap phase"),
+    ("targetError", "when an invalid target is specified"),
+    ("timeout", "when a request timeout occurs"),
+    ("validation:validate", "before an element is validated"),
+    ("validation:failed", "when an element fails validation"),
+];
+
+const EVENTS_URL: &str = "https://htmx.org/events/";
+
+/// `afterSwap` as `after-swap`, attribute names lose their case so hx-on
+/// needs the kebab-case form.
+pub fn kebab_case(event: &str) -> String {
+    let mut kebab = String::with_capacity(event.len() + 4);
+    for c in event.chars() {
+        if c.is_ascii_uppercase() {
+            kebab.push('-');
+        }
+        kebab.push(c.to_ascii_lowercase());
+    }
+
+    return kebab;
+}
+
+/// What fires the htmx event `event`, written with or without the `htmx:`
+/// prefix and in either case.
+pub fn htmx_event_description(event: &str) -> Option<String> {
+    let event = event.strip_prefix("htmx:").unwrap_or(event);
+    let (name, desc) = HTMX_EVENTS
+        .iter()
+        .find(|(name, _)| *name == event || kebab_case(name) == event)?;
+
+    return Some(format!(
+        "htmx:{} - triggered {}\n\n\n[HTMX Reference]({}#htmx:{})",
+        name, desc, EVENTS_URL, name
+    ));
+}
+
+pub fn dom_event_description(event: &str) -> Option<String> {
+    let (name, desc) = DOM_EVENTS.iter().find(|(name, _)| *name == event)?;
+
+    return Some(format!("{} - fires when {}", name, desc));
+}
+
+/// The htmx events as `htmx:<event>`, the way triggers listen to them.
+pub fn htmx_event_completions() -> Vec<HxCompletion> {
+    return HTMX_EVENTS
+        .iter()
+        .map(|(name, _)| {
+            let name = format!("htmx:{}", name);
+            return HxCompletion {
+                desc: htmx_event_description(&name).unwrap_or_default(),
+                name,
+                insert_text: None,
+            };
+        })
+        .collect();
+}
+
+/// `hx-on:*` attribute names for the event typed so far after `hx-on:`,
+/// `hx-on::` is the shorthand for the htmx events and once `htmx:` is typed
+/// only those are left.
+pub fn hx_on_completions(event: &str) -> Vec<HxCompletion> {
+    let htmx = HTMX_EVENTS.iter().map(|(name, _)| kebab_case(name));
+    if event.starts_with(':') {
+        return htmx
+            .map(|name| hx_on_completion(format!("hx-on::{}", name)))
+            .collect();
+    }
+    if event.starts_with("htmx:") {
+        return htmx
+            .map(|name| hx_on_completion(format!("hx-on:htmx:{}", name)))
+            .collect();
+    }
+
+    return DOM_EVENTS
+        .iter()
+        .map(|(name, _)| hx_on_completion(format!("hx-on:{}", name)))
+        .chain(htmx.map(|name| hx_on_completion(format!("hx-on:htmx:{}", name))))
+        .collect();
+}
+
+fn hx_on_completion(name: String) -> HxCompletion {
+    let desc = hx_on_documentation(&name).unwrap_or_default();
+    return HxCompletion {
+        name,
+        desc,
+        insert_text: None,
+    };
+}
+
+/// The description of an `hx-on:*` attribute name, from the event it
+/// handles.
+pub fn hx_on_documentation(name: &str) -> Option<String> {
+    let event = name.strip_prefix("hx-on:")?;
+    let desc = match event.strip_prefix(':') {
+        Some(event) => htmx_event_description(event)?,
+        None if event.starts_with("htmx:") => htmx_event_description(event)?,
+        None => dom_event_description(event)?,
+    };
+
+    return Some(format!(
+        "Runs the inline script when the event fires on the element.\n\n{}",
+        desc
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        htmx_event_completions, htmx_event_description, hx_on_completions, hx_on_documentation,
+        kebab_case,
+    };
+
+    #[test]
+    fn test_htmx_events_in_both_cases() {
+        assert_eq!(kebab_case("beforeRequest"), "before-request");
+        assert_eq!(kebab_case("validation:failed"), "validation:failed");
+
+        assert_eq!(
+            htmx_event_description("htmx:after-swap"),
+            htmx_event_description("afterSwap")
+        );
+        assert!(htmx_event_description("htmx:nope").is_none());
+
+        assert!(htmx_event_completions()
+            .iter()
+            .any(|item| item.name == "htmx:configRequest" && item.desc.contains("customize")));
+    }
+
+    #[test]
+    fn test_hx_on_names_after_the_colon() {
+        let names: Vec<_> = hx_on_completions("cl")
+            .into_iter()
+            .map(|x| x.name)
+            .collect();
+        assert!(names.contains(&"hx-on:click".to_string()));
+        assert!(names.contains(&"hx-on:htmx:before-request".to_string()));
+
+        let names: Vec<_> = hx_on_completions(":").into_iter().map(|x| x.name).collect();
+        assert!(names.contains(&"hx-on::after-swap".to_string()));
+        assert!(!names.contains(&"hx-on::click".to_string()));
+
+        let names: Vec<_> = hx_on_completions("htmx:before-")
+            .into_iter()
+            .map(|x| x.name)
+            .collect();
+        assert!(names.contains(&"hx-on:htmx:before-swap".to_string()));
+        assert!(!names.contains(&"hx-on:click".to_string()));
+
+        assert!(hx_on_documentation("hx-on::before-request")
+            .is_some_and(|desc| desc.contains("htmx:beforeRequest")));
+        assert!(hx_on_documentation("hx-on:submit").is_some_and(|desc| desc.contains("form")));
+        assert!(hx_on_documentation("hx-on:nope").is_none());
+    }
+}
@@ -0,0 +1,218 @@
+//! Attributes the htmx extensions bring, they only mean something below an
+//! element loading the extension with `hx-ext`.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use super::{
+    canonical_name, HxCompletion, EXTENSION_ATTRIBUTES, EXTENSION_CORE_VALUES, EXTENSION_PREFIXES,
+    EXTENSION_VALUES,
+};
+use crate::{dom::Dom, encoding::position_to_byte_offset, text_store::get_text_document};
+use lsp_types::TextDocumentPositionParams;
+
+pub const HEAD_SUPPORT: &str = "head-support";
+
+/// Attributes of each extension, keyed by extension name
+pub static EXTENSION_TAGS: OnceLock<HashMap<String, Vec<HxCompletion>>> = OnceLock::new();
+/// Values of the extension attributes, and the ones extensions bring to
+/// htmx attributes, keyed by attribute name
+pub static EXTENSION_ATTRIBUTE_VALUES: OnceLock<HashMap<String, Vec<HxCompletion>>> =
+    OnceLock::new();
+
+pub fn init_extension_tags() {
+    let mut tags: HashMap<String, Vec<HxCompletion>> = HashMap::new();
+    for (extension, name, desc) in EXTENSION_ATTRIBUTES.iter() {
+        tags.entry(extension.to_string())
+            .or_default()
+            .push(HxCompletion::from(&(*name, *desc)));
+    }
+    _ = EXTENSION_TAGS.set(tags);
+
+    let mut values: HashMap<String, Vec<HxCompletion>> = HashMap::new();
+    for (_, attribute, name, desc) in EXTENSION_VALUES.iter().chain(EXTENSION_CORE_VALUES.iter()) {
+        values
+            .entry(attribute.to_string())
+            .or_default()
+            .push(HxCompletion::from(&(*name, *desc)));
+    }
+    _ = EXTENSION_ATTRIBUTE_VALUES.set(values);
+}
+
+/// The names of every extension attribute, whichever extension brings it.
+pub fn extension_attribute_names() -> impl Iterator<Item = &'static str> {
+    return EXTENSION_ATTRIBUTES.iter().map(|(_, name, _)| *name);
+}
+
+/// The extension bringing the attribute `name`, by its name or the prefix
+/// of its family.
+pub fn attribute_extension(name: &str) -> Option<&'static str> {
+    let name = canonical_name(name);
+    return EXTENSION_ATTRIBUTES
+        .iter()
+        .find(|(_, attribute, _)| *attribute == name)
+        .or_else(|| {
+            return EXTENSION_PREFIXES
+                .iter()
+                .find(|(_, prefix, _)| name.starts_with(prefix));
+        })
+        .map(|(extension, _, _)| *extension);
+}
+
+/// Whether the element `idx` is the `<head>` or inside it.
+pub fn in_head(dom: &Dom, idx: usize) -> bool {
+    return std::iter::once(idx)
+        .chain(dom.ancestors(idx))
+        .any(|element| dom.elements[element].tag == "head");
+}
+
+/// The extensions loaded at the element `idx` by its own hx-ext or the one
+/// of an ancestor, leaving out the ones a closer `ignore:` drops.
+/// head-support reads the `<head>` of the responses, where no hx-ext loads
+/// it, so it counts as loaded inside `<head>` and nowhere else.
+pub fn loaded_extensions(dom: &Dom, idx: usize) -> Vec<String> {
+    let mut loaded: Vec<String> = vec![];
+    let mut ignored: Vec<String> = vec![];
+
+    for element in std::iter::once(idx).chain(dom.ancestors(idx)) {
+        let Some(value) = dom.elements[element].attribute_value("hx-ext") else {
+            continue;
+        };
+
+        for name in value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            match name.strip_prefix("ignore:") {
+                Some(name) => ignored.push(name.trim().to_string()),
+                None if ignored
+                    .iter()
+                    .chain(loaded.iter())
+                    .any(|other| other == name) => {}
+                None => loaded.push(name.to_string()),
+            }
+        }
+    }
+
+    loaded.retain(|name| name != HEAD_SUPPORT);
+    if in_head(dom, idx) && !ignored.iter().any(|name| name == HEAD_SUPPORT) {
+        loaded.push(HEAD_SUPPORT.to_string());
+    }
+
+    return loaded;
+}
+
+/// The extensions loaded at `byte` of `source`.
+pub fn extensions_at(source: &str, byte: usize) -> Vec<String> {
+    let dom = Dom::parse(source);
+    return match dom.element_at(byte) {
+        Some(idx) => loaded_extensions(&dom, idx),
+        None => vec![],
+    };
+}
+
+/// The extensions loaded where the completion is asked for.
+pub fn position_extensions(text_params: &TextDocumentPositionParams) -> Vec<String> {
+    let Some(text) = get_text_document(text_params.text_document.uri.clone()) else {
+        return vec![];
+    };
+
+    return match position_to_byte_offset(&text, text_params.position) {
+        Some(byte) => extensions_at(&text, byte),
+        None => vec![],
+    };
+}
+
+/// The attributes of the `extensions`, in the order they are loaded.
+pub fn extension_attributes(extensions: &[String]) -> Vec<HxCompletion> {
+    let Some(tags) = EXTENSION_TAGS.get() else {
+        return vec![];
+    };
+
+    return extensions
+        .iter()
+        .filter_map(|extension| tags.get(extension))
+        .flatten()
+        .cloned()
+        .collect();
+}
+
+/// The bundled values of an extension attribute.
+pub fn extension_values(attribute: &str) -> Option<Vec<HxCompletion>> {
+    return EXTENSION_ATTRIBUTE_VALUES.get()?.get(attribute).cloned();
+}
+
+/// The values the `extensions` bring to the htmx `attribute`, like the
+/// `multi:` swap of multi-swap.
+pub fn extension_core_values(extensions: &[String], attribute: &str) -> Vec<HxCompletion> {
+    return EXTENSION_CORE_VALUES
+        .iter()
+        .filter(|(extension, name, _, _)| {
+            return *name == attribute && extensions.iter().any(|loaded| loaded == extension);
+        })
+        .map(|(_, _, value, desc)| HxCompletion::from(&(*value, *desc)))
+        .collect();
+}
+
+/// The documentation of an extension attribute, loaded or not.
+pub fn extension_documentation(name: &str) -> Option<String> {
+    return EXTENSION_ATTRIBUTES
+        .iter()
+        .find(|(_, attribute, _)| *attribute == name)
+        .or_else(|| {
+            return EXTENSION_PREFIXES
+                .iter()
+                .find(|(_, prefix, _)| name.starts_with(prefix));
+        })
+        .map(|(_, _, desc)| desc.to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{attribute_extension, extension_attributes, extensions_at, init_extension_tags};
+
+    #[test]
+    fn test_extensions_are_loaded_by_the_element_and_its_ancestors() {
+        let text = r##"<body hx-ext="preload, remove-me">
+  <div hx-ext="ignore:preload,path-deps"><a id="inner"></a></div>
+  <a id="outer"></a>
+</body>"##;
+
+        let inner = text.find("inner").expect("inner");
+        assert_eq!(extensions_at(text, inner), vec!["path-deps", "remove-me"]);
+
+        let outer = text.find("outer").expect("outer");
+        assert_eq!(extensions_at(text, outer), vec!["preload", "remove-me"]);
+
+        assert!(extensions_at("<a></a>", 1).is_empty());
+    }
+
+    #[test]
+    fn test_head_support_is_loaded_inside_head_only() {
+        let text = r##"<html><head><script id="tracking"></script></head>
+<body hx-ext="head-support"><a id="link"></a></body></html>"##;
+
+        let script = text.find("tracking").expect("tracking");
+        assert_eq!(extensions_at(text, script), vec!["head-support"]);
+        let link = text.find("link").expect("link");
+        assert!(extensions_at(text, link).is_empty());
+    }
+
+    #[test]
+    fn test_attributes_of_the_loaded_extensions() {
+        init_extension_tags();
+
+        let names: Vec<_> = extension_attributes(&["preload".to_string(), "json-enc".to_string()])
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(names, vec!["preload", "preload-images"]);
+
+        assert_eq!(attribute_extension("remove-me"), Some("remove-me"));
+        assert_eq!(attribute_extension("hx-get"), None);
+        assert_eq!(
+            attribute_extension("data-hx-target-403"),
+            Some("response-targets")
+        );
+    }
+}
@@ -1,15 +1,46 @@
-use log::{debug, error};
+use log::debug;
 use lsp_types::TextDocumentPositionParams;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::PathBuf, sync::OnceLock};
-use util::get_text_byte_offset;
 
-use crate::tree_sitter::Position;
+use crate::{
+    config::get_config,
+    dom::Dom,
+    encoding::position_to_byte_offset,
+    text_store::get_text_document,
+    tree_sitter::{is_raw_text_at, Position},
+};
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+mod events;
+mod extension;
+mod reference;
+mod values;
+mod version;
+
+use events::{hx_on_completions, hx_on_documentation};
+pub use extension::{
+    attribute_extension, extension_attribute_names, extensions_at, in_head, loaded_extensions,
+    HEAD_SUPPORT,
+};
+use extension::{
+    extension_attributes, extension_documentation, extension_values, init_extension_tags,
+    position_extensions, EXTENSION_ATTRIBUTE_VALUES,
+};
+pub use reference::{completion_reference, snippet_reference, Reference};
+pub use values::{
+    classes_problem, head_values, multi_swap_problem, swap_problem, HYPERSCRIPT_ATTRIBUTES,
+};
+use values::{value_before, value_completions, value_documentation};
+pub use version::project_version;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct HxCompletion {
     pub name: String,
+    #[serde(alias = "description")]
     pub desc: String,
+    /// What gets written in place of the name, when the two differ
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insert_text: Option<String>,
 }
 
 impl From<&(&str, &str)> for HxCompletion {
@@ -17,6 +48,7 @@ impl From<&(&str, &str)> for HxCompletion {
         Self {
             name: name.to_string(),
             desc: desc.to_string(),
+            insert_text: None,
         }
     }
 }
@@ -26,189 +58,654 @@ impl TryFrom<&(PathBuf, String)> for HxCompletion {
 
     fn try_from((path, desc): &(PathBuf, String)) -> Result<Self, Self::Error> {
         let name = path.to_str().unwrap_or("").to_string();
-        if name == "" {
+        if name.is_empty() {
             return Err(anyhow::anyhow!("Invalid path"));
         }
         return Ok(Self {
             name,
             desc: desc.to_string(),
+            insert_text: None,
         });
     }
 }
 
-pub fn hx_completion(text_params: TextDocumentPositionParams) -> Option<Vec<HxCompletion>> {
+/// Completion candidates at the given position, along with the attribute
+/// they are values of when completing inside an attribute value.
+pub fn hx_completion(
+    text_params: TextDocumentPositionParams,
+) -> Option<(Vec<HxCompletion>, Option<String>)> {
     let result = crate::tree_sitter::get_position_from_lsp_completion(text_params.clone())?;
 
     debug!("result: {:?} params: {:?}", result, text_params);
 
     match result {
+        Position::AttributeName(name) if is_alpine_attribute(&name) => return None,
         Position::AttributeName(name) => {
-            if name.starts_with("hx-") {
-                return HX_TAGS.get().cloned();
+            // Extension attributes have prefixes of their own, like `ws-`,
+            // and only exist below an element loading their extension
+            let present = present_attributes(&text_params);
+            let extension_tags = extension_attributes(&position_extensions(&text_params));
+            let extension_matches: Vec<_> = extension_tags
+                .iter()
+                .filter(|tag| !name.is_empty() && tag.name.starts_with(name.as_str()))
+                .filter(|tag| !tag.name.starts_with("hx-"))
+                .filter(|tag| !present.contains(&tag.name))
+                .cloned()
+                .collect();
+            // `data-` is also on its way to `data-hx-*`, both get offered
+            if !extension_matches.is_empty()
+                && htmx_name(&name).is_none()
+                && !"data-hx-".starts_with(name.as_str())
+            {
+                return Some((extension_matches, None));
+            }
+
+            // Strict HTML users write `data-hx-*`, offered once `data-h` is typed
+            let (data, name) = match name.strip_prefix("data-") {
+                Some(name) if "hx".starts_with(name) || name.starts_with("hx") => (true, name),
+                Some(_) => return None,
+                None => (false, name.as_str()),
+            };
+            let with_data = |items: Vec<HxCompletion>| {
+                return items
+                    .into_iter()
+                    .map(|item| match data {
+                        true => HxCompletion {
+                            name: format!("data-{}", item.name),
+                            desc: item.desc,
+                            insert_text: item.insert_text,
+                        },
+                        false => item,
+                    })
+                    .collect::<Vec<_>>();
+            };
+
+            // An element can't have the same attribute twice, and the
+            // project htmx version may not have it at all
+            let version = project_version();
+            let with_data = |items: Vec<HxCompletion>| {
+                return with_data(items)
+                    .into_iter()
+                    .filter(|item| !present.contains(&canonical_name(&item.name).to_string()))
+                    .filter(|item| is_available(&item.name, None, version.as_deref()))
+                    .collect::<Vec<_>>();
+            };
+
+            if let Some(event) = name.strip_prefix("hx-on:") {
+                return Some((with_data(hx_on_completions(event)), None));
+            }
+
+            // After the colon of other names the attribute names don't fit,
+            // `hx` without its dash starts a fuzzy name like `hxtg`
+            if (data || name.starts_with("hx")) && !name.contains(':') {
+                let mut tags: Vec<_> = HX_TAGS.get()?.clone();
+                tags.extend(get_config().custom_attributes);
+                tags.extend(
+                    extension_tags
+                        .into_iter()
+                        .filter(|tag| tag.name.starts_with("hx-")),
+                );
+                let mut tags = with_data(tags);
+                tags.extend(extension_matches);
+                return Some((tags, None));
             }
         }
 
+        Position::AttributeValue { name, .. } if is_alpine_attribute(&name) => return None,
         Position::AttributeValue { name, .. } => {
-            let values = HX_ATTRIBUTE_VALUES.get()?.get(&name)?;
-            return Some(values.clone());
+            let text = get_text_document(text_params.text_document.uri.clone())?;
+            let byte = position_to_byte_offset(&text, text_params.position).unwrap_or_default();
+            let before = value_before(&text, byte);
+            return attribute_value_completion(&text_params, &text, byte, &name, &before);
         }
     };
 
     return None;
 }
 
+/// Value candidates right after the `=` of an attribute, before any quote
+/// or value is typed.
+pub fn equals_completion_values(
+    text_params: &TextDocumentPositionParams,
+    attribute: &str,
+) -> Option<(Vec<HxCompletion>, Option<String>)> {
+    let text = get_text_document(text_params.text_document.uri.clone())?;
+    if is_alpine_attribute(attribute) || is_raw_text_at(&text, text_params.position) {
+        return None;
+    }
+
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+    return attribute_value_completion(text_params, &text, byte, attribute, "");
+}
+
+/// The candidates for the value of the attribute, given what of it is
+/// typed before the position.
+fn attribute_value_completion(
+    text_params: &TextDocumentPositionParams,
+    text: &str,
+    byte: usize,
+    name: &str,
+    before: &str,
+) -> Option<(Vec<HxCompletion>, Option<String>)> {
+    let name = canonical_name(name).to_string();
+
+    if let Some(extension) = attribute_extension(&name) {
+        if !extensions_at(text, byte)
+            .iter()
+            .any(|loaded| loaded == extension)
+        {
+            return None;
+        }
+    }
+
+    let mut values =
+        match value_completions(&name, before, text, byte, &text_params.text_document.uri) {
+            Some(values) => values,
+            None => hx_attribute_values(&name).or_else(|| extension_values(&name))?,
+        };
+    let version = project_version();
+    values.retain(|value| is_available(&name, Some(&value.name), version.as_deref()));
+    return Some((values, Some(name)));
+}
+
+/// The canonical names of the attributes the element at the position
+/// already has, leaving out the one being typed.
+fn present_attributes(text_params: &TextDocumentPositionParams) -> Vec<String> {
+    let Some(text) = get_text_document(text_params.text_document.uri.clone()) else {
+        return vec![];
+    };
+    let Some(byte) = position_to_byte_offset(&text, text_params.position) else {
+        return vec![];
+    };
+
+    let dom = Dom::parse(&text);
+    let Some(idx) = dom.element_at(byte) else {
+        return vec![];
+    };
+
+    return dom.elements[idx]
+        .attributes
+        .iter()
+        .filter(|attribute| {
+            let range = attribute.name_range;
+            return byte < range.start_byte || range.end_byte < byte;
+        })
+        .map(|attribute| canonical_name(&attribute.name).to_string())
+        .collect();
+}
+
+/// The bundled values of an attribute the project htmx version has.
+pub fn hx_attribute_values(attribute: &str) -> Option<Vec<HxCompletion>> {
+    let version = project_version();
+    let mut values = HX_ATTRIBUTE_VALUES.get()?.get(attribute)?.clone();
+    values.retain(|value| is_available(attribute, Some(&value.name), version.as_deref()));
+
+    return Some(values);
+}
+
+/// The bundled description of an attribute, or of one of its values.
+pub fn hx_documentation(attribute: Option<&str>, name: &str) -> Option<String> {
+    let attribute = attribute.map(canonical_name);
+    let name = match attribute {
+        Some(_) => name,
+        None => canonical_name(name),
+    };
+    let find = |items: &Vec<HxCompletion>| {
+        return items
+            .iter()
+            .find(|item| item.name == name)
+            .map(|item| item.desc.clone());
+    };
+
+    return match attribute {
+        Some(attribute) => HX_ATTRIBUTE_VALUES
+            .get()?
+            .get(attribute)
+            .and_then(find)
+            .or_else(|| {
+                EXTENSION_ATTRIBUTE_VALUES
+                    .get()?
+                    .get(attribute)
+                    .and_then(find)
+            })
+            .or_else(|| value_documentation(attribute, name)),
+        None => find(HX_TAGS.get()?)
+            .or_else(|| find(&get_config().custom_attributes))
+            .or_else(|| hx_on_documentation(name))
+            .or_else(|| extension_documentation(name)),
+    };
+}
+
+/// The attribute name without its data- prefix, eg. `hx-get` for both
+/// `hx-get` and `data-hx-get`.
+pub fn htmx_name(name: &str) -> Option<&str> {
+    let name = name.strip_prefix("data-").unwrap_or(name);
+    return name.starts_with("hx-").then_some(name);
+}
+
+/// `htmx_name` for htmx attributes, any other name as it is.
+pub fn canonical_name(name: &str) -> &str {
+    return htmx_name(name).unwrap_or(name);
+}
+
+/// Alpine.js attributes, many htmx projects use both: `x-*` directives and
+/// the `@event` and `:attribute` shorthands.
+const ALPINE_PREFIXES: [&str; 3] = ["x-", "@", ":"];
+
+/// Whether `name` belongs to Alpine.js, its expressions are none of our
+/// business.
+pub fn is_alpine_attribute(name: &str) -> bool {
+    return ALPINE_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+}
+
+/// Whether `name` is one of the bundled hx-* attributes.
+pub fn is_core_attribute(name: &str) -> bool {
+    let name = canonical_name(name);
+    return HX_TAGS
+        .get()
+        .is_some_and(|tags| tags.iter().any(|tag| tag.name == name));
+}
+
+/// The htmx attribute names htmx, the extensions and the settings bring,
+/// whatever the project version or the extensions loaded.
+pub fn known_attribute_names() -> Vec<String> {
+    let custom = get_config().custom_attributes;
+    return HX_TAGS
+        .get()
+        .into_iter()
+        .flatten()
+        .map(|tag| tag.name.clone())
+        .chain(extension_attribute_names().map(str::to_string))
+        .chain(custom.into_iter().map(|tag| tag.name))
+        .filter(|name| htmx_name(name).is_some())
+        .collect();
+}
+
+/// Whether the htmx attribute `name` is known, event handlers in both the
+/// colon and the dash form of `hx-on` included.
+pub fn is_known_attribute(name: &str) -> bool {
+    let name = canonical_name(name);
+    let handler = name.starts_with("hx-on:") || name.starts_with("hx-on-");
+    return handler
+        || attribute_extension(name).is_some()
+        || known_attribute_names().iter().any(|known| known == name);
+}
+
+/// An attribute htmx moved away from, `removed` is the release that dropped
+/// it from core.
+#[derive(Debug, PartialEq)]
+pub struct Deprecation {
+    pub name: &'static str,
+    pub since: &'static str,
+    pub removed: Option<&'static str>,
+    pub replacement: &'static str,
+}
+
+/// An attribute, or a value of one when `value` is set, htmx added after
+/// 1.0. Names ending with a colon stand for every name they start.
+#[derive(Debug, PartialEq)]
+pub struct Addition {
+    pub name: &'static str,
+    pub value: Option<&'static str>,
+    pub since: &'static str,
+}
+
+fn addition(name: &str, value: Option<&str>) -> Option<&'static Addition> {
+    let name = canonical_name(name);
+    return ADDED_ATTRIBUTES.iter().find(|addition| {
+        let matches_name = match addition.name.ends_with(':') {
+            true => name.starts_with(addition.name),
+            false => name == addition.name,
+        };
+        return matches_name && addition.value == value;
+    });
+}
+
+fn parse_version(version: &str) -> [u32; 3] {
+    let mut parts = version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0));
+
+    return [(); 3].map(|_| parts.next().unwrap_or(0));
+}
+
+/// Whether the project `version` is `release` or newer.
+pub fn is_at_least(version: &str, release: &str) -> bool {
+    return parse_version(version) >= parse_version(release);
+}
+
+/// The deprecation of `name` as of the project htmx version, projects that
+/// don't set one are taken to be on the latest release.
+pub fn deprecation(name: &str, version: Option<&str>) -> Option<&'static Deprecation> {
+    return DEPRECATED_ATTRIBUTES
+        .iter()
+        .find(|deprecation| deprecation.name == canonical_name(name))
+        .filter(|deprecation| version.is_none_or(|v| is_at_least(v, deprecation.since)));
+}
+
+/// Whether `name` no longer exists in the project htmx version.
+pub fn is_removed(name: &str, version: Option<&str>) -> bool {
+    return match (deprecation(name, version), version) {
+        (
+            Some(Deprecation {
+                removed: Some(removed),
+                ..
+            }),
+            Some(version),
+        ) => is_at_least(version, removed),
+        _ => false,
+    };
+}
+
+/// Whether the project `version` has the attribute `name`, or its `value`
+/// when set, projects that don't set one are taken to be on the latest
+/// release.
+pub fn is_available(name: &str, value: Option<&str>, version: Option<&str>) -> bool {
+    if value.is_none() && is_removed(name, version) {
+        return false;
+    }
+
+    return match (addition(name, value), version) {
+        (Some(addition), Some(version)) => is_at_least(version, addition.since),
+        _ => true,
+    };
+}
+
+/// Why the attribute `name`, or its `value`, doesn't fit the project htmx
+/// version: deprecated, removed or not there yet.
+pub fn version_note(name: &str, value: Option<&str>, version: Option<&str>) -> Option<String> {
+    if value.is_none() {
+        if let Some(deprecation) = deprecation(name, version) {
+            return Some(match is_removed(name, version) {
+                true => format!(
+                    "`{}` was removed in htmx {}, use {}",
+                    name,
+                    deprecation.removed.unwrap_or_default(),
+                    deprecation.replacement
+                ),
+                false => format!(
+                    "`{}` is deprecated since htmx {}, use {}",
+                    name, deprecation.since, deprecation.replacement
+                ),
+            });
+        }
+    }
+
+    let addition = addition(name, value)?;
+    let version = version.filter(|version| !is_at_least(version, addition.since))?;
+    return Some(format!(
+        "`{}` needs htmx {}, the project uses {}",
+        value.unwrap_or(name),
+        addition.since,
+        version
+    ));
+}
+
+// CATALOG_VERSION, the attributes with their documentation and values and
+// when htmx added or deprecated them, see build.rs
+include!(concat!(env!("OUT_DIR"), "/catalog.rs"));
+
 pub static HX_TAGS: OnceLock<Vec<HxCompletion>> = OnceLock::new();
 pub static HX_ATTRIBUTE_VALUES: OnceLock<HashMap<String, Vec<HxCompletion>>> = OnceLock::new();
 
 fn to_hx_completion(values: Vec<(&str, &str)>) -> Vec<HxCompletion> {
-    return values
-        .iter()
-        .filter_map(|x| x.try_into().ok())
-        .collect();
+    return values.iter().map(|x| x.into()).collect();
 }
 
 pub fn init_hx_tags() {
     _ = HX_ATTRIBUTE_VALUES.set(
-        maplit::hashmap!{
-            String::from("hx-swap") => to_hx_completion(vec![
-                ("innerHTML", include_str!("./hx-swap/innerHTML.md")),
-                ("outerHTML", include_str!("./hx-swap/outerHTML.md")),
-                ("afterbegin", include_str!("./hx-swap/afterbegin.md")),
-                ("afterend", include_str!("./hx-swap/afterend.md")),
-                ("beforebegin", include_str!("./hx-swap/beforebegin.md")),
-                ("beforeend", include_str!("./hx-swap/beforeend.md")),
-                ("delete", include_str!("./hx-swap/delete.md")),
-                ("none", include_str!("./hx-swap/none.md")),
-            ]),
-
-            String::from("hx-target") => to_hx_completion(vec![
-                ("closest", include_str!("./hx-target/closest.md")),
-                ("find", include_str!("./hx-target/find.md")),
-                ("next", include_str!("./hx-target/next.md")),
-                ("prev", include_str!("./hx-target/prev.md")),
-                ("this", include_str!("./hx-target/this.md")),
-            ]),
-
-            String::from("hx-boost") => to_hx_completion(vec![
-                ("true", include_str!("./hx-boost/true.md")),
-                ("false", include_str!("./hx-boost/false.md")),
-            ]),
-
-            String::from("hx-trigger") => to_hx_completion(vec![
-                ("click", include_str!("./hx-trigger/click.md")),
-                ("once", include_str!("./hx-trigger/once.md")),
-                ("changed", include_str!("./hx-trigger/changed.md")),
-                ("delay:", include_str!("./hx-trigger/delay.md")),
-                ("throttle:", include_str!("./hx-trigger/throttle.md")),
-                ("from:", include_str!("./hx-trigger/from.md")),
-                ("target:", include_str!("./hx-trigger/target.md")),
-                ("consume", include_str!("./hx-trigger/consume.md")),
-                ("queue:", include_str!("./hx-trigger/queue.md")),
-                ("keyup", include_str!("./hx-trigger/keyup.md")),
-                ("load", include_str!("./hx-trigger/load.md")),
-                ("revealed", include_str!("./hx-trigger/revealed.md")),
-                ("intersect", include_str!("./hx-trigger/intersect.md")),
-                ("every", include_str!("./hx-trigger/every.md")),
-            ]),
-
-            String::from("hx-ext") => to_hx_completion(vec![
-                ("ajax-header", include_str!("./hx-ext/ajax-header.md")),
-                ("alpine-morph", include_str!("./hx-ext/alpine-morph.md")),
-                ("class-tools", include_str!("./hx-ext/class-tools.md")),
-                ("client-side-templates", include_str!("./hx-ext/client-side-templates.md")),
-                ("debug", include_str!("./hx-ext/debug.md")),
-                ("disable-element", include_str!("./hx-ext/disable-element.md")),
-                ("event-header", include_str!("./hx-ext/event-header.md")),
-                ("head-support", include_str!("./hx-ext/head-support.md")),
-                ("include-vals", include_str!("./hx-ext/include-vals.md")),
-                ("json-enc", include_str!("./hx-ext/json-enc.md")),
-                ("morph", include_str!("./hx-ext/morph.md")),
-                ("loading-states", include_str!("./hx-ext/loading-states.md")),
-                ("method-override", include_str!("./hx-ext/method-override.md")),
-                ("morphdom-swap", include_str!("./hx-ext/morphdom-swap.md")),
-                ("multi-swap", include_str!("./hx-ext/multi-swap.md")),
-                ("path-deps", include_str!("./hx-ext/path-deps.md")),
-                ("preload", include_str!("./hx-ext/preload.md")),
-                ("remove-me", include_str!("./hx-ext/remove-me.md")),
-                ("response-targets", include_str!("./hx-ext/response-targets.md")),
-                ("restored", include_str!("./hx-ext/restored.md")),
-                ("sse", include_str!("./hx-ext/sse.md")),
-                ("ws", include_str!("./hx-ext/ws.md")),
-            ]),
-
-            String::from("hx-push-url") => to_hx_completion(vec![
-                ("true", include_str!("./hx-push-url/true.md")),
-                ("false", include_str!("./hx-push-url/false.md")),
-            ]),
-
-            String::from("hx-swap-oob") => to_hx_completion(vec![
-                ("true", include_str!("./hx-swap-oob/true.md")),
-                ("innerHTML", include_str!("./hx-swap/innerHTML.md")),
-                ("outerHTML", include_str!("./hx-swap/outerHTML.md")),
-                ("afterbegin", include_str!("./hx-swap/afterbegin.md")),
-                ("afterend", include_str!("./hx-swap/afterend.md")),
-                ("beforebegin", include_str!("./hx-swap/beforebegin.md")),
-                ("beforeend", include_str!("./hx-swap/beforeend.md")),
-                ("delete", include_str!("./hx-swap/delete.md")),
-                ("none", include_str!("./hx-swap/none.md")),
-            ]),
-
-            String::from("hx-history") => to_hx_completion(vec![
-                ("false", include_str!("./hx-history/false.md")),
-            ]),
-
-            String::from("hx-params") => to_hx_completion(vec![
-                ("*", include_str!("./hx-params/star.md")),
-                ("none", include_str!("./hx-params/none.md")),
-                ("not", include_str!("./hx-params/not.md")),
-            ]),
-
-            String::from("hx-replace-url") => to_hx_completion(vec![
-                ("true", include_str!("./hx-replace-url/true.md")),
-                ("false", include_str!("./hx-replace-url/false.md")),
-            ]),
-
-            String::from("hx-sync") => to_hx_completion(vec![
-                ("drop", include_str!("./hx-sync/drop.md")),
-                ("abort", include_str!("./hx-sync/abort.md")),
-                ("replace", include_str!("./hx-sync/replace.md")),
-                ("queue", include_str!("./hx-sync/queue.md")),
-            ])
-        });
-
-    _ = HX_TAGS.set(
-        to_hx_completion(vec![
-            ("hx-boost", include_str!("./attributes/hx-boost.md")),
-            ("hx-delete", include_str!("./attributes/hx-delete.md")),
-            ("hx-get", include_str!("./attributes/hx-get.md")),
-            ("hx-include", include_str!("./attributes/hx-include.md")),
-            ("hx-patch", include_str!("./attributes/hx-patch.md")),
-            ("hx-post", include_str!("./attributes/hx-post.md")),
-            ("hx-put", include_str!("./attributes/hx-put.md")),
-            ("hx-swap", include_str!("./attributes/hx-swap.md")),
-            ("hx-target", include_str!("./attributes/hx-target.md")),
-            ("hx-trigger", include_str!("./attributes/hx-trigger.md")),
-            ("hx-vals", include_str!("./attributes/hx-vals.md")),
-            ("hx-push-url", include_str!("./attributes/hx-push-url.md")),
-            ("hx-select", include_str!("./attributes/hx-select.md")),
-            ("hx-ext", include_str!("./attributes/hx-ext.md")),
-            ("hx-on", include_str!("./attributes/hx-on.md")),
-            ("hx-select-oob", include_str!("./attributes/hx-select-oob.md")),
-            ("hx-swap-oob", include_str!("./attributes/hx-swap-oob.md")),
-            ("hx-confirm", include_str!("./attributes/hx-confirm.md")),
-            ("hx-disable", include_str!("./attributes/hx-disable.md")),
-            ("hx-encoding", include_str!("./attributes/hx-encoding.md")),
-            ("hx-headers", include_str!("./attributes/hx-headers.md")),
-            ("hx-history", include_str!("./attributes/hx-history.md")),
-            ("hx-history-elt", include_str!("./attributes/hx-history-elt.md")),
-            ("hx-indicator", include_str!("./attributes/hx-indicator.md")),
-            ("hx-params", include_str!("./attributes/hx-params.md")),
-            ("hx-preserve", include_str!("./attributes/hx-preserve.md")),
-            ("hx-prompt", include_str!("./attributes/hx-prompt.md")),
-            ("hx-replace-url", include_str!("./attributes/hx-replace-url.md")),
-            ("hx-request", include_str!("./attributes/hx-request.md")),
-            ("hx-sync", include_str!("./attributes/hx-sync.md")),
-            ("hx-validate", include_str!("./attributes/hx-validate.md")),
-        ])
+        ATTRIBUTE_VALUES
+            .iter()
+            .map(|(attribute, values)| (attribute.to_string(), to_hx_completion(values.to_vec())))
+            .collect(),
     );
+
+    _ = HX_TAGS.set(to_hx_completion(ATTRIBUTES.to_vec()));
+
+    init_extension_tags();
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, TextDocumentIdentifier, TextDocumentPositionParams, Url};
+
+    use super::{
+        canonical_name, deprecation, equals_completion_values, htmx_name, hx_attribute_values,
+        hx_completion, init_hx_tags, is_alpine_attribute, is_at_least, is_available,
+        is_core_attribute, is_removed, version_note, CATALOG_VERSION, DEPRECATED_ATTRIBUTES,
+        HX_TAGS,
+    };
+    use crate::{
+        config::{init_config, HtmxConfig},
+        text_store::{init_text_store, TEXT_STORE},
+    };
+
+    #[test]
+    fn test_deprecations_follow_the_project_version() {
+        assert!(is_at_least("1.9.10", "1.9.3"));
+        assert!(is_at_least("2", "2.0"));
+        assert!(!is_at_least("1.6.1", "1.7"));
+
+        assert!(deprecation("hx-sse", None).is_some());
+        assert!(deprecation("hx-sse", Some("1.6.1")).is_none());
+        assert!(deprecation("hx-get", None).is_none());
+
+        assert!(!is_removed("hx-ws", None));
+        assert!(!is_removed("hx-ws", Some("1.9.6")));
+        assert!(is_removed("hx-ws", Some("2.0.1")));
+        assert!(!is_removed("hx-vars", Some("2.0.1")));
+    }
+
+    #[test]
+    fn test_catalog_is_generated_from_the_reference_data() {
+        init_hx_tags();
+        let catalog: serde_json::Value =
+            serde_json::from_str(include_str!("./catalog.json")).expect("valid catalog");
+        let attributes = catalog["attributes"].as_array().expect("attributes");
+
+        assert_eq!(catalog["version"], CATALOG_VERSION);
+        assert_eq!(HX_TAGS.get().expect("tags").len(), attributes.len());
+        assert!(DEPRECATED_ATTRIBUTES
+            .iter()
+            .all(|deprecation| is_core_attribute(deprecation.name)));
+
+        let swap = hx_attribute_values("hx-swap").expect("hx-swap values");
+        assert_eq!(swap[0].name, "innerHTML");
+        assert!(swap[0].desc.contains("inside the target element"));
+    }
+
+    #[test]
+    fn test_catalog_follows_the_major_version() {
+        assert!(is_available("hx-ws", None, Some("1.9.6")));
+        assert!(!is_available("hx-ws", None, Some("2.0.0")));
+        assert!(!is_available("hx-inherit", None, Some("1.9.6")));
+        assert!(is_available("hx-inherit", None, Some("2.0.0")));
+        assert!(!is_available("hx-on:click", None, Some("1.9.2")));
+        assert!(!is_available("hx-swap", Some("textContent"), Some("1.9.6")));
+        assert!(is_available("hx-swap", Some("textContent"), None));
+        assert!(is_available("hx-swap", Some("innerHTML"), Some("1.0.0")));
+
+        assert_eq!(
+            version_note("hx-sse", None, Some("2.0.1")).as_deref(),
+            Some("`hx-sse` was removed in htmx 2.0, use the sse extension")
+        );
+        assert_eq!(
+            version_note("hx-vars", None, None).as_deref(),
+            Some("`hx-vars` is deprecated since htmx 1.3, use hx-vals")
+        );
+        assert_eq!(
+            version_note("hx-swap", Some("textContent"), Some("1.9.6")).as_deref(),
+            Some("`textContent` needs htmx 2.0, the project uses 1.9.6")
+        );
+        assert_eq!(version_note("hx-get", None, Some("1.9.6")), None);
+    }
+
+    #[test]
+    fn test_data_prefixed_names_are_the_same_attribute() {
+        assert_eq!(htmx_name("data-hx-get"), Some("hx-get"));
+        assert_eq!(htmx_name("hx-get"), Some("hx-get"));
+        assert_eq!(htmx_name("data-id"), None);
+        assert_eq!(canonical_name("data-id"), "data-id");
+
+        assert!(deprecation("data-hx-ws", None).is_some());
+    }
+
+    #[test]
+    fn test_presence_only_attributes_say_so() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        for attribute in ["hx-preserve", "hx-disable"] {
+            let values = hx_attribute_values(attribute).expect("has values");
+            assert_eq!(values.len(), 1);
+            assert!(values[0].desc.contains("presence"));
+        }
+        assert!(hx_attribute_values("hx-validate").is_some());
+    }
+
+    #[test]
+    fn test_attributes_already_set_are_left_out() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        let uri = Url::parse("file:///present.html").expect("valid uri");
+        let text = r##"<button hx-get="/a" data-hx-target="#b" hx-></button>"##;
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(uri.to_string(), text.to_string());
+
+        let (items, _) = hx_completion(TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri },
+            position: Position::new(0, text.find("hx->").expect("typed") as u32 + 3),
+        })
+        .expect("has completions");
+        let names: Vec<_> = items.iter().map(|item| item.name.as_str()).collect();
+
+        assert!(!names.contains(&"hx-get"));
+        assert!(!names.contains(&"hx-target"));
+        assert!(names.contains(&"hx-post"));
+    }
+
+    #[test]
+    fn test_extension_attributes_need_the_extension_loaded() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        let complete = |uri: &str, text: &str, typed: &str| {
+            let uri = Url::parse(uri).expect("valid uri");
+            TEXT_STORE
+                .get()
+                .expect("text store initialized")
+                .lock()
+                .expect("text store mutex")
+                .texts
+                .insert(uri.to_string(), text.to_string());
+
+            let position = text.find(typed).expect("typed") + typed.len();
+            return hx_completion(TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(0, position as u32),
+            })
+            .map(|(items, _)| items.into_iter().map(|item| item.name).collect::<Vec<_>>());
+        };
+
+        let loaded = r##"<body hx-ext="preload"><a preload="mouse" pre></a></body>"##;
+        assert_eq!(
+            complete("file:///loaded.html", loaded, "\" pre"),
+            Some(vec!["preload-images".to_string()])
+        );
+        let values = complete("file:///loaded.html", loaded, "preload=\"").expect("values");
+        assert_eq!(values, vec!["mousedown", "mouseover", "preload:init"]);
+
+        let plain = r##"<body><a preload="" pre></a></body>"##;
+        assert_eq!(complete("file:///plain.html", plain, "\" pre"), None);
+        assert_eq!(complete("file:///plain.html", plain, "preload=\""), None);
+    }
+
+    #[test]
+    fn test_alpine_attributes_are_left_alone() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        for name in ["x-data", "x-on:click", "@click.away", ":class"] {
+            assert!(is_alpine_attribute(name), "{}", name);
+        }
+        for name in ["hx-on:click", "hx-on::after-request", "class", "data-x"] {
+            assert!(!is_alpine_attribute(name), "{}", name);
+        }
+
+        let uri = Url::parse("file:///alpine.html").expect("valid uri");
+        let text = r##"<div x-data="{ open: false, hx-" @click="open = !open" hx-></div>"##;
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(uri.to_string(), text.to_string());
+        let complete = |typed: &str| {
+            let position = text.find(typed).expect("typed") + typed.len();
+            return hx_completion(TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, position as u32),
+            });
+        };
+
+        assert!(complete("false, hx-").is_none());
+        assert!(complete("!open\" hx-").is_some());
+    }
+
+    #[test]
+    fn test_values_after_equals_go_through_value_completion() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        let complete = |uri: &str, text: &str, attribute: &str| {
+            let uri = Url::parse(uri).expect("valid uri");
+            TEXT_STORE
+                .get()
+                .expect("text store initialized")
+                .lock()
+                .expect("text store mutex")
+                .texts
+                .insert(uri.to_string(), text.to_string());
+
+            let typed = format!("{}=", attribute);
+            let position = text.find(&typed).expect("typed") + typed.len();
+            return equals_completion_values(
+                &TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri },
+                    position: Position::new(0, position as u32),
+                },
+                attribute,
+            )
+            .map(|(items, attribute)| {
+                let names = items.into_iter().map(|item| item.name).collect::<Vec<_>>();
+                return (names, attribute);
+            });
+        };
+
+        let (names, attribute) = complete(
+            "file:///equals.html",
+            "<div data-hx-swap=></div>",
+            "data-hx-swap",
+        )
+        .expect("swap values");
+        assert_eq!(attribute.as_deref(), Some("hx-swap"));
+        assert!(names.contains(&"innerHTML".to_string()));
+        assert!(names.contains(&"outerHTML".to_string()));
+
+        let script = "<script>let a = '<div hx-swap=></div>'</script>";
+        assert_eq!(complete("file:///script.html", script, "hx-swap"), None);
+
+        let plain = r##"<a preload=></a>"##;
+        assert_eq!(complete("file:///plain.html", plain, "preload"), None);
+    }
 }
@@ -0,0 +1,254 @@
+//! Completion documentation laid out like the htmx reference: what the item
+//! does, an example of it in use and the page to read more.
+
+use super::{canonical_name, htmx_name, hx_attribute_values, hx_documentation, HX_TAGS};
+
+const ATTRIBUTES_URL: &str = "https://htmx.org/attributes/";
+
+// Only their presence matters, an example with a value would mislead
+const PRESENCE_ONLY: [&str; 6] = [
+    "hx-disable",
+    "hx-preserve",
+    "ws-send",
+    "data-loading-disable",
+    "data-loading-aria-busy",
+    "data-loading-states",
+];
+
+#[derive(Debug, PartialEq)]
+pub struct Reference {
+    pub summary: String,
+    pub example: String,
+    pub link: Option<String>,
+}
+
+impl Reference {
+    pub fn markdown(&self) -> String {
+        let mut doc = format!("{}\n\n```html\n{}\n```", self.summary, self.example);
+        if let Some(link) = &self.link {
+            doc.push_str(&format!("\n\n[HTMX Reference]({})", link));
+        }
+
+        return doc;
+    }
+
+    pub fn plain_text(&self) -> String {
+        let mut doc = format!("{}\n\n{}", self.summary, self.example);
+        if let Some(link) = &self.link {
+            doc.push_str(&format!("\n\n{}", link));
+        }
+
+        return doc;
+    }
+}
+
+/// The first sentence of the documentation, past the title some of the
+/// bundled pages start with.
+fn summary(desc: &str, name: &str) -> Option<String> {
+    return desc
+        .lines()
+        .map(str::trim)
+        .find(|line| {
+            return !line.is_empty()
+                && line.trim_end_matches(':') != name
+                && !line.starts_with('<')
+                && !line.starts_with('[');
+        })
+        .map(|line| line.to_string());
+}
+
+/// The first HTML block of the documentation, with its indentation removed.
+fn example(desc: &str) -> Option<String> {
+    let lines: Vec<&str> = desc.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('<'))?;
+    let block: Vec<&str> = lines[start..]
+        .iter()
+        .enumerate()
+        .take_while(|(idx, line)| {
+            let continues = line.starts_with(char::is_whitespace) && !line.trim().is_empty();
+            return *idx == 0 || line.starts_with('<') || continues;
+        })
+        .map(|(_, line)| *line)
+        .collect();
+
+    let indent = block
+        .iter()
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let block: Vec<&str> = block.iter().map(|line| &line[indent..]).collect();
+    return Some(block.join("\n"));
+}
+
+/// The url of the first markdown link of the documentation.
+fn link(desc: &str) -> Option<String> {
+    let start = desc.find("](")? + "](".len();
+    let end = desc[start..].find(')')? + start;
+    return Some(desc[start..end].to_string());
+}
+
+/// The reference page of a bundled attribute, `hx-on:*` names all share
+/// the hx-on one.
+fn attribute_url(name: &str) -> Option<String> {
+    let name = canonical_name(name);
+    let name = match name.starts_with("hx-on:") {
+        true => "hx-on",
+        false => name,
+    };
+
+    let bundled = HX_TAGS.get()?.iter().any(|tag| tag.name == name);
+    return bundled.then(|| format!("{}{}/", ATTRIBUTES_URL, name));
+}
+
+fn example_element(attribute: &str, value: Option<&str>) -> String {
+    if PRESENCE_ONLY.contains(&canonical_name(attribute)) {
+        return format!("<div {}></div>", attribute);
+    }
+
+    let value = match value {
+        Some(value) => value.to_string(),
+        None => hx_attribute_values(canonical_name(attribute))
+            .and_then(|values| values.first().map(|value| value.name.clone()))
+            .unwrap_or_else(|| "...".to_string()),
+    };
+    return format!("<div {}=\"{}\"></div>", attribute, value);
+}
+
+/// The documentation of a completion item, `name` being an attribute name
+/// or, when `attribute` is set, one of its values. Items only known by
+/// their `detail`, like the classes of the stylesheets, get that as summary.
+pub fn completion_reference(
+    attribute: Option<&str>,
+    name: &str,
+    detail: Option<&str>,
+) -> Reference {
+    let desc = hx_documentation(attribute, name).unwrap_or_default();
+    let summary = summary(&desc, name)
+        .or_else(|| detail.map(|detail| detail.to_string()))
+        .unwrap_or_else(|| name.to_string());
+
+    // Values are shown in use, the example of the attribute page is about
+    // the attribute as a whole
+    let example = match attribute {
+        Some(attribute) => example_element(attribute, Some(name)),
+        None => example(&desc).unwrap_or_else(|| example_element(name, None)),
+    };
+
+    let link = link(&desc).or_else(|| attribute_url(attribute.unwrap_or(name)));
+    return Reference {
+        summary,
+        example,
+        link,
+    };
+}
+
+/// The documentation of a snippet adding several attributes at once, its
+/// example fills the placeholders with their defaults.
+pub fn snippet_reference(description: &str, body: &str) -> Reference {
+    let mut attributes = String::new();
+    let mut rest = body;
+    while let Some(start) = rest.find('$') {
+        attributes.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..];
+        rest = match placeholder.strip_prefix('{') {
+            Some(placeholder) => {
+                let end = placeholder.find('}').unwrap_or(placeholder.len());
+                let default = placeholder[..end]
+                    .split_once(':')
+                    .map_or("", |(_, default)| default);
+                attributes.push_str(default);
+                placeholder.get(end + 1..).unwrap_or("")
+            }
+            None => placeholder.trim_start_matches(|c: char| c.is_ascii_digit()),
+        };
+    }
+    attributes.push_str(rest);
+
+    let first = body.split('=').next().and_then(htmx_name);
+    return Reference {
+        summary: description.to_string(),
+        example: format!("<div {}></div>", attributes),
+        link: first.and_then(attribute_url),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{completion_reference, snippet_reference, Reference};
+    use crate::{
+        config::{init_config, HtmxConfig},
+        htmx::init_hx_tags,
+    };
+
+    #[test]
+    fn test_attribute_reference_has_summary_example_and_link() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        let reference = completion_reference(None, "hx-get", None);
+        assert_eq!(
+            reference.summary,
+            "The hx-get attribute will cause an element to issue a GET to the specified URL and swap the HTML into the DOM using a swap strategy:"
+        );
+        assert_eq!(
+            reference.example,
+            r#"<div hx-get="/example">Get Some HTML</div>"#
+        );
+        assert_eq!(
+            reference.link.as_deref(),
+            Some("https://htmx.org/attributes/hx-get/")
+        );
+
+        let reference = completion_reference(None, "hx-inherit", None);
+        assert_eq!(
+            reference.example,
+            "<div hx-target=\"#tab-container\" hx-inherit=\"hx-target\">\n  <a hx-boost=\"true\" href=\"/tab1\">Tab 1</a>\n  <a hx-boost=\"true\" href=\"/tab2\">Tab 2</a>\n</div>"
+        );
+
+        let markdown = reference.markdown();
+        assert!(markdown.contains("```html\n<div hx-target"));
+        assert!(markdown.ends_with("[HTMX Reference](https://htmx.org/attributes/hx-inherit/)"));
+    }
+
+    #[test]
+    fn test_value_and_snippet_references() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+
+        assert_eq!(
+            completion_reference(Some("hx-swap"), "outerHTML", None),
+            Reference {
+                summary: "replaces the entire target element with the returned content".to_string(),
+                example: r#"<div hx-swap="outerHTML"></div>"#.to_string(),
+                link: Some("https://htmx.org/attributes/hx-swap/".to_string()),
+            }
+        );
+        assert_eq!(
+            completion_reference(Some("class"), "btn-primary", Some("class in app.css")),
+            Reference {
+                summary: "class in app.css".to_string(),
+                example: r#"<div class="btn-primary"></div>"#.to_string(),
+                link: None,
+            }
+        );
+        assert_eq!(
+            completion_reference(None, "hx-preserve", None).example,
+            "<div hx-preserve></div>"
+        );
+
+        assert_eq!(
+            snippet_reference(
+                "Load content into another element",
+                "hx-get=\"${1:/path}\" hx-target=\"${2:#result}\" hx-swap=\"$3\""
+            ),
+            Reference {
+                summary: "Load content into another element".to_string(),
+                example: r##"<div hx-get="/path" hx-target="#result" hx-swap=""></div>"##
+                    .to_string(),
+                link: Some("https://htmx.org/attributes/hx-get/".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,2455 @@
+//! Completion of attribute values with a grammar of their own, where what
+//! fits depends on the words already written before the cursor.
+
+use std::ops::Range;
+
+use lsp_types::Url;
+
+use crate::{
+    css::css_classes,
+    dom::Dom,
+    index::{document_text, workspace_classes},
+    inlay_hints::INHERITED_ATTRIBUTES,
+    links::{route_template, REQUEST_ATTRIBUTES},
+    routes::workspace_routes,
+    symbols::element_request,
+    tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+};
+
+use super::{
+    events::{dom_event_description, htmx_event_completions, htmx_event_description, DOM_EVENTS},
+    extension::{attribute_extension, extension_core_values, extension_values, extensions_at},
+    hx_documentation, HxCompletion, EXTENSION_CORE_VALUES, HX_ATTRIBUTE_VALUES,
+};
+
+/// A `name:` modifier, `label` is what gets offered and `options` what can
+/// follow the colon.
+struct Modifier {
+    name: &'static str,
+    label: &'static str,
+    desc: &'static str,
+    options: &'static [&'static str],
+}
+
+const TIMINGS: &[&str] = &["100ms", "500ms", "1s"];
+const BOOLEANS: &[&str] = &["true", "false"];
+
+/// Common intervals of `every <interval>` polling
+const POLL_INTERVALS: &[&str] = &["1s", "2s", "5s", "10s", "30s", "1m", "500ms"];
+const TIME_UNITS: [&str; 3] = ["ms", "s", "m"];
+
+const SWAP_MODIFIERS: [Modifier; 7] = [
+    Modifier {
+        name: "swap",
+        label: "swap:",
+        desc: include_str!("./hx-swap/modifiers/swap.md"),
+        options: TIMINGS,
+    },
+    Modifier {
+        name: "settle",
+        label: "settle:",
+        desc: include_str!("./hx-swap/modifiers/settle.md"),
+        options: TIMINGS,
+    },
+    Modifier {
+        name: "scroll",
+        label: "scroll:",
+        desc: include_str!("./hx-swap/modifiers/scroll.md"),
+        options: &["top", "bottom", "window:top", "window:bottom"],
+    },
+    Modifier {
+        name: "show",
+        label: "show:",
+        desc: include_str!("./hx-swap/modifiers/show.md"),
+        options: &["top", "bottom", "window:top", "window:bottom", "none"],
+    },
+    Modifier {
+        name: "focus-scroll",
+        label: "focus-scroll:",
+        desc: include_str!("./hx-swap/modifiers/focus-scroll.md"),
+        options: BOOLEANS,
+    },
+    Modifier {
+        name: "transition",
+        label: "transition:true",
+        desc: include_str!("./hx-swap/modifiers/transition.md"),
+        options: BOOLEANS,
+    },
+    Modifier {
+        name: "ignoreTitle",
+        label: "ignoreTitle:true",
+        desc: include_str!("./hx-swap/modifiers/ignoreTitle.md"),
+        options: BOOLEANS,
+    },
+];
+
+/// What can start a trigger, events and the `every` of polling
+const TRIGGER_EVENTS: [(&str, &str); 16] = [
+    ("click", include_str!("./hx-trigger/click.md")),
+    ("change", include_str!("./hx-trigger/events/change.md")),
+    ("submit", include_str!("./hx-trigger/events/submit.md")),
+    ("input", include_str!("./hx-trigger/events/input.md")),
+    ("keyup", include_str!("./hx-trigger/keyup.md")),
+    ("keydown", include_str!("./hx-trigger/events/keydown.md")),
+    ("dblclick", include_str!("./hx-trigger/events/dblclick.md")),
+    (
+        "mouseenter",
+        include_str!("./hx-trigger/events/mouseenter.md"),
+    ),
+    (
+        "mouseleave",
+        include_str!("./hx-trigger/events/mouseleave.md"),
+    ),
+    ("focus", include_str!("./hx-trigger/events/focus.md")),
+    ("blur", include_str!("./hx-trigger/events/blur.md")),
+    ("search", include_str!("./hx-trigger/events/search.md")),
+    ("load", include_str!("./hx-trigger/load.md")),
+    ("revealed", include_str!("./hx-trigger/revealed.md")),
+    ("intersect", include_str!("./hx-trigger/intersect.md")),
+    ("every", include_str!("./hx-trigger/every.md")),
+];
+
+const TRIGGER_MODIFIERS: [Modifier; 8] = [
+    Modifier {
+        name: "once",
+        label: "once",
+        desc: include_str!("./hx-trigger/once.md"),
+        options: &[],
+    },
+    Modifier {
+        name: "changed",
+        label: "changed",
+        desc: include_str!("./hx-trigger/changed.md"),
+        options: &[],
+    },
+    Modifier {
+        name: "delay",
+        label: "delay:",
+        desc: include_str!("./hx-trigger/delay.md"),
+        options: TIMINGS,
+    },
+    Modifier {
+        name: "throttle",
+        label: "throttle:",
+        desc: include_str!("./hx-trigger/throttle.md"),
+        options: TIMINGS,
+    },
+    Modifier {
+        name: "from",
+        label: "from:",
+        desc: include_str!("./hx-trigger/from.md"),
+        options: &["document", "window", "body"],
+    },
+    Modifier {
+        name: "target",
+        label: "target:",
+        desc: include_str!("./hx-trigger/target.md"),
+        options: &[],
+    },
+    Modifier {
+        name: "consume",
+        label: "consume",
+        desc: include_str!("./hx-trigger/consume.md"),
+        options: &[],
+    },
+    Modifier {
+        name: "queue",
+        label: "queue:",
+        desc: include_str!("./hx-trigger/queue.md"),
+        options: &["first", "last", "all", "none"],
+    },
+];
+
+/// Extended selector keywords of hx-target, all but `this` take a css
+/// selector after them
+const TARGET_KEYWORDS: [(&str, &str); 5] = [
+    ("this", include_str!("./hx-target/this.md")),
+    ("closest", include_str!("./hx-target/closest.md")),
+    ("find", include_str!("./hx-target/find.md")),
+    ("next", include_str!("./hx-target/next.md")),
+    ("previous", include_str!("./hx-target/prev.md")),
+];
+
+fn bundled(attribute: &str) -> Vec<HxCompletion> {
+    return HX_ATTRIBUTE_VALUES
+        .get()
+        .and_then(|values| values.get(attribute))
+        .cloned()
+        .unwrap_or_default();
+}
+
+/// The words before the one being typed, and the one being typed.
+fn split_current(before: &str) -> (Vec<&str>, &str) {
+    let start = before.rfind(char::is_whitespace).map_or(0, |idx| idx + 1);
+
+    return (
+        before[..start].split_whitespace().collect(),
+        &before[start..],
+    );
+}
+
+/// Modifier names, or the options of the modifier whose colon was typed.
+/// Items are whole words since they replace the word under the cursor.
+fn modifier_completions(modifiers: &[Modifier], current: &str) -> Vec<HxCompletion> {
+    let Some((name, _)) = current.split_once(':') else {
+        return modifiers
+            .iter()
+            .map(|modifier| HxCompletion {
+                name: modifier.label.to_string(),
+                desc: modifier.desc.to_string(),
+                insert_text: None,
+            })
+            .collect();
+    };
+
+    let Some(modifier) = modifiers.iter().find(|modifier| modifier.name == name) else {
+        return vec![];
+    };
+
+    return modifier
+        .options
+        .iter()
+        .map(|option| HxCompletion {
+            name: format!("{}:{}", modifier.name, option),
+            desc: modifier.desc.to_string(),
+            insert_text: None,
+        })
+        .collect();
+}
+
+/// `hx-swap="<strategy> <modifier>..."`, the loaded extensions may bring
+/// strategies of their own.
+fn swap_completions(before: &str, source: &str, byte: usize) -> Vec<HxCompletion> {
+    let (previous, current) = split_current(before);
+    let extensions = extensions_at(source, byte);
+    let multi_swap = extensions.iter().any(|extension| extension == "multi-swap");
+    let extension_swaps = extension_core_values(&extensions, "hx-swap");
+    match previous.first() {
+        None if multi_swap && current.starts_with("multi:") => {
+            return multi_swap_completions(&current["multi:".len()..], source);
+        }
+        // Strategies like `morph:innerHTML` have a colon of their own
+        None if !current.contains(':')
+            || extension_swaps
+                .iter()
+                .any(|item| item.name.starts_with(current)) =>
+        {
+            let mut items = bundled("hx-swap");
+            items.extend(extension_swaps);
+            return items;
+        }
+        // multi-swap stops reading the value at the first space
+        Some(first) if multi_swap && first.starts_with("multi:") => return vec![],
+        _ => {}
+    }
+
+    return modifier_completions(&SWAP_MODIFIERS, current);
+}
+
+/// `multi:<id>,<id>:<strategy>,...` of the multi-swap extension, which
+/// only swaps elements by id. The first item carries the `multi:` prefix
+/// since it is part of the word being replaced.
+fn multi_swap_completions(swaps: &str, source: &str) -> Vec<HxCompletion> {
+    let (listed, current) = match swaps.rsplit_once(',') {
+        Some((listed, current)) => (listed, current),
+        None => ("", swaps),
+    };
+    let prefix = match swaps.contains(',') {
+        true => "",
+        false => "multi:",
+    };
+
+    if let Some((selector, _)) = current.split_once(':') {
+        return bundled("hx-swap")
+            .into_iter()
+            .map(|strategy| HxCompletion {
+                name: format!("{}{}:{}", prefix, selector, strategy.name),
+                desc: strategy.desc,
+                insert_text: None,
+            })
+            .collect();
+    }
+
+    let listed: Vec<&str> = listed
+        .split(',')
+        .filter_map(|swap| swap.split(':').next())
+        .collect();
+    let dom = Dom::parse(source);
+    return dom
+        .elements
+        .iter()
+        .filter_map(|element| {
+            let selector = format!("#{}", element.id()?);
+            if listed.contains(&selector.as_str()) {
+                return None;
+            }
+            return Some(HxCompletion {
+                name: format!("{}{}", prefix, selector),
+                desc: format!(
+                    "swaps the `{}` of the response into the `{}` of this document",
+                    selector,
+                    element.selector()
+                ),
+                insert_text: None,
+            });
+        })
+        .collect();
+}
+
+/// What is wrong with the `multi:` value of the multi-swap extension,
+/// whose ids and strategies htmx silently skips when it can't find them.
+pub fn multi_swap_problem(swaps: &str) -> Option<String> {
+    if swaps.contains(char::is_whitespace) {
+        return Some("multi-swap only reads the value up to its first space".to_string());
+    }
+
+    let strategies = bundled("hx-swap");
+    for swap in swaps.split(',') {
+        let (selector, strategy) = swap.split_once(':').unwrap_or((swap, "innerHTML"));
+        if selector.is_empty() {
+            return Some("a selector between the commas is empty".to_string());
+        }
+        if !selector.starts_with('#') || selector.len() == 1 {
+            return Some(format!(
+                "`{}` is not an id selector, multi-swap only swaps elements by id",
+                selector
+            ));
+        }
+        if !strategies.iter().any(|item| item.name == strategy) {
+            return Some(format!(
+                "`{}` is not a swap strategy, use one of hx-swap like outerHTML",
+                strategy
+            ));
+        }
+    }
+
+    return None;
+}
+
+/// The words of `value` with the bytes they span.
+fn word_ranges(value: &str) -> Vec<(Range<usize>, &str)> {
+    let mut words = vec![];
+    let mut end = 0;
+    for word in value.split_whitespace() {
+        let start = end + value[end..].find(word).unwrap_or(0);
+        end = start + word.len();
+        words.push((start..end, word));
+    }
+
+    return words;
+}
+
+/// Whether `time` is an interval htmx reads, in milliseconds unless it
+/// ends with s or m.
+fn is_time(time: &str) -> bool {
+    let amount = time.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let unit = &time[amount.len()..];
+    return amount.parse::<f64>().is_ok() && (unit.is_empty() || TIME_UNITS.contains(&unit));
+}
+
+/// What is wrong with a swap modifier like `scroll:top`.
+fn swap_modifier_problem(word: &str) -> Option<String> {
+    let Some((name, option)) = word.split_once(':') else {
+        return Some(format!(
+            "`{}` is not a swap modifier, modifiers are written like swap:1s",
+            word
+        ));
+    };
+    let Some(modifier) = SWAP_MODIFIERS.iter().find(|modifier| modifier.name == name) else {
+        let names: Vec<&str> = SWAP_MODIFIERS
+            .iter()
+            .map(|modifier| modifier.name)
+            .collect();
+        let cased = names.iter().find(|known| known.eq_ignore_ascii_case(name));
+        return Some(match cased {
+            Some(known) => format!("`{}` is not a swap modifier, did you mean {}?", name, known),
+            None => format!(
+                "`{}` is not a swap modifier, use one of {}",
+                name,
+                names.join(", ")
+            ),
+        });
+    };
+
+    // scroll and show take a selector before the position, like #list:top
+    let position = option.rsplit(':').next().unwrap_or(option);
+    let (valid, expected) = match modifier.name {
+        "swap" | "settle" => (is_time(option), "a time like 100ms or 1s"),
+        "scroll" => (
+            matches!(position, "top" | "bottom") && !option.starts_with(':'),
+            "top or bottom, after the selector to scroll like #list: or window:",
+        ),
+        "show" => (
+            option == "none" || matches!(position, "top" | "bottom") && !option.starts_with(':'),
+            "top, bottom or none, after the selector to show like #list: or window:",
+        ),
+        _ => (BOOLEANS.contains(&option), "true or false"),
+    };
+    if valid {
+        return None;
+    }
+
+    return Some(format!("`{}` needs {}", word, expected));
+}
+
+/// What is wrong with an hx-swap value and the bytes of the value the
+/// problem is about. htmx falls back to its default swap when it can't read
+/// the strategy and skips the modifiers it doesn't know. The strategies of
+/// every extension are accepted, `multi:` swaps are checked on their own.
+pub fn swap_problem(value: &str) -> Option<(Range<usize>, String)> {
+    let mut strategies: Vec<String> = bundled("hx-swap")
+        .into_iter()
+        .map(|item| item.name)
+        .collect();
+    let builtin = strategies.join(", ");
+    strategies.extend(
+        EXTENSION_CORE_VALUES
+            .iter()
+            .filter(|(_, attribute, _, _)| *attribute == "hx-swap")
+            .map(|(_, _, strategy, _)| strategy.to_string()),
+    );
+
+    let words = word_ranges(value);
+    let modifiers = match words.first() {
+        None => return None,
+        Some((_, word)) if word.starts_with("multi:") => return None,
+        Some((_, word)) if strategies.iter().any(|strategy| strategy == word) => &words[1..],
+        // Without a strategy the value starts with the modifiers
+        Some((_, word)) if word.contains(':') => &words[..],
+        Some((range, word)) => {
+            let problem = match strategies
+                .iter()
+                .find(|strategy| strategy.eq_ignore_ascii_case(word))
+            {
+                Some(strategy) => format!(
+                    "`{}` is not a swap strategy, did you mean {}?",
+                    word, strategy
+                ),
+                None => format!("`{}` is not a swap strategy, use one of {}", word, builtin),
+            };
+            return Some((range.clone(), problem));
+        }
+    };
+
+    return modifiers.iter().find_map(|(range, word)| {
+        return swap_modifier_problem(word).map(|problem| (range.clone(), problem));
+    });
+}
+
+/// What a trigger can start with: the bundled events, the other DOM events
+/// and the htmx ones, which custom events like `htmx:afterSettle from:body`
+/// usually are.
+fn trigger_events() -> Vec<HxCompletion> {
+    let mut items: Vec<HxCompletion> = TRIGGER_EVENTS.iter().map(HxCompletion::from).collect();
+    items.extend(
+        DOM_EVENTS
+            .iter()
+            .filter(|(name, _)| !TRIGGER_EVENTS.iter().any(|(event, _)| event == name))
+            .map(|(name, _)| HxCompletion {
+                name: name.to_string(),
+                desc: dom_event_description(name).unwrap_or_default(),
+                insert_text: None,
+            }),
+    );
+    items.extend(htmx_event_completions());
+
+    return items;
+}
+
+/// `hx-trigger="<event> <modifier>..., <event>..."`, each comma starts a
+/// new trigger.
+fn trigger_completions(before: &str) -> Vec<HxCompletion> {
+    // Nothing to offer inside the javascript of a `[filter]`
+    if before.matches('[').count() > before.matches(']').count() {
+        return vec![];
+    }
+
+    let trigger = before.rsplit(',').next().unwrap_or_default();
+    let (previous, current) = split_current(trigger);
+    if previous.first() == Some(&"every") {
+        return polling_completions(&previous[1..], current);
+    }
+
+    // The event may have a colon, but only the htmx: of htmx events
+    let is_event =
+        !current.contains(':') || "htmx:".starts_with(current) || current.starts_with("htmx:");
+    if previous.is_empty() && is_event {
+        return trigger_events();
+    }
+
+    return modifier_completions(&TRIGGER_MODIFIERS, current);
+}
+
+/// `every <interval> [<filter>]`, the interval, in the units the digits
+/// typed so far can take, then the optional filter. Polling takes no
+/// modifiers.
+fn polling_completions(previous: &[&str], current: &str) -> Vec<HxCompletion> {
+    let interval = |name: String| {
+        let desc = interval_description(&name).unwrap_or_default();
+        return HxCompletion {
+            name,
+            desc,
+            insert_text: None,
+        };
+    };
+
+    return match previous {
+        [] if current.is_empty() => POLL_INTERVALS
+            .iter()
+            .map(|name| interval(name.to_string()))
+            .collect(),
+        [] => {
+            let digits = current.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return vec![];
+            }
+            TIME_UNITS
+                .iter()
+                .map(|unit| interval(format!("{}{}", digits, unit)))
+                .collect()
+        }
+        [interval] if interval_description(interval).is_some() => vec![HxCompletion {
+            name: "[condition]".to_string(),
+            desc: include_str!("./hx-trigger/every.md").to_string(),
+            insert_text: Some("[".to_string()),
+        }],
+        _ => vec![],
+    };
+}
+
+/// `2s` as "Polls every 2 seconds", `None` when it isn't an interval.
+fn interval_description(interval: &str) -> Option<String> {
+    let digits = interval.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let amount: u64 = digits.parse().ok()?;
+    let unit = match &interval[digits.len()..] {
+        "" | "ms" => "millisecond",
+        "s" => "second",
+        "m" => "minute",
+        _ => return None,
+    };
+
+    return Some(match amount {
+        1 => format!("Polls every {}.", unit),
+        amount => format!("Polls every {} {}s.", amount, unit),
+    });
+}
+
+/// `hx-ext="<extension>, ignore:<extension>"`, extensions already in the
+/// list are left out.
+fn ext_completions(before: &str) -> Vec<HxCompletion> {
+    let mut listed = before.split(',').map(str::trim);
+    let current = listed.next_back().unwrap_or_default();
+    let listed: Vec<&str> = listed.collect();
+
+    let extensions = bundled("hx-ext");
+    if current.starts_with("ignore:") {
+        return extensions
+            .into_iter()
+            .map(|extension| HxCompletion {
+                name: format!("ignore:{}", extension.name),
+                desc: extension.desc,
+                insert_text: None,
+            })
+            .collect();
+    }
+
+    let mut items: Vec<HxCompletion> = extensions
+        .into_iter()
+        .filter(|extension| !listed.contains(&extension.name.as_str()))
+        .collect();
+    items.push(HxCompletion {
+        name: "ignore:".to_string(),
+        desc: include_str!("./hx-ext/ignore.md").to_string(),
+        insert_text: None,
+    });
+
+    return items;
+}
+
+/// The ids and classes of the document as `#id` and `.class` selectors,
+/// along with its tag names when `with_tags` is set.
+fn document_selectors(source: &str, with_tags: bool) -> Vec<HxCompletion> {
+    let mut selectors: Vec<HxCompletion> = vec![];
+    let mut add = |name: String, desc: String| {
+        if !selectors.iter().any(|selector| selector.name == name) {
+            selectors.push(HxCompletion {
+                name,
+                desc,
+                insert_text: None,
+            });
+        }
+    };
+
+    let dom = Dom::parse(source);
+    for element in dom.elements.iter() {
+        if let Some(id) = element.id() {
+            add(
+                format!("#{}", id),
+                format!("the `{}` of this document", element.selector()),
+            );
+        }
+    }
+    for element in dom.elements.iter() {
+        for class in element.classes() {
+            add(
+                format!(".{}", class),
+                format!("elements with the `{}` class", class),
+            );
+        }
+    }
+    if with_tags {
+        for element in dom.elements.iter() {
+            add(element.tag.clone(), format!("`<{}>` elements", element.tag));
+        }
+    }
+
+    return selectors;
+}
+
+/// `hx-target="<selector>"` or `hx-target="<keyword> <selector>"`
+fn target_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let (previous, _) = split_current(before);
+
+    return match previous.as_slice() {
+        [] => TARGET_KEYWORDS
+            .iter()
+            .map(HxCompletion::from)
+            .chain(document_selectors(source, false))
+            .collect(),
+        [keyword] if *keyword != "this" => document_selectors(source, true),
+        _ => vec![],
+    };
+}
+
+const DISABLED_ELT_KEYWORDS: [(&str, &str); 5] = [
+    ("this", "disables the element itself during the request"),
+    ("closest", "closest <CSS selector>, disables the closest ancestor element or itself matching the selector"),
+    ("find", "find <CSS selector>, disables the first child descendant matching the selector"),
+    ("next", "disables the next sibling, or with a selector the first following element matching it"),
+    ("previous", "disables the previous sibling, or with a selector the first preceding element matching it"),
+];
+
+/// `hx-disabled-elt="<extended selector>, <extended selector>"`, each one
+/// like the value of hx-target.
+fn disabled_elt_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let current = before.rsplit(',').next().unwrap_or_default().trim_start();
+    let (previous, _) = split_current(current);
+
+    return match previous.as_slice() {
+        [] => DISABLED_ELT_KEYWORDS
+            .iter()
+            .map(HxCompletion::from)
+            .chain(document_selectors(source, false))
+            .collect(),
+        [keyword] if *keyword != "this" => document_selectors(source, true),
+        _ => vec![],
+    };
+}
+
+const INCLUDE_KEYWORDS: [(&str, &str); 5] = [
+    ("this", "includes the element itself, and its descendants' values when it isn't a form"),
+    ("closest", "closest <CSS selector>, includes the closest ancestor element or itself matching the selector"),
+    ("find", "find <CSS selector>, includes the first child descendant matching the selector"),
+    ("next", "includes the next sibling, or with a selector the first following element matching it"),
+    ("previous", "includes the previous sibling, or with a selector the first preceding element matching it"),
+];
+
+/// `[name='<name>']` selectors for the named fields of the document, the
+/// most common thing to include.
+fn field_selectors(dom: &Dom) -> Vec<HxCompletion> {
+    let mut selectors: Vec<HxCompletion> = vec![];
+    for element in dom.elements.iter() {
+        let name = element.attribute_value("name").unwrap_or_default();
+        if name.is_empty() || !FIELD_TAGS.contains(&element.tag.as_str()) {
+            continue;
+        }
+
+        let selector = format!("[name='{}']", name);
+        if !selectors.iter().any(|known| known.name == selector) {
+            selectors.push(HxCompletion {
+                name: selector,
+                desc: format!("the `<{}>` field named `{}`", element.tag, name),
+                insert_text: None,
+            });
+        }
+    }
+
+    return selectors;
+}
+
+/// `hx-include="<extended selector>, <extended selector>"`, the named
+/// fields of the document come before the other selectors.
+fn include_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let current = before.rsplit(',').next().unwrap_or_default().trim_start();
+    let (previous, _) = split_current(current);
+    let dom = Dom::parse(source);
+
+    return match previous.as_slice() {
+        [] => field_selectors(&dom)
+            .into_iter()
+            .chain(INCLUDE_KEYWORDS.iter().map(HxCompletion::from))
+            .chain(document_selectors(source, false))
+            .collect(),
+        [keyword] if *keyword != "this" => field_selectors(&dom)
+            .into_iter()
+            .chain(document_selectors(source, true))
+            .collect(),
+        _ => vec![],
+    };
+}
+
+/// `hx-inherit="<attribute> <attribute>"` and the same for hx-disinherit,
+/// `*` stands for every inherited attribute.
+fn inherit_completions(attribute: &str, before: &str) -> Vec<HxCompletion> {
+    let (listed, _) = split_current(before);
+    if listed.contains(&"*") {
+        return vec![];
+    }
+
+    let verb = match attribute {
+        "hx-inherit" => "children inherit",
+        _ => "children don't inherit",
+    };
+    let all = HxCompletion {
+        name: "*".to_string(),
+        desc: format!("{} any of the attributes of the element", verb),
+        insert_text: None,
+    };
+
+    return listed
+        .is_empty()
+        .then_some(all)
+        .into_iter()
+        .chain(
+            INHERITED_ATTRIBUTES
+                .iter()
+                .filter(|name| !listed.contains(name))
+                .map(|name| HxCompletion {
+                    name: name.to_string(),
+                    desc: format!("{} the `{}` of the element", verb, name),
+                    insert_text: None,
+                }),
+        )
+        .collect();
+}
+
+fn file_name(uri: &str) -> &str {
+    return uri.rsplit('/').next().unwrap_or(uri);
+}
+
+/// The classes of the stylesheets the document links to and of the ones of
+/// the workspace, along with the file defining them.
+fn stylesheet_classes(source: &str, uri: &Url) -> Vec<(String, String)> {
+    let dom = Dom::parse(source);
+    let linked = dom
+        .elements
+        .iter()
+        .filter(|element| element.tag == "link")
+        .filter(|element| {
+            return element
+                .attribute_value("rel")
+                .is_some_and(|rel| rel.split_whitespace().any(|rel| rel == "stylesheet"));
+        })
+        .filter_map(|element| uri.join(element.attribute_value("href")?).ok())
+        .filter(|stylesheet| stylesheet.scheme() == "file");
+
+    let mut classes = vec![];
+    for stylesheet in linked {
+        let Some(text) = document_text(&stylesheet) else {
+            continue;
+        };
+        for class in css_classes(&text) {
+            classes.push((class, stylesheet.to_string()));
+        }
+    }
+    classes.extend(workspace_classes());
+
+    return classes;
+}
+
+/// `hx-indicator="<selector>"` or `closest <selector>`, the elements with
+/// the htmx-indicator class come first, then the classes of the
+/// stylesheets.
+fn indicator_completions(before: &str, source: &str, uri: &Url) -> Vec<HxCompletion> {
+    let (previous, _) = split_current(before);
+    match previous.as_slice() {
+        [] => {}
+        ["closest"] => return document_selectors(source, true),
+        _ => return vec![],
+    };
+
+    let mut items: Vec<HxCompletion> = TARGET_KEYWORDS
+        .iter()
+        .filter(|(keyword, _)| *keyword == "closest")
+        .map(HxCompletion::from)
+        .collect();
+    let mut add = |name: String, desc: String| {
+        if !items.iter().any(|item| item.name == name) {
+            items.push(HxCompletion {
+                name,
+                desc,
+                insert_text: None,
+            });
+        }
+    };
+
+    let dom = Dom::parse(source);
+    for element in dom.elements.iter() {
+        if !element.classes().any(|class| class == "htmx-indicator") {
+            continue;
+        }
+        if let Some(id) = element.id() {
+            let desc = format!(
+                "the `{}` of this document, an htmx-indicator",
+                element.selector()
+            );
+            add(format!("#{}", id), desc);
+        }
+    }
+    add(
+        ".htmx-indicator".to_string(),
+        "the elements with the `htmx-indicator` class, shown during requests".to_string(),
+    );
+    for (class, stylesheet) in stylesheet_classes(source, uri) {
+        let desc = format!("the `{}` class of `{}`", class, file_name(&stylesheet));
+        add(format!(".{}", class), desc);
+    }
+
+    return items;
+}
+
+const QUEUE_OPTIONS: [&str; 3] = ["first", "last", "all"];
+
+/// `hx-sync="<selector>:<strategy>"`, the selector takes the extended forms
+/// of hx-target and `queue` an option after a space.
+fn sync_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let Some(colon) = before.rfind(':') else {
+        return target_completions(before, source);
+    };
+
+    let strategy = &before[colon + 1..];
+    let strategies = bundled("hx-sync");
+    if strategy.starts_with("queue ") {
+        let desc = strategies
+            .iter()
+            .find(|item| item.name == "queue")
+            .map(|item| item.desc.clone())
+            .unwrap_or_default();
+
+        return QUEUE_OPTIONS
+            .iter()
+            .map(|option| HxCompletion {
+                name: option.to_string(),
+                desc: desc.clone(),
+                insert_text: None,
+            })
+            .collect();
+    }
+
+    // The word under the cursor starts with the last word of the selector
+    let (_, selector) = split_current(&before[..colon]);
+    return strategies
+        .into_iter()
+        .map(|item| HxCompletion {
+            name: format!("{}:{}", selector, item.name),
+            desc: item.desc,
+            insert_text: None,
+        })
+        .collect();
+}
+
+/// Where the cursor is in the JSON object of an attribute value.
+#[derive(Debug, PartialEq)]
+enum JsonPosition {
+    /// Nothing written yet
+    Empty,
+    /// Where a new key can start
+    Key,
+    /// Inside the quotes of a key
+    QuotedKey,
+    /// Anywhere else, like in a value
+    Other,
+}
+
+/// The position the JSON `before` the cursor leaves it at, the `js:` prefix
+/// of values evaluated as javascript is skipped. htmx adds the braces of
+/// objects written without them, like `"timeout": 100`.
+fn json_position(before: &str) -> JsonPosition {
+    let body = before.trim_start();
+    let body = JS_PREFIXES
+        .iter()
+        .find_map(|prefix| body.strip_prefix(prefix))
+        .unwrap_or(body)
+        .trim_start();
+    if body.is_empty() {
+        return JsonPosition::Empty;
+    }
+
+    // The word being typed belongs to the key it starts
+    let body = body.trim_end_matches(|c: char| c.is_alphanumeric() || c == '-' || c == '_');
+
+    let (mut nesting, mut last) = match body.starts_with('{') {
+        true => (vec![], None),
+        false => (vec!['{'], Some('{')),
+    };
+    // Inside a string, whether it is a key
+    let mut in_string = None;
+    let mut escaped = false;
+    for c in body.chars() {
+        if in_string.is_some() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    in_string = None;
+                    last = Some(c);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = Some(nesting.last() == Some(&'{') && matches!(last, Some('{' | ',')))
+            }
+            '{' | '[' => {
+                nesting.push(c);
+                last = Some(c);
+            }
+            '}' | ']' => {
+                nesting.pop();
+                last = Some(c);
+            }
+            c if c.is_whitespace() => {}
+            c => last = Some(c),
+        }
+    }
+
+    return match in_string {
+        Some(true) => JsonPosition::QuotedKey,
+        Some(false) => JsonPosition::Other,
+        None if nesting.last() == Some(&'{') && matches!(last, Some('{' | ',')) => {
+            JsonPosition::Key
+        }
+        None => JsonPosition::Other,
+    };
+}
+
+const REQUEST_HEADERS: [(&str, &str); 10] = [
+    ("Accept", "the content types the response can be in"),
+    (
+        "Accept-Language",
+        "the languages the response is preferred in",
+    ),
+    (
+        "Authorization",
+        "the credentials authenticating the request, eg. `Bearer <token>`",
+    ),
+    ("Cache-Control", "the caching directives of the request"),
+    ("Content-Type", "the media type of the request body"),
+    (
+        "If-None-Match",
+        "makes the request conditional on the ETag of the resource",
+    ),
+    (
+        "X-CSRF-Token",
+        "the anti forgery token, the name Rails and Laravel look for",
+    ),
+    (
+        "X-CSRFToken",
+        "the anti forgery token, the name Django looks for",
+    ),
+    (
+        "X-Requested-With",
+        "marks the request as an AJAX one for servers checking `XMLHttpRequest`",
+    ),
+    ("X-Api-Key", "the api key of the client"),
+];
+
+/// The keys of `hx-request`, with the value they are inserted with and
+/// the type they take
+const REQUEST_CONFIG: [(&str, &str, &str); 3] = [
+    (
+        "timeout",
+        "1000",
+        "`number`, milliseconds before the request is aborted",
+    ),
+    (
+        "credentials",
+        "true",
+        "`boolean`, whether cross origin requests send cookies and auth headers",
+    ),
+    (
+        "noHeaders",
+        "true",
+        "`boolean`, strips the htmx headers, like `HX-Request`, from the request",
+    ),
+];
+
+const JS_PREFIXES: [&str; 2] = ["javascript:", "js:"];
+
+const JS_PREFIX_DOC: &str = "`js:` or `javascript:` evaluates the rest of the value as \
+javascript, an object literal whose values can be computed each time the request is sent.";
+
+/// Object keys, written as JSON from where they are typed along with the
+/// `value` of the key, the javascript prefixes while the value is still
+/// empty.
+fn json_key_completions<'a>(
+    keys: &[(&'a str, &str)],
+    before: &str,
+    value: impl Fn(&'a str) -> &'a str,
+) -> Vec<HxCompletion> {
+    let position = json_position(before);
+    let insert_text = |key: &'a str| {
+        return match position {
+            JsonPosition::Empty => Some(format!("{{\"{}\": {}}}", key, value(key))),
+            JsonPosition::Key => Some(format!("\"{}\": {}", key, value(key))),
+            _ => None,
+        };
+    };
+
+    let mut items: Vec<HxCompletion> = match position {
+        JsonPosition::Other => return vec![],
+        _ => keys
+            .iter()
+            .map(|(key, desc)| HxCompletion {
+                name: key.to_string(),
+                desc: desc.to_string(),
+                insert_text: insert_text(key),
+            })
+            .collect(),
+    };
+    if position == JsonPosition::Empty {
+        items.extend(JS_PREFIXES.iter().map(|prefix| HxCompletion {
+            name: prefix.to_string(),
+            desc: JS_PREFIX_DOC.to_string(),
+            insert_text: None,
+        }));
+    }
+
+    return items;
+}
+
+/// `hx-push-url` and `hx-replace-url` take `true`, `false` or the url to
+/// put in the navigation bar instead of the fetched one.
+fn history_url_completions(attribute: &str) -> Vec<HxCompletion> {
+    let action = match attribute {
+        "hx-push-url" => "pushes",
+        _ => "replaces the current url with",
+    };
+
+    let mut items = bundled(attribute);
+    items.extend(
+        workspace_routes()
+            .into_iter()
+            .filter(|route| route.answers("GET"))
+            .map(|route| HxCompletion {
+                desc: format!(
+                    "{} `{}` instead of the fetched url, {}",
+                    action, route.path, route.origin
+                ),
+                name: route.path,
+                insert_text: None,
+            }),
+    );
+
+    return items;
+}
+
+/// The known routes answering the method of the request attribute, see
+/// `workspace_routes`.
+fn request_url_completions(attribute: &str) -> Vec<HxCompletion> {
+    let method = attribute["hx-".len()..].to_uppercase();
+
+    return workspace_routes()
+        .into_iter()
+        .filter(|route| route.answers(&method))
+        .map(|route| HxCompletion {
+            desc: format!(
+                "`{} {}`, {}",
+                route.method.as_deref().unwrap_or(&method),
+                route.path,
+                route.origin
+            ),
+            name: route.path,
+            insert_text: None,
+        })
+        .collect();
+}
+
+/// The known routes an sse or ws connection can be opened to, both start
+/// with a GET request.
+fn connect_completions(attribute: &str) -> Vec<HxCompletion> {
+    let connection = match attribute {
+        "sse-connect" => "listens to the server sent events of",
+        _ => "opens a WebSocket to",
+    };
+
+    return workspace_routes()
+        .into_iter()
+        .filter(|route| route.answers("GET"))
+        .map(|route| HxCompletion {
+            desc: format!("{} `{}`, {}", connection, route.path, route.origin),
+            name: route.path,
+            insert_text: None,
+        })
+        .collect();
+}
+
+/// The names of the sse messages the document already swaps or triggers
+/// on, `sse-swap` takes a comma separated list of them.
+fn sse_swap_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let listed: Vec<&str> = before.split(',').map(str::trim).collect();
+    let mut items: Vec<HxCompletion> = extension_values("sse-swap").unwrap_or_default();
+
+    let dom = Dom::parse(source);
+    for element in dom.elements.iter() {
+        let swapped = element
+            .attribute_value("sse-swap")
+            .into_iter()
+            .flat_map(|value| value.split(','));
+        let triggered = element
+            .attribute_value("hx-trigger")
+            .into_iter()
+            .flat_map(|value| value.split([',', ' ']))
+            .filter_map(|trigger| trigger.trim().strip_prefix("sse:"));
+
+        for name in swapped.chain(triggered).map(str::trim) {
+            if name.is_empty() || items.iter().any(|item| item.name == name) {
+                continue;
+            }
+            items.push(HxCompletion {
+                name: name.to_string(),
+                desc: format!(
+                    "the `{}` messages, already listened to in this document",
+                    name
+                ),
+                insert_text: None,
+            });
+        }
+    }
+
+    items.retain(|item| !listed[..listed.len() - 1].contains(&item.name.as_str()));
+    return items;
+}
+
+/// The classes of the stylesheets for the space separated lists of the
+/// loading-states extension, leaving out the ones already listed.
+fn loading_class_completions(
+    attribute: &str,
+    before: &str,
+    source: &str,
+    uri: &Url,
+) -> Vec<HxCompletion> {
+    let (listed, _) = split_current(before);
+    let action = match attribute {
+        "data-loading-class" => "adds",
+        _ => "removes",
+    };
+
+    let mut items: Vec<HxCompletion> = vec![];
+    for (class, stylesheet) in stylesheet_classes(source, uri) {
+        if listed.contains(&class.as_str()) || items.iter().any(|item| item.name == class) {
+            continue;
+        }
+        items.push(HxCompletion {
+            desc: format!(
+                "{} the `{}` class of `{}` during requests",
+                action,
+                class,
+                file_name(&stylesheet)
+            ),
+            name: class,
+            insert_text: None,
+        });
+    }
+
+    return items;
+}
+
+const CLASS_OPERATIONS: [(&str, &str); 3] = [
+    ("add", "adds the class once the delay has passed"),
+    ("remove", "removes the class once the delay has passed"),
+    ("toggle", "toggles the class every time the delay passes"),
+];
+
+/// `classes="<operation> <class>:<delay>, ... & ..."` of the class-tools
+/// extension, `,` chains operations and `&` starts another run. The element
+/// classes come first for the operations taking them away.
+fn classes_completions(before: &str, source: &str, byte: usize, uri: &Url) -> Vec<HxCompletion> {
+    let current_operation = before.rsplit([',', '&']).next().unwrap_or(before);
+    let (previous, current) = split_current(current_operation);
+    let operation = match previous.as_slice() {
+        [] => return CLASS_OPERATIONS.iter().map(HxCompletion::from).collect(),
+        [operation] if CLASS_OPERATIONS.iter().any(|(name, _)| name == operation) => *operation,
+        _ => return vec![],
+    };
+
+    if let Some((class, _)) = current.split_once(':') {
+        let when = match operation {
+            "toggle" => "every",
+            _ => "after",
+        };
+        return TIMINGS
+            .iter()
+            .map(|delay| HxCompletion {
+                name: format!("{}:{}", class, delay),
+                desc: format!("{}s `{}` {} {}", operation, class, when, delay),
+                insert_text: None,
+            })
+            .collect();
+    }
+
+    let mut items: Vec<HxCompletion> = vec![];
+    let mut add = |name: String, desc: String| {
+        if !items.iter().any(|item| item.name == name) {
+            items.push(HxCompletion {
+                name,
+                desc,
+                insert_text: None,
+            });
+        }
+    };
+
+    let dom = Dom::parse(source);
+    if let Some(idx) = dom.element_at(byte).filter(|_| operation != "add") {
+        for class in dom.elements[idx].classes() {
+            add(
+                class.to_string(),
+                format!("the `{}` class of this element", class),
+            );
+        }
+    }
+    for (class, stylesheet) in stylesheet_classes(source, uri) {
+        let desc = format!("the `{}` class of `{}`", class, file_name(&stylesheet));
+        add(class, desc);
+    }
+
+    return items;
+}
+
+/// What is wrong with a `classes` value of the class-tools extension, which
+/// htmx skips over without a word.
+pub fn classes_problem(value: &str) -> Option<String> {
+    for operation in value.split(['&', ',']).map(str::trim) {
+        let words: Vec<&str> = operation.split_whitespace().collect();
+        let (name, class) = match words.as_slice() {
+            [] => return Some("an operation between the separators is empty".to_string()),
+            [name, class] => (*name, *class),
+            [name] => (*name, ""),
+            _ => {
+                return Some(format!(
+                    "`{}` has more than an operation and a class, separate operations with , or &",
+                    operation
+                ))
+            }
+        };
+
+        if !CLASS_OPERATIONS
+            .iter()
+            .any(|(operation, _)| *operation == name)
+        {
+            return Some(format!(
+                "`{}` is not a class operation, use add, remove or toggle",
+                name
+            ));
+        }
+        let (class, delay) = class.split_once(':').unwrap_or((class, "100ms"));
+        if class.is_empty() {
+            return Some(format!("`{}` needs the class to {}", operation, name));
+        }
+        if interval_description(delay).is_none() {
+            return Some(format!(
+                "`{}` is not a delay, use a time like 100ms or 1s",
+                delay
+            ));
+        }
+    }
+
+    return None;
+}
+
+/// The attributes hyperscript reads its features from
+pub const HYPERSCRIPT_ATTRIBUTES: [&str; 3] = ["_", "data-script", "data-hyperscript"];
+
+const HYPERSCRIPT_OPENERS: [(&str, &str); 8] = [
+    (
+        "on click",
+        "runs the commands that follow every time the element is clicked",
+    ),
+    (
+        "on submit",
+        "runs the commands that follow when the form is submitted",
+    ),
+    (
+        "on load",
+        "runs the commands that follow once the element is loaded",
+    ),
+    (
+        "on htmx:beforeRequest",
+        "runs the commands that follow before htmx sends a request of the element",
+    ),
+    (
+        "on htmx:afterRequest",
+        "runs the commands that follow once a request of the element completes",
+    ),
+    (
+        "on htmx:afterSwap",
+        "runs the commands that follow once htmx swapped new content in",
+    ),
+    (
+        "init",
+        "runs the commands that follow once, when hyperscript initializes the element",
+    ),
+    (
+        "def",
+        "defines a function the other features of the element can call",
+    ),
+];
+
+/// The starts of the hyperscript features, where a line begins, and the
+/// events after their `on`. The rest is left to the hyperscript tooling.
+fn hyperscript_completions(before: &str) -> Vec<HxCompletion> {
+    let line = before.rsplit('\n').next().unwrap_or(before).trim_start();
+    return match line.split_once(char::is_whitespace) {
+        None => HYPERSCRIPT_OPENERS.iter().map(HxCompletion::from).collect(),
+        Some(("on", event)) if !event.contains(char::is_whitespace) => trigger_events(),
+        _ => vec![],
+    };
+}
+
+/// `hx-head` of head-support: how the `<head>` of a response joins the one
+/// of the page, or on the elements inside it whether they are added again.
+fn head_completions(source: &str, byte: usize) -> Vec<HxCompletion> {
+    let dom = Dom::parse(source);
+    let on_head = dom
+        .element_at(byte)
+        .is_some_and(|idx| dom.elements[idx].tag == "head");
+
+    return extension_values("hx-head")
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| head_values(on_head).contains(&item.name.as_str()))
+        .collect();
+}
+
+/// The `hx-head` values of the `<head>` tag, or of the elements inside it.
+pub fn head_values(on_head: bool) -> &'static [&'static str] {
+    return match on_head {
+        true => &["merge", "append"],
+        false => &["re-eval"],
+    };
+}
+
+/// Script types of code rather than of a template
+const SCRIPT_TYPES: [&str; 4] = ["", "text/javascript", "application/javascript", "module"];
+
+/// The ids of the templates of the document for the client-side-templates
+/// extension: `<template>` elements and `<script>` ones of a template type,
+/// like `text/mustache`.
+fn template_completions(source: &str) -> Vec<HxCompletion> {
+    let dom = Dom::parse(source);
+    return dom
+        .elements
+        .iter()
+        .filter_map(|element| {
+            let id = element.id()?;
+            let kind = match element.tag.as_str() {
+                "template" => "`<template>`".to_string(),
+                "script" => {
+                    let kind = element.attribute_value("type").unwrap_or("");
+                    if SCRIPT_TYPES.contains(&kind.trim().to_lowercase().as_str()) {
+                        return None;
+                    }
+                    format!("`<script type=\"{}\">`", kind)
+                }
+                _ => return None,
+            };
+            return Some(HxCompletion {
+                name: id.to_string(),
+                desc: format!(
+                    "renders the response with the {} `#{}` of this document",
+                    kind, id
+                ),
+                insert_text: None,
+            });
+        })
+        .collect();
+}
+
+/// The known routes `data-loading-path` can narrow the loading states to,
+/// whatever their method.
+fn loading_path_completions() -> Vec<HxCompletion> {
+    let mut items: Vec<HxCompletion> = vec![];
+    for route in workspace_routes() {
+        if items.iter().any(|item| item.name == route.path) {
+            continue;
+        }
+        items.push(HxCompletion {
+            desc: format!(
+                "applies the loading states during the requests to `{}`, {}",
+                route.path, route.origin
+            ),
+            name: route.path,
+            insert_text: None,
+        });
+    }
+
+    return items;
+}
+
+/// `hx-swap-oob="true"`, `<strategy>` or `<strategy>:<selector>`, unlike
+/// hx-swap the selector follows the strategy after a colon and there are
+/// no modifiers.
+fn swap_oob_completions(before: &str, source: &str) -> Vec<HxCompletion> {
+    let before = before.trim_start();
+    let Some((strategy, _)) = before.split_once(':') else {
+        return bundled("hx-swap-oob");
+    };
+
+    return document_selectors(source, false)
+        .into_iter()
+        .map(|selector| HxCompletion {
+            name: format!("{}:{}", strategy, selector.name),
+            desc: selector.desc,
+            insert_text: None,
+        })
+        .collect();
+}
+
+/// hx-select picks from the response, its selectors come from the template
+/// the request of the element renders when the routes directory maps it,
+/// from the document otherwise.
+fn response_selectors(source: &str, byte: usize, uri: &Url) -> Vec<HxCompletion> {
+    let dom = Dom::parse(source);
+    let template = dom
+        .element_at(byte)
+        .and_then(|idx| element_request(&dom.elements[idx].attributes))
+        .and_then(|(_, url)| route_template(uri, &url));
+    let Some((template, text)) = template.and_then(|template| {
+        let text = document_text(&template)?;
+        return Some((template, text));
+    }) else {
+        return document_selectors(source, false);
+    };
+
+    let file = template
+        .path_segments()
+        .and_then(|mut segments| segments.next_back());
+    return document_selectors(&text, false)
+        .into_iter()
+        .map(|selector| HxCompletion {
+            desc: format!(
+                "{}, in the `{}` template the request renders",
+                selector.desc,
+                file.unwrap_or_default()
+            ),
+            ..selector
+        })
+        .collect();
+}
+
+/// `hx-select-oob="<selector>, <selector>:<target>"`, each selector picks
+/// from the response and the optional target is an element of the page.
+fn select_oob_completions(before: &str, source: &str, byte: usize, uri: &Url) -> Vec<HxCompletion> {
+    let current = before.rsplit(',').next().unwrap_or_default().trim_start();
+    let Some((selector, _)) = current.split_once(':') else {
+        return response_selectors(source, byte, uri);
+    };
+
+    return document_selectors(source, false)
+        .into_iter()
+        .map(|target| HxCompletion {
+            name: format!("{}:{}", selector, target.name),
+            desc: target.desc,
+            insert_text: None,
+        })
+        .collect();
+}
+
+const FIELD_TAGS: [&str; 4] = ["input", "select", "textarea", "button"];
+
+/// The names of the fields the request of the element `idx` sends, those
+/// of its closest form or, outside of one, of the element itself.
+fn field_names(dom: &Dom, idx: usize) -> Vec<String> {
+    let form = std::iter::once(idx)
+        .chain(dom.ancestors(idx))
+        .find(|&ancestor| dom.elements[ancestor].tag == "form")
+        .unwrap_or(idx);
+
+    let mut names: Vec<String> = vec![];
+    let fields = std::iter::once(form)
+        .chain((form + 1..dom.elements.len()).take_while(|&other| dom.is_descendant(other, form)));
+    for field in fields {
+        let element = &dom.elements[field];
+        if !FIELD_TAGS.contains(&element.tag.as_str()) {
+            continue;
+        }
+
+        let name = element.attribute_value("name").unwrap_or_default();
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    return names;
+}
+
+/// `hx-vals` keys, the names of the fields the request already sends so
+/// their values can be overridden. In `js:` mode the value is javascript,
+/// so only the keys are completed and nothing is checked as JSON.
+fn vals_completions(before: &str, source: &str, byte: usize) -> Vec<HxCompletion> {
+    let dom = Dom::parse(source);
+    let fields: Vec<(String, String)> = dom
+        .element_at(byte)
+        .map(|idx| field_names(&dom, idx))
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| {
+            let desc = format!("overrides the value of the `{}` field", name);
+            return (name, desc);
+        })
+        .collect();
+    let keys: Vec<(&str, &str)> = fields
+        .iter()
+        .map(|(name, desc)| (name.as_str(), desc.as_str()))
+        .collect();
+
+    return json_key_completions(&keys, before, |_| "\"\"");
+}
+
+/// `hx-params="*"`, `none`, `not <names>` or `<names>`, comma separated
+/// names of the fields of the form, names already listed are left out.
+fn params_completions(before: &str, source: &str, byte: usize) -> Vec<HxCompletion> {
+    let list = before.trim_start();
+    let (excluding, list) = match list.strip_prefix("not ") {
+        Some(list) => (true, list),
+        None => (false, list),
+    };
+    if list == "*" || list == "none" {
+        return vec![];
+    }
+
+    let mut listed = list.split(',').map(str::trim);
+    listed.next_back();
+    let listed: Vec<&str> = listed.collect();
+
+    let dom = Dom::parse(source);
+    let names = dom
+        .element_at(byte)
+        .map(|idx| field_names(&dom, idx))
+        .unwrap_or_default();
+
+    let mut items = match excluding || !listed.is_empty() {
+        true => vec![],
+        false => bundled("hx-params"),
+    };
+    items.extend(
+        names
+            .into_iter()
+            .filter(|name| !listed.contains(&name.as_str()))
+            .map(|name| HxCompletion {
+                desc: match excluding {
+                    true => format!("leaves the `{}` field out of the request", name),
+                    false => format!("sends the `{}` field and no other", name),
+                },
+                name,
+                insert_text: None,
+            }),
+    );
+
+    return items;
+}
+
+/// Completions for `attribute` given the part of its value before the
+/// cursor at `byte` of the document `uri`, `None` for attributes without a
+/// grammar.
+pub fn value_completions(
+    attribute: &str,
+    before: &str,
+    source: &str,
+    byte: usize,
+    uri: &Url,
+) -> Option<Vec<HxCompletion>> {
+    return match attribute {
+        "hx-target" => Some(target_completions(before, source)),
+        "hx-ext" => Some(ext_completions(before)),
+        "hx-sync" => Some(sync_completions(before, source)),
+        "hx-swap" => Some(swap_completions(before, source, byte)),
+        "hx-trigger" => Some(trigger_completions(before)),
+        "hx-headers" => Some(json_key_completions(&REQUEST_HEADERS, before, |_| "\"\"")),
+        "hx-request" => {
+            let keys: Vec<(&str, &str)> = REQUEST_CONFIG
+                .iter()
+                .map(|(key, _, desc)| (*key, *desc))
+                .collect();
+            let value = |name: &str| {
+                return REQUEST_CONFIG
+                    .iter()
+                    .find(|(key, _, _)| *key == name)
+                    .map_or("\"\"", |(_, value, _)| value);
+            };
+            Some(json_key_completions(&keys, before, value))
+        }
+        "hx-push-url" | "hx-replace-url" => Some(history_url_completions(attribute)),
+        attribute if REQUEST_ATTRIBUTES.contains(&attribute) => {
+            Some(request_url_completions(attribute))
+        }
+        "hx-params" => Some(params_completions(before, source, byte)),
+        "hx-vals" => Some(vals_completions(before, source, byte)),
+        "hx-swap-oob" => Some(swap_oob_completions(before, source)),
+        "hx-disabled-elt" => Some(disabled_elt_completions(before, source)),
+        "hx-include" => Some(include_completions(before, source)),
+        "hx-indicator" => Some(indicator_completions(before, source, uri)),
+        "hx-inherit" | "hx-disinherit" => Some(inherit_completions(attribute, before)),
+        "hx-select" => Some(response_selectors(source, byte, uri)),
+        "hx-select-oob" => Some(select_oob_completions(before, source, byte, uri)),
+        "sse-connect" | "ws-connect" => Some(connect_completions(attribute)),
+        "sse-swap" => Some(sse_swap_completions(before, source)),
+        "data-loading-class" | "data-loading-class-remove" => {
+            Some(loading_class_completions(attribute, before, source, uri))
+        }
+        "data-loading-target" => Some(document_selectors(source, true)),
+        "hx-head" => Some(head_completions(source, byte)),
+        attribute if HYPERSCRIPT_ATTRIBUTES.contains(&attribute) => {
+            Some(hyperscript_completions(before))
+        }
+        "classes" | "data-classes" => Some(classes_completions(before, source, byte, uri)),
+        attribute
+            if attribute.ends_with("-template") && attribute_extension(attribute).is_some() =>
+        {
+            Some(template_completions(source))
+        }
+        "data-loading-path" => Some(loading_path_completions()),
+        // The response-targets family takes what hx-target takes
+        attribute if attribute.starts_with("hx-target-") => {
+            Some(target_completions(before, source))
+        }
+        _ => None,
+    };
+}
+
+/// Documentation of the words only the grammars know about, like the
+/// `scroll:top` modifier of hx-swap.
+pub fn value_documentation(attribute: &str, name: &str) -> Option<String> {
+    let modifiers: &[Modifier] = match attribute {
+        "hx-swap" => &SWAP_MODIFIERS,
+        "hx-trigger" => &TRIGGER_MODIFIERS,
+        "hx-ext" if name.starts_with("ignore:") => {
+            let extension = &name["ignore:".len()..];
+            return match extension {
+                "" => Some(include_str!("./hx-ext/ignore.md").to_string()),
+                extension => bundled("hx-ext")
+                    .into_iter()
+                    .find(|item| item.name == extension)
+                    .map(|item| item.desc),
+            };
+        }
+        "hx-sync" => {
+            let strategy = match name.rsplit(':').next()? {
+                option if QUEUE_OPTIONS.contains(&option) => "queue",
+                strategy => strategy,
+            };
+            return bundled("hx-sync")
+                .into_iter()
+                .find(|item| item.name == strategy)
+                .map(|item| item.desc);
+        }
+        "hx-swap-oob" => {
+            let strategy = name.split(':').next()?;
+            return bundled("hx-swap")
+                .into_iter()
+                .find(|item| item.name == strategy)
+                .map(|item| item.desc);
+        }
+        "hx-push-url" | "hx-replace-url" => {
+            return history_url_completions(attribute)
+                .into_iter()
+                .find(|item| item.name == name)
+                .map(|item| item.desc);
+        }
+        attribute if REQUEST_ATTRIBUTES.contains(&attribute) => {
+            return request_url_completions(attribute)
+                .into_iter()
+                .find(|item| item.name == name)
+                .map(|item| item.desc);
+        }
+        attribute if attribute.starts_with("hx-target-") => {
+            return hx_documentation(Some("hx-target"), name);
+        }
+        "sse-connect" | "ws-connect" => {
+            return connect_completions(attribute)
+                .into_iter()
+                .find(|item| item.name == name)
+                .map(|item| item.desc);
+        }
+        "classes" | "data-classes" => {
+            let (_, desc) = CLASS_OPERATIONS
+                .iter()
+                .find(|(operation, _)| *operation == name)?;
+            return Some(desc.to_string());
+        }
+        attribute if HYPERSCRIPT_ATTRIBUTES.contains(&attribute) => {
+            let (_, desc) = HYPERSCRIPT_OPENERS
+                .iter()
+                .find(|(opener, _)| *opener == name)?;
+            return Some(desc.to_string());
+        }
+        "data-loading-path" => {
+            return loading_path_completions()
+                .into_iter()
+                .find(|item| item.name == name)
+                .map(|item| item.desc);
+        }
+        "hx-headers" | "hx-request" | "hx-vals" if JS_PREFIXES.contains(&name) => {
+            return Some(JS_PREFIX_DOC.to_string());
+        }
+        "hx-request" => {
+            let (_, _, desc) = REQUEST_CONFIG.iter().find(|(key, _, _)| *key == name)?;
+            return Some(desc.to_string());
+        }
+        "hx-headers" => {
+            let (_, desc) = REQUEST_HEADERS.iter().find(|(header, _)| *header == name)?;
+            return Some(desc.to_string());
+        }
+        "hx-inherit" | "hx-disinherit" => {
+            return inherit_completions(attribute, "")
+                .into_iter()
+                .find(|item| item.name == name)
+                .map(|item| item.desc);
+        }
+        "hx-include" => {
+            let (_, desc) = INCLUDE_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| *keyword == name)?;
+            return Some(desc.to_string());
+        }
+        "hx-disabled-elt" => {
+            let (_, desc) = DISABLED_ELT_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| *keyword == name)?;
+            return Some(desc.to_string());
+        }
+        "hx-target" => {
+            let (_, desc) = TARGET_KEYWORDS
+                .iter()
+                .find(|(keyword, _)| *keyword == name)?;
+            return Some(desc.to_string());
+        }
+        _ => return None,
+    };
+
+    if attribute == "hx-trigger" {
+        if let Some((_, desc)) = TRIGGER_EVENTS.iter().find(|(event, _)| *event == name) {
+            return Some(desc.to_string());
+        }
+        if let Some(desc) = htmx_event_description(name).filter(|_| name.starts_with("htmx:")) {
+            return Some(desc);
+        }
+        if let Some(desc) = dom_event_description(name) {
+            return Some(desc);
+        }
+        if let Some(desc) = interval_description(name) {
+            return Some(desc);
+        }
+    }
+
+    let name = name.split(':').next()?;
+    return modifiers
+        .iter()
+        .find(|modifier| modifier.name == name)
+        .map(|modifier| modifier.desc.to_string());
+}
+
+/// The part of the attribute value under the cursor that comes before it.
+pub fn value_before(source: &str, byte: usize) -> String {
+    let Some(tree) = parse_html(source) else {
+        return String::new();
+    };
+
+    return query_attributes(tree.root_node(), source)
+        .into_iter()
+        .filter_map(|attribute| attribute.value_range)
+        .find(|range| range.start_byte <= byte && byte <= range.end_byte)
+        .map(|range| source[range.start_byte..byte].to_string())
+        .unwrap_or_default();
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Url;
+
+    use super::{
+        json_position, swap_problem, value_before, value_completions, value_documentation,
+        JsonPosition,
+    };
+    use crate::{
+        config::{init_config, HtmxConfig},
+        htmx::{hx_documentation, init_hx_tags},
+        index::{init_index, INDEX},
+        text_store::{init_text_store, TEXT_STORE},
+    };
+
+    fn uri() -> Url {
+        return Url::parse("file:///values.html").expect("valid uri");
+    }
+
+    const SOURCE: &str = r#"<ul id="items"><li class="item done">a</li></ul><p class="item"></p>"#;
+
+    fn names(attribute: &str, before: &str) -> Vec<String> {
+        return value_completions(attribute, before, SOURCE, 0, &uri())
+            .expect("has a grammar")
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+    }
+
+    #[test]
+    fn test_swap_problems_point_at_their_word() {
+        init_hx_tags();
+        fn problem(value: &str) -> Option<(&str, String)> {
+            return swap_problem(value).map(|(range, problem)| (&value[range], problem));
+        }
+
+        assert_eq!(swap_problem(""), None);
+        assert_eq!(
+            swap_problem("outerHTML swap:0.5s settle:100ms focus-scroll:false"),
+            None
+        );
+        assert_eq!(swap_problem("scroll:window:bottom show:top"), None);
+        assert_eq!(swap_problem("morphdom"), None);
+        assert_eq!(swap_problem("multi:#a,#b:outerHTML"), None);
+        assert_eq!(
+            problem("beforeend scroll:middle"),
+            Some((
+                "scroll:middle",
+                "`scroll:middle` needs top or bottom, after the selector to scroll like #list: or window:"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            problem("innerHTML ignoretitle:true"),
+            Some((
+                "ignoretitle:true",
+                "`ignoretitle` is not a swap modifier, did you mean ignoreTitle?".to_string()
+            ))
+        );
+        assert_eq!(
+            problem("swap:1s  delay:1s"),
+            Some((
+                "delay:1s",
+                "`delay` is not a swap modifier, use one of swap, settle, scroll, show, focus-scroll, transition, ignoreTitle"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            problem("innerHTML outerHTML"),
+            Some((
+                "outerHTML",
+                "`outerHTML` is not a swap modifier, modifiers are written like swap:1s"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_swap_strategies_then_modifiers() {
+        init_hx_tags();
+
+        assert!(names("hx-swap", "").contains(&"outerHTML".to_string()));
+        assert!(names("hx-swap", "inn").contains(&"innerHTML".to_string()));
+
+        let modifiers = names("hx-swap", "innerHTML ");
+        assert!(modifiers.contains(&"settle:".to_string()));
+        assert!(modifiers.contains(&"transition:true".to_string()));
+        assert!(!modifiers.contains(&"outerHTML".to_string()));
+
+        assert_eq!(
+            names("hx-swap", "outerHTML swap:1s scroll:"),
+            vec![
+                "scroll:top",
+                "scroll:bottom",
+                "scroll:window:top",
+                "scroll:window:bottom"
+            ]
+        );
+        assert!(names("hx-swap", "outerHTML bogus:").is_empty());
+
+        assert!(value_documentation("hx-swap", "scroll:top")
+            .expect("documented")
+            .starts_with("scroll:"));
+    }
+
+    #[test]
+    fn test_trigger_events_then_modifiers_per_trigger() {
+        let events = names("hx-trigger", "");
+        assert!(events.contains(&"submit".to_string()));
+        assert!(events.contains(&"revealed".to_string()));
+        assert!(!events.contains(&"delay:".to_string()));
+
+        let modifiers = names("hx-trigger", "keyup ");
+        assert!(modifiers.contains(&"changed".to_string()));
+        assert!(modifiers.contains(&"delay:".to_string()));
+        assert!(!modifiers.contains(&"click".to_string()));
+
+        // A comma starts over with an event
+        assert!(names("hx-trigger", "keyup changed delay:1s, ").contains(&"load".to_string()));
+        assert_eq!(
+            names("hx-trigger", "load, click queue:"),
+            vec!["queue:first", "queue:last", "queue:all", "queue:none"]
+        );
+
+        assert!(value_documentation("hx-trigger", "submit").is_some());
+        assert!(value_documentation("hx-trigger", "delay:1s").is_some());
+    }
+
+    #[test]
+    fn test_trigger_polling() {
+        assert!(names("hx-trigger", "every ").contains(&"2s".to_string()));
+        assert_eq!(names("hx-trigger", "every 15"), vec!["15ms", "15s", "15m"]);
+        assert_eq!(
+            names("hx-trigger", "load, every 3s"),
+            vec!["3ms", "3s", "3m"]
+        );
+        assert_eq!(names("hx-trigger", "every 2s "), vec!["[condition]"]);
+        assert!(names("hx-trigger", "every 2s [isActive(").is_empty());
+        assert!(names("hx-trigger", "every 2s [ok] ").is_empty());
+        assert!(names("hx-trigger", "every nope ").is_empty());
+
+        assert_eq!(
+            value_documentation("hx-trigger", "30s").as_deref(),
+            Some("Polls every 30 seconds.")
+        );
+        assert_eq!(
+            value_documentation("hx-trigger", "1m").as_deref(),
+            Some("Polls every minute.")
+        );
+    }
+
+    #[test]
+    fn test_trigger_htmx_events() {
+        let events = names("hx-trigger", "htmx:after");
+        assert!(events.contains(&"htmx:afterSettle".to_string()));
+        assert!(events.contains(&"scroll".to_string()));
+        assert!(names("hx-trigger", "click, htmx:").contains(&"htmx:beforeSwap".to_string()));
+        assert!(names("hx-trigger", "htmx:afterSettle ").contains(&"from:".to_string()));
+
+        assert!(value_documentation("hx-trigger", "htmx:configRequest")
+            .is_some_and(|desc| desc.contains("customize")));
+    }
+
+    #[test]
+    fn test_target_keywords_and_document_selectors() {
+        assert_eq!(
+            names("hx-target", ""),
+            vec!["this", "closest", "find", "next", "previous", "#items", ".item", ".done"]
+        );
+        assert_eq!(
+            names("hx-target", "closest "),
+            vec!["#items", ".item", ".done", "ul", "li", "p"]
+        );
+        assert!(names("hx-target", "this ").is_empty());
+    }
+
+    #[test]
+    fn test_ext_lists_and_ignores_extensions() {
+        init_hx_tags();
+
+        let extensions = names("hx-ext", "");
+        assert!(extensions.contains(&"json-enc".to_string()));
+        assert!(extensions.contains(&"ignore:".to_string()));
+
+        let rest = names("hx-ext", "json-enc, ");
+        assert!(!rest.contains(&"json-enc".to_string()));
+        assert!(rest.contains(&"sse".to_string()));
+
+        assert!(names("hx-ext", "sse, ignore:").contains(&"ignore:debug".to_string()));
+        assert!(value_documentation("hx-ext", "ignore:").is_some());
+        assert!(value_documentation("hx-ext", "ignore:debug").is_some());
+    }
+
+    #[test]
+    fn test_sync_selector_then_strategy() {
+        init_hx_tags();
+
+        assert!(names("hx-sync", "").contains(&"closest".to_string()));
+        assert!(names("hx-sync", "closest ").contains(&"ul".to_string()));
+        assert_eq!(
+            names("hx-sync", "closest ul:"),
+            vec!["ul:drop", "ul:abort", "ul:replace", "ul:queue"]
+        );
+        assert_eq!(
+            names("hx-sync", "this:queue "),
+            vec!["first", "last", "all"]
+        );
+
+        assert!(value_documentation("hx-sync", "ul:abort").is_some());
+        assert_eq!(
+            value_documentation("hx-sync", "last"),
+            value_documentation("hx-sync", "this:queue")
+        );
+    }
+
+    #[test]
+    fn test_value_before_the_cursor() {
+        let text = r#"<div hx-swap="innerHTML settle:1s"></div>"#;
+        let cursor = text.find(" settle").expect("has settle");
+
+        assert_eq!(value_before(text, cursor + 1), "innerHTML ");
+        assert_eq!(value_before(text, 3), "");
+    }
+
+    #[test]
+    fn test_json_positions() {
+        assert_eq!(json_position(""), JsonPosition::Empty);
+        assert_eq!(json_position("js:"), JsonPosition::Empty);
+        assert_eq!(json_position("{"), JsonPosition::Key);
+        assert_eq!(json_position("{Acc"), JsonPosition::Key);
+        assert_eq!(json_position(r#"{"Acc"#), JsonPosition::QuotedKey);
+        assert_eq!(json_position(r#"{"a": "b", "#), JsonPosition::Key);
+        assert_eq!(json_position(r#"{"a": "x,"#), JsonPosition::Other);
+        assert_eq!(json_position(r#"{"a": "#), JsonPosition::Other);
+        assert_eq!(json_position(r#"{"a": [1, "#), JsonPosition::Other);
+        assert_eq!(json_position(r#"js:{a: 1, "#), JsonPosition::Key);
+        assert_eq!(json_position("time"), JsonPosition::Key);
+        assert_eq!(json_position(r#""timeout": 100, "#), JsonPosition::Key);
+        assert_eq!(json_position("javascript: timeout: "), JsonPosition::Other);
+    }
+
+    #[test]
+    fn test_header_keys_are_written_as_json() {
+        let insert = |before: &str| {
+            return value_completions("hx-headers", before, SOURCE, 0, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .find(|item| item.name == "Accept")
+                .map(|item| item.insert_text);
+        };
+
+        assert_eq!(insert(""), Some(Some(r#"{"Accept": ""}"#.to_string())));
+        assert_eq!(insert("{ "), Some(Some(r#""Accept": """#.to_string())));
+        assert_eq!(insert(r#"{"Ac"#), Some(None));
+        assert_eq!(insert(r#"{"Accept": "#), None);
+
+        assert!(names("hx-headers", "").contains(&"js:".to_string()));
+        assert!(!names("hx-headers", "{").contains(&"js:".to_string()));
+        assert!(value_documentation("hx-headers", "X-CSRFToken").is_some());
+    }
+
+    #[test]
+    fn test_request_config_keys_with_typed_values() {
+        let insert = |before: &str, key: &str| {
+            return value_completions("hx-request", before, SOURCE, 0, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .find(|item| item.name == key)
+                .and_then(|item| item.insert_text);
+        };
+
+        assert_eq!(
+            insert("", "timeout"),
+            Some(r#"{"timeout": 1000}"#.to_string())
+        );
+        assert_eq!(
+            insert(r#""timeout": 100, "#, "noHeaders"),
+            Some(r#""noHeaders": true"#.to_string())
+        );
+        assert!(names("hx-request", "").contains(&"javascript:".to_string()));
+        assert!(value_documentation("hx-request", "credentials")
+            .is_some_and(|desc| desc.starts_with("`boolean`")));
+        assert!(value_documentation("hx-request", "javascript:").is_some());
+    }
+
+    #[test]
+    fn test_history_urls_come_from_the_requests_of_the_workspace() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        init_index();
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(
+                "file:///history.html".to_string(),
+                r#"<a hx-get="/contacts"></a><a hx-get="/contacts"></a><a hx-get="{{ url }}"></a>"#
+                    .to_string(),
+            );
+
+        let urls = names("hx-push-url", "");
+        assert_eq!(urls[..2], ["true", "false"]);
+        assert!(urls.contains(&"/contacts".to_string()));
+        assert!(!urls.contains(&"{{ url }}".to_string()));
+
+        assert!(value_documentation("hx-replace-url", "/contacts")
+            .is_some_and(|desc| desc.contains("requested by 2 elements")));
+    }
+
+    #[test]
+    fn test_request_urls_answering_the_method() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_index();
+        INDEX
+            .get()
+            .expect("index initialized")
+            .lock()
+            .expect("index mutex")
+            .routes
+            .insert(
+                "file:///app/views.py".to_string(),
+                vec![
+                    (Some("GET".to_string()), "/users".to_string()),
+                    (Some("POST".to_string()), "/users/new".to_string()),
+                    (None, "/health".to_string()),
+                ],
+            );
+
+        let urls = names("hx-post", "");
+        assert!(urls.contains(&"/users/new".to_string()));
+        assert!(urls.contains(&"/health".to_string()));
+        assert!(!urls.contains(&"/users".to_string()));
+
+        assert!(names("hx-get", "").contains(&"/users".to_string()));
+        assert_eq!(
+            value_documentation("hx-post", "/users/new").as_deref(),
+            Some("`POST /users/new`, declared in `views.py`")
+        );
+    }
+
+    #[test]
+    fn test_sse_messages_of_the_document_and_connect_urls() {
+        init_hx_tags();
+        init_config(HtmxConfig::default());
+        init_text_store();
+        init_index();
+        let source = r#"<div hx-ext="sse" sse-connect="/events">
+  <p sse-swap="chat,notice"></p><p hx-get="/more" hx-trigger="sse:reload"></p><p sse-swap=""></p>
+</div>"#;
+        let swaps = |before: &str| {
+            return value_completions("sse-swap", before, source, 0, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(swaps(""), vec!["message", "chat", "notice", "reload"]);
+        assert_eq!(swaps("chat, "), vec!["message", "notice", "reload"]);
+
+        INDEX
+            .get()
+            .expect("index initialized")
+            .lock()
+            .expect("index mutex")
+            .routes
+            .insert(
+                "file:///app/stream.py".to_string(),
+                vec![(Some("GET".to_string()), "/stream".to_string())],
+            );
+        assert!(names("ws-connect", "").contains(&"/stream".to_string()));
+        assert_eq!(
+            value_documentation("sse-connect", "/stream").as_deref(),
+            Some("listens to the server sent events of `/stream`, declared in `stream.py`")
+        );
+    }
+
+    #[test]
+    fn test_vals_keys_from_the_fields_of_the_form() {
+        let source =
+            r#"<form><input name="email"><button hx-post="/save" hx-vals=""></button></form>"#;
+        let byte = source.find("hx-vals").expect("has hx-vals") + 9;
+        let insert = |before: &str| {
+            return value_completions("hx-vals", before, source, byte, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| (item.name, item.insert_text))
+                .collect::<Vec<_>>();
+        };
+
+        let items = insert("");
+        assert_eq!(
+            items[0],
+            ("email".to_string(), Some(r#"{"email": ""}"#.to_string()))
+        );
+        assert!(items.iter().any(|(name, _)| name == "javascript:"));
+        assert_eq!(
+            insert("js:{count: 1, "),
+            vec![("email".to_string(), Some(r#""email": """#.to_string()))]
+        );
+        assert!(value_documentation("hx-vals", "js:").is_some());
+    }
+
+    #[test]
+    fn test_params_from_the_fields_of_the_form() {
+        init_hx_tags();
+        let source = r#"<form hx-post="/save" hx-params=""><input name="email"><select name="role"></select><textarea name="bio"></textarea></form><input name="outside">"#;
+        let byte = source.find("hx-params").expect("has hx-params") + 11;
+        let names = |before: &str| {
+            return value_completions("hx-params", before, source, byte, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(names(""), ["*", "none", "not", "email", "role", "bio"]);
+        assert_eq!(names("not email, "), ["role", "bio"]);
+        assert_eq!(names("email,"), ["role", "bio"]);
+        assert!(names("*").is_empty());
+    }
+
+    #[test]
+    fn test_swap_oob_strategies_then_selectors() {
+        init_hx_tags();
+
+        let strategies = names("hx-swap-oob", "");
+        assert_eq!(strategies[0], "true");
+        assert!(strategies.contains(&"beforeend".to_string()));
+        assert!(!strategies.contains(&"settle:".to_string()));
+        assert_eq!(
+            strategies
+                .iter()
+                .filter(|name| *name == "beforeend")
+                .count(),
+            1
+        );
+
+        let selectors = names("hx-swap-oob", "beforeend:");
+        assert!(selectors.contains(&"beforeend:#items".to_string()));
+        assert!(selectors.contains(&"beforeend:.item".to_string()));
+
+        assert_eq!(
+            value_documentation("hx-swap-oob", "beforeend:#items"),
+            hx_documentation(Some("hx-swap"), "beforeend")
+        );
+    }
+
+    #[test]
+    fn test_select_oob_selectors_then_targets() {
+        init_config(HtmxConfig::default());
+
+        let selectors = names("hx-select", "");
+        assert!(selectors.contains(&"#items".to_string()));
+        assert!(selectors.contains(&".done".to_string()));
+
+        assert!(names("hx-select-oob", "#items, ").contains(&".item".to_string()));
+        assert!(names("hx-select-oob", "#alert:").contains(&"#alert:#items".to_string()));
+    }
+
+    #[test]
+    fn test_disabled_elt_extended_selectors_per_comma() {
+        let keywords = names("hx-disabled-elt", "");
+        assert!(keywords.contains(&"closest".to_string()));
+        assert!(keywords.contains(&"#items".to_string()));
+
+        assert!(names("hx-disabled-elt", "this, closest ").contains(&"ul".to_string()));
+        assert!(names("hx-disabled-elt", "this, ").contains(&"find".to_string()));
+        assert!(names("hx-disabled-elt", "this ").is_empty());
+        assert!(value_documentation("hx-disabled-elt", "next").is_some());
+    }
+
+    #[test]
+    fn test_include_named_fields_first() {
+        let source = r#"<form><input name="q"><select name="sort"></select><input name="q"></form><div id="extra"></div>"#;
+        let names = |before: &str| {
+            return value_completions("hx-include", before, source, 0, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        let all = names("");
+        assert_eq!(all[..2], ["[name='q']", "[name='sort']"]);
+        assert!(all.contains(&"closest".to_string()));
+        assert!(all.contains(&"#extra".to_string()));
+
+        assert!(names("#extra, closest ").contains(&"form".to_string()));
+        assert!(names("closest ").contains(&"[name='sort']".to_string()));
+        assert!(names("this ").is_empty());
+        assert!(value_documentation("hx-include", "this").is_some());
+    }
+
+    #[test]
+    fn test_inherit_lists_of_inherited_attributes() {
+        let all = names("hx-disinherit", "");
+        assert_eq!(all[0], "*");
+        assert!(all.contains(&"hx-target".to_string()));
+
+        let rest = names("hx-inherit", "hx-target ");
+        assert!(!rest.contains(&"*".to_string()));
+        assert!(!rest.contains(&"hx-target".to_string()));
+        assert!(rest.contains(&"hx-swap".to_string()));
+
+        assert!(names("hx-inherit", "* ").is_empty());
+        assert!(value_documentation("hx-disinherit", "hx-swap")
+            .is_some_and(|desc| desc.contains("don't inherit")));
+    }
+
+    #[test]
+    fn test_indicators_then_stylesheet_classes() {
+        init_index();
+        let root = std::env::temp_dir().join(format!("htmx-lsp-indicator-{}", std::process::id()));
+        std::fs::create_dir_all(&root).expect("creates the folder");
+        std::fs::write(root.join("app.css"), ".spinner { opacity: 0; }").expect("writes css");
+
+        let source = r#"<link rel="stylesheet" href="app.css"><img id="busy" class="htmx-indicator"><button hx-indicator=""></button>"#;
+        let uri = Url::from_file_path(root.join("page.html")).expect("file uri");
+        let indicators: Vec<String> = value_completions("hx-indicator", "", source, 0, &uri)
+            .expect("has a grammar")
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+        std::fs::remove_dir_all(&root).expect("cleanup");
+
+        assert_eq!(
+            indicators[..4],
+            ["closest", "#busy", ".htmx-indicator", ".spinner"]
+        );
+        assert!(names("hx-indicator", "closest ").contains(&"li".to_string()));
+    }
+
+    #[test]
+    fn test_loading_states_classes_selectors_and_paths() {
+        init_index();
+        let root = std::env::temp_dir().join(format!("htmx-lsp-loading-{}", std::process::id()));
+        std::fs::create_dir_all(&root).expect("creates the folder");
+        std::fs::write(root.join("app.css"), ".dim { opacity: 0.5; } .hidden {}")
+            .expect("writes css");
+
+        let source = r#"<link rel="stylesheet" href="app.css"><div data-loading-class=""></div>"#;
+        let uri = Url::from_file_path(root.join("page.html")).expect("file uri");
+        let classes = |before: &str| {
+            return value_completions("data-loading-class", before, source, 0, &uri)
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+        let all = classes("");
+        let rest = classes("dim ");
+        std::fs::remove_dir_all(&root).expect("cleanup");
+
+        assert_eq!(all[..2], ["dim", "hidden"]);
+        assert!(!rest.contains(&"dim".to_string()));
+        assert!(names("data-loading-target", "").contains(&"#items".to_string()));
+
+        INDEX
+            .get()
+            .expect("index initialized")
+            .lock()
+            .expect("index mutex")
+            .routes
+            .insert(
+                "file:///app/save.py".to_string(),
+                vec![(Some("POST".to_string()), "/save".to_string())],
+            );
+        assert!(names("data-loading-path", "").contains(&"/save".to_string()));
+    }
+
+    #[test]
+    fn test_class_operations_then_classes_then_delays() {
+        init_index();
+        let source = r#"<div hx-ext="class-tools"><a class="bar" classes=""></a></div>"#;
+        let byte = source.find("classes=").expect("classes");
+        let classes = |before: &str| {
+            return value_completions("classes", before, source, byte, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        assert_eq!(classes(""), vec!["add", "remove", "toggle"]);
+        assert_eq!(classes("remove foo:1s & "), vec!["add", "remove", "toggle"]);
+        assert_eq!(classes("remove ")[0], "bar");
+        assert!(!classes("add ").contains(&"bar".to_string()));
+        assert_eq!(
+            classes("add foo, toggle bar:"),
+            vec!["bar:100ms", "bar:500ms", "bar:1s"]
+        );
+        assert!(classes("grow ").is_empty());
+    }
+
+    #[test]
+    fn test_multi_swap_ids_then_strategies() {
+        init_hx_tags();
+        let source =
+            r#"<div hx-ext="multi-swap"><a id="a"></a><p id="b"></p><i hx-swap=""></i></div>"#;
+        let byte = source.find("hx-swap").expect("hx-swap");
+        let swaps = |before: &str| {
+            return value_completions("hx-swap", before, source, byte, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        assert!(swaps("").contains(&"multi:".to_string()));
+        assert!(!names("hx-swap", "").contains(&"multi:".to_string()));
+        assert_eq!(swaps("multi:"), vec!["multi:#a", "multi:#b"]);
+        assert_eq!(swaps("multi:#a,"), vec!["#b"]);
+        assert_eq!(swaps("multi:#a:")[1], "multi:#a:outerHTML");
+        assert!(swaps("multi:#a ").is_empty());
+    }
+
+    #[test]
+    fn test_morph_strategies_of_the_loaded_extension() {
+        init_hx_tags();
+        let source = r#"<div hx-ext="morph"><i hx-swap=""></i></div><p hx-ext="morphdom-swap" hx-swap=""></p>"#;
+        let swaps = |element: &str, before: &str| {
+            let byte = source.find(element).expect("element");
+            return value_completions("hx-swap", before, source, byte, &uri())
+                .expect("has a grammar")
+                .into_iter()
+                .map(|item| item.name)
+                .collect::<Vec<_>>();
+        };
+
+        let morph = swaps("<i", "");
+        assert!(morph.ends_with(&[
+            "morph".to_string(),
+            "morph:innerHTML".to_string(),
+            "morph:outerHTML".to_string()
+        ]));
+        assert!(swaps("<i", "morph:").contains(&"morph:outerHTML".to_string()));
+        assert!(swaps("<i", "outerHTML swap:").contains(&"swap:1s".to_string()));
+        assert_eq!(swaps("<p", "").last().map(String::as_str), Some("morphdom"));
+        assert!(hx_documentation(Some("hx-swap"), "morph:innerHTML")
+            .is_some_and(|desc| desc.contains("like innerHTML")));
+    }
+
+    #[test]
+    fn test_hyperscript_openers_then_events() {
+        let openers = names("_", "");
+        assert_eq!(openers[..2], ["on click", "on submit"]);
+        assert!(openers.contains(&"on htmx:afterSwap".to_string()));
+        assert_eq!(names("data-script", "in"), openers);
+
+        let events = names("_", "on htmx:");
+        assert!(events.contains(&"htmx:afterSwap".to_string()));
+        assert!(events.contains(&"keyup".to_string()));
+        assert!(names("_", "on click toggle .hx-").is_empty());
+        assert_eq!(names("_", "on click log me end\non "), events);
+    }
+
+    #[test]
+    fn test_templates_of_the_document_by_id() {
+        init_hx_tags();
+        let source = r#"<div hx-ext="client-side-templates"><button mustache-template=""></button>
+<template id="todo"></template><script id="row" type="text/mustache"></script><script id="app"></script></div>"#;
+        let templates: Vec<String> = value_completions("mustache-template", "", source, 0, &uri())
+            .expect("has a grammar")
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+
+        assert_eq!(templates, vec!["todo", "row"]);
+        assert!(value_completions("my-template", "", source, 0, &uri()).is_none());
+    }
+}
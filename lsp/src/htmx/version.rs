@@ -0,0 +1,83 @@
+//! The htmx release the project uses, from the `htmxVersion` setting or
+//! the htmx script tag of its templates.
+
+use crate::{config::get_config, index::INDEX, text_store::TEXT_STORE};
+
+/// The version in an htmx script source, like `htmx.org@1.9.6` of unpkg,
+/// `libs/htmx/1.9.6/htmx.min.js` of cdnjs or a vendored `htmx-2.0.1.min.js`.
+pub fn script_version(text: &str) -> Option<String> {
+    return text.match_indices("htmx").find_map(|(idx, _)| {
+        let after = &text[idx + "htmx".len()..];
+        let after = after.strip_prefix(".org").unwrap_or(after);
+        let after = after.strip_prefix(['@', '/', '-'])?;
+
+        let version: String = after
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let version = version.trim_end_matches('.');
+        if !version.starts_with(|c: char| c.is_ascii_digit()) || !version.contains('.') {
+            return None;
+        }
+
+        return Some(version.to_string());
+    });
+}
+
+/// The htmx version of the project, the setting wins over what the open
+/// documents and then the indexed files load. `None` stands for the latest
+/// release.
+pub fn project_version() -> Option<String> {
+    if let Some(version) = get_config().htmx_version {
+        return Some(version);
+    }
+
+    let open = TEXT_STORE.get().and_then(|text_store| {
+        let text_store = text_store.lock().ok()?;
+        return text_store
+            .texts
+            .values()
+            .find_map(|text| script_version(text));
+    });
+    if open.is_some() {
+        return open;
+    }
+
+    return INDEX.get().and_then(|index| {
+        let index = index.lock().ok()?;
+        return index.files.values().find_map(|text| script_version(text));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::script_version;
+
+    #[test]
+    fn test_reads_the_version_of_htmx_scripts() {
+        let cases = [
+            (
+                r#"<script src="https://unpkg.com/htmx.org@1.9.6"></script>"#,
+                Some("1.9.6"),
+            ),
+            (
+                r#"<script src="https://unpkg.com/htmx.org@2.0.1/dist/htmx.min.js"></script>"#,
+                Some("2.0.1"),
+            ),
+            (
+                r#"<script src="https://cdnjs.cloudflare.com/ajax/libs/htmx/1.9.12/htmx.min.js">"#,
+                Some("1.9.12"),
+            ),
+            (
+                r#"<script src="/static/js/htmx-2.0.0.min.js"></script>"#,
+                Some("2.0.0"),
+            ),
+            (r#"<script src="/static/htmx.min.js"></script>"#, None),
+            (r#"<div hx-get="/htmx-1"></div>"#, None),
+        ];
+
+        for (text, version) in cases {
+            assert_eq!(script_version(text).as_deref(), version, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use lsp_types::{
+    notification::ShowMessage, CodeLens, Command, MessageType, ShowMessageParams, Url,
+};
+
+use crate::{client::send_notification, config::get_config, symbols::document_endpoints};
+
+pub const SEND_REQUEST_COMMAND: &str = "htmx-lsp.sendRequest";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest part of the body shown in the editor notification
+const PREVIEW_LENGTH: usize = 1000;
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: String,
+    pub body: String,
+}
+
+/// The `Host` header of `url`, with the port when it isn't the default one
+/// and IPv6 addresses in their brackets.
+fn host_header(url: &Url) -> Result<String> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow!("url without host: {}", url))?;
+
+    return Ok(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    });
+}
+
+/// Connects to the first address of `url` that answers in time, an
+/// unreachable dev server fails fast instead of after the OS timeout.
+fn connect(url: &Url) -> Result<TcpStream> {
+    let mut error = anyhow!("no address for {}", url);
+    for addr in url.socket_addrs(|| Some(80))? {
+        match TcpStream::connect_timeout(&addr, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(e) => error = e.into(),
+        }
+    }
+
+    return Err(error);
+}
+
+/// A tiny plain http client for hitting the dev server, HTTP/1.0 keeps the
+/// responses free of chunked encoding.
+pub fn send(method: &str, url: &Url) -> Result<HttpResponse> {
+    if url.scheme() != "http" {
+        return Err(anyhow!("only http urls are supported, got {}", url));
+    }
+
+    let host = host_header(url)?;
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut stream = connect(url)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    // Servers answer htmx requests with partials, so act like htmx does.
+    // Written at once, servers may answer as soon as the first bytes arrive
+    let request = format!(
+        "{method} {path} HTTP/1.0\r\nHost: {host}\r\nHX-Request: true\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes())?;
+
+    let mut raw = vec![];
+    stream.read_to_end(&mut raw)?;
+    let raw = String::from_utf8_lossy(&raw);
+
+    let (head, body) = raw.split_once("\r\n\r\n").unwrap_or((&raw, ""));
+    let status = head
+        .lines()
+        .next()
+        .and_then(|line| line.split_once(' '))
+        .map(|(_, status)| status.to_string())
+        .ok_or_else(|| anyhow!("invalid http response"))?;
+
+    return Ok(HttpResponse {
+        status,
+        body: body.to_string(),
+    });
+}
+
+/// "Send request" lenses over the elements issuing GET and POST requests,
+/// only offered when there is a dev server to send them to.
+pub fn send_request_lenses(source: &str) -> Vec<CodeLens> {
+    let Some(base_url) = get_config().base_url.and_then(|url| Url::parse(&url).ok()) else {
+        return vec![];
+    };
+
+    return document_endpoints(source)
+        .into_iter()
+        .filter(|endpoint| endpoint.method == "GET" || endpoint.method == "POST")
+        .filter_map(|endpoint| {
+            let url = base_url.join(&endpoint.url).ok()?;
+
+            return Some(CodeLens {
+                range: endpoint.range,
+                command: Some(Command {
+                    title: format!("Send request ({} {})", endpoint.method, endpoint.url),
+                    command: SEND_REQUEST_COMMAND.to_string(),
+                    arguments: Some(vec![
+                        serde_json::json!(endpoint.method),
+                        serde_json::json!(url),
+                    ]),
+                }),
+                data: None,
+            });
+        })
+        .collect();
+}
+
+fn show_message(typ: MessageType, message: String) {
+    send_notification::<ShowMessage>(ShowMessageParams { typ, message });
+}
+
+/// Runs the request in the background and shows the response in the
+/// editor once it arrives.
+pub fn execute_send_request(arguments: Vec<serde_json::Value>) -> Result<serde_json::Value> {
+    let method = arguments
+        .first()
+        .and_then(|m| m.as_str())
+        .map(str::to_string);
+    let url = arguments
+        .get(1)
+        .and_then(|u| u.as_str())
+        .and_then(|u| Url::parse(u).ok());
+
+    let (Some(method), Some(url)) = (method, url) else {
+        return Err(anyhow!(
+            "{} expects a method and an url",
+            SEND_REQUEST_COMMAND
+        ));
+    };
+
+    std::thread::spawn(move || {
+        debug!("sending {} {}", method, url);
+
+        match send(&method, &url) {
+            Ok(response) => {
+                debug!(
+                    "{} {} -> {}, {} bytes",
+                    method,
+                    url,
+                    response.status,
+                    response.body.len()
+                );
+
+                let preview: String = response.body.chars().take(PREVIEW_LENGTH).collect();
+                show_message(
+                    MessageType::INFO,
+                    format!(
+                        "{} {} → {}\n\n{}",
+                        method,
+                        url.path(),
+                        response.status,
+                        preview
+                    ),
+                );
+            }
+            Err(e) => show_message(
+                MessageType::ERROR,
+                format!("{} {} failed: {}", method, url, e),
+            ),
+        }
+    });
+
+    return Ok(serde_json::Value::Null);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    use super::{host_header, send};
+    use lsp_types::Url;
+
+    #[test]
+    fn test_sends_htmx_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("binds");
+        let port = listener.local_addr().expect("has address").port();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accepts");
+            let mut request = vec![];
+            let mut chunk = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = stream.read(&mut chunk).expect("reads");
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&chunk[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.0 200 OK\r\nContent-Type: text/html\r\n\r\n<li>one</li>")
+                .expect("writes");
+            return String::from_utf8_lossy(&request).to_string();
+        });
+
+        let url = Url::parse(&format!("http://127.0.0.1:{}/items?page=2", port)).unwrap();
+        let response = send("GET", &url).expect("sends");
+        let request = server.join().expect("server finishes");
+
+        assert!(request.starts_with("GET /items?page=2 HTTP/1.0\r\n"));
+        assert!(request.contains("HX-Request: true\r\n"));
+        assert!(request.contains(&format!("Host: 127.0.0.1:{}\r\n", port)));
+        assert_eq!(response.status, "200 OK");
+        assert_eq!(response.body, "<li>one</li>");
+    }
+
+    #[test]
+    fn test_host_header_keeps_the_port_and_brackets() {
+        let host = |url: &str| host_header(&Url::parse(url).expect("valid url")).expect("has host");
+
+        assert_eq!(host("http://localhost:8000/items"), "localhost:8000");
+        assert_eq!(host("http://localhost:80/items"), "localhost");
+        assert_eq!(host("http://[::1]:8000/"), "[::1]:8000");
+        assert_eq!(host("http://example.com/"), "example.com");
+    }
+}
@@ -0,0 +1,159 @@
+use lsp_types::{Location, Range, Url};
+
+use crate::{
+    encoding::byte_offset_to_position, htmx::canonical_name, tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+};
+
+/// Attributes whose value is a css selector that can point at an element id.
+pub const SELECTOR_ATTRIBUTES: [&str; 4] = ["hx-target", "hx-include", "hx-indicator", "hx-select"];
+
+/// An element id, either declared with `id="foo"` or referenced by a
+/// selector like `hx-target="#foo"`, the bytes span the id name only.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdOccurrence {
+    pub id: String,
+    /// The attribute holding the occurrence, `id` for declarations
+    pub attribute: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl IdOccurrence {
+    pub fn contains(&self, byte: usize) -> bool {
+        return self.start <= byte && byte <= self.end;
+    }
+
+    pub fn location(&self, uri: Url, source: &str) -> Location {
+        return Location {
+            uri,
+            range: Range {
+                start: byte_offset_to_position(source, self.start),
+                end: byte_offset_to_position(source, self.end),
+            },
+        };
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DocumentIds {
+    pub definitions: Vec<IdOccurrence>,
+    pub references: Vec<IdOccurrence>,
+}
+
+impl DocumentIds {
+    /// The id declared or referenced at `byte`, the "#" of a selector counts
+    /// as part of the reference.
+    pub fn id_at(&self, byte: usize) -> Option<&IdOccurrence> {
+        return self
+            .references
+            .iter()
+            .find(|reference| reference.contains(byte + 1))
+            .or_else(|| self.definitions.iter().find(|d| d.contains(byte)));
+    }
+}
+
+pub fn is_id_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '-' || c == '_';
+}
+
+/// The `#id` parts of a selector, eg. both ids of `closest #a, #b`.
+fn selector_ids(attribute: &str, value: &str, offset: usize) -> Vec<IdOccurrence> {
+    return value
+        .match_indices('#')
+        .filter_map(|(idx, _)| {
+            let start = idx + 1;
+            let len = value[start..]
+                .find(|c: char| !is_id_char(c))
+                .unwrap_or(value.len() - start);
+            if len == 0 {
+                return None;
+            }
+
+            return Some(IdOccurrence {
+                id: value[start..start + len].to_string(),
+                attribute: attribute.to_string(),
+                start: offset + start,
+                end: offset + start + len,
+            });
+        })
+        .collect();
+}
+
+pub fn document_ids(source: &str) -> DocumentIds {
+    let Some(tree) = parse_html(source) else {
+        return DocumentIds::default();
+    };
+
+    let mut ids = DocumentIds::default();
+    for attribute in query_attributes(tree.root_node(), source) {
+        let (Some(value), Some(range)) = (attribute.value.as_deref(), attribute.value_range) else {
+            continue;
+        };
+
+        if attribute.name == "id" {
+            let id = value.trim();
+            if id.is_empty() || !id.chars().all(is_id_char) {
+                continue;
+            }
+
+            let start = range.start_byte + value.find(id).unwrap_or(0);
+            ids.definitions.push(IdOccurrence {
+                id: id.to_string(),
+                attribute: attribute.name.clone(),
+                start,
+                end: start + id.len(),
+            });
+        } else if SELECTOR_ATTRIBUTES.contains(&canonical_name(&attribute.name)) {
+            ids.references.extend(selector_ids(
+                canonical_name(&attribute.name),
+                value,
+                range.start_byte,
+            ));
+        }
+    }
+
+    return ids;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document_ids, IdOccurrence};
+
+    #[test]
+    fn test_collects_definitions_and_references() {
+        let text = r##"<div id="list"></div>
+<button hx-target="closest #list, #other" hx-include="[name='q']" hx-get="#list"></button>"##;
+
+        let ids = document_ids(text);
+
+        let list = text.find("list").expect("has list");
+        assert_eq!(
+            ids.definitions,
+            vec![IdOccurrence {
+                id: "list".to_string(),
+                attribute: "id".to_string(),
+                start: list,
+                end: list + 4,
+            }]
+        );
+
+        let names: Vec<_> = ids.references.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(names, vec!["list", "other"]);
+        assert_eq!(
+            &text[ids.references[1].start..ids.references[1].end],
+            "other"
+        );
+    }
+
+    #[test]
+    fn test_finds_id_at_cursor() {
+        let text = r##"<div id="list"></div><form hx-select="#list"></form>"##;
+        let ids = document_ids(text);
+
+        let hash = text.find('#').expect("has selector");
+        assert_eq!(ids.id_at(hash).map(|id| id.id.as_str()), Some("list"));
+        assert_eq!(ids.id_at(10).map(|id| id.id.as_str()), Some("list"));
+        assert_eq!(ids.id_at(2), None);
+    }
+}
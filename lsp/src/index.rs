@@ -0,0 +1,460 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use log::{debug, warn};
+use lsp_types::{
+    request::RegisterCapability, DidChangeWatchedFilesRegistrationOptions, FileSystemWatcher,
+    GlobPattern, Registration, RegistrationParams, Url, WorkspaceFolder,
+};
+use walkdir::{DirEntry, WalkDir};
+
+use crate::{
+    client::send_request,
+    css::css_classes,
+    progress::Progress,
+    routes::{is_route_source, scan_routes, ScannedRoute, ROUTE_SOURCE_EXTENSIONS},
+    symbols::{document_endpoints, Endpoint},
+    text_store::{get_text_document, TEXT_STORE},
+    uri::uri_key,
+    usage::{document_usage, Usage},
+    workspace::WORKSPACE,
+};
+
+/// Text of the template files found in the workspace folders, including the
+/// ones never opened in the editor, keyed by uri.
+pub struct WorkspaceIndex {
+    pub files: HashMap<String, String>,
+    pub endpoints: HashMap<String, Vec<Endpoint>>,
+    /// Class names of the stylesheets, keyed by uri
+    pub stylesheets: HashMap<String, Vec<String>>,
+    /// Routes declared by the backend source files, keyed by uri
+    pub routes: HashMap<String, Vec<ScannedRoute>>,
+    /// How often each template uses the htmx attributes, keyed by uri
+    pub usage: HashMap<String, Usage>,
+    /// The uri of each indexed file as found on disk, keys are for lookups
+    /// only
+    pub uris: HashMap<String, Url>,
+}
+
+impl WorkspaceIndex {
+    /// The uri to hand back to the client for the file indexed under `key`.
+    pub fn uri(&self, key: &str) -> Option<Url> {
+        return self.uris.get(key).cloned().or_else(|| Url::parse(key).ok());
+    }
+}
+
+pub static INDEX: OnceLock<Arc<Mutex<WorkspaceIndex>>> = OnceLock::new();
+pub fn init_index() {
+    _ = INDEX.set(Arc::new(Mutex::new(WorkspaceIndex {
+        files: HashMap::new(),
+        endpoints: HashMap::new(),
+        stylesheets: HashMap::new(),
+        routes: HashMap::new(),
+        usage: HashMap::new(),
+        uris: HashMap::new(),
+    })));
+}
+
+pub const INDEXED_EXTENSIONS: [&str; 14] = [
+    "html", "htm", "jinja", "jinja2", "j2", "njk", "hbs", "mustache", "tmpl", "gohtml", "templ",
+    "erb", "ejs", "twig",
+];
+
+pub const STYLESHEET_EXTENSIONS: [&str; 1] = ["css"];
+
+const IGNORED_DIRECTORIES: [&str; 5] = ["node_modules", "target", "dist", "build", "vendor"];
+
+pub fn is_indexable(path: &Path) -> bool {
+    return path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| INDEXED_EXTENSIONS.contains(&ext))
+        .unwrap_or(false);
+}
+
+pub fn is_stylesheet(path: &Path) -> bool {
+    return path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| STYLESHEET_EXTENSIONS.contains(&ext))
+        .unwrap_or(false);
+}
+
+/// Whether changes to the file matter to the index.
+pub fn is_tracked(path: &Path) -> bool {
+    return is_indexable(path) || is_stylesheet(path) || is_route_source(path);
+}
+
+fn is_ignored(entry: &DirEntry) -> bool {
+    let name = entry.file_name().to_str().unwrap_or("");
+    return entry.depth() > 0
+        && entry.file_type().is_dir()
+        && (name.starts_with('.') || IGNORED_DIRECTORIES.contains(&name));
+}
+
+/// Lists the indexable files below a workspace folder.
+pub fn workspace_files(folder: &WorkspaceFolder) -> Vec<std::path::PathBuf> {
+    let Ok(root) = folder.uri.to_file_path() else {
+        return vec![];
+    };
+
+    return WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| !is_ignored(entry))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| is_tracked(entry.path()))
+        .map(|entry| entry.into_path())
+        .collect();
+}
+
+pub fn index_file(path: &Path) {
+    match fs::read_to_string(path) {
+        Ok(text) => index_text(path, text),
+        Err(e) => warn!("failed to index {:?}: {:?}", path, e),
+    }
+}
+
+/// Indexes `text` as the content of the file at `path`, like the text a
+/// document is saved with.
+pub fn index_text(path: &Path, text: String) {
+    let Ok(uri) = Url::from_file_path(path) else {
+        return;
+    };
+
+    let key = uri_key(&uri);
+    INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned")
+        .uris
+        .insert(key.clone(), uri);
+
+    // Stylesheets only matter for their classes, they aren't documents
+    if is_stylesheet(path) {
+        let classes = css_classes(&text);
+        INDEX
+            .get()
+            .expect("index not initialized")
+            .lock()
+            .expect("index mutex poisoned")
+            .stylesheets
+            .insert(key, classes);
+        return;
+    }
+
+    if is_route_source(path) {
+        let routes = scan_routes(&text);
+        INDEX
+            .get()
+            .expect("index not initialized")
+            .lock()
+            .expect("index mutex poisoned")
+            .routes
+            .insert(key, routes);
+        return;
+    }
+
+    let endpoints = document_endpoints(&text);
+    let usage = document_usage(&text);
+
+    let mut index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+    index.endpoints.insert(key.clone(), endpoints);
+    index.usage.insert(key.clone(), usage);
+    index.files.insert(key, text);
+}
+
+pub fn remove_file(uri: &Url) {
+    let mut index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+    index.files.remove(&uri_key(uri));
+    index.endpoints.remove(&uri_key(uri));
+    index.stylesheets.remove(&uri_key(uri));
+    index.routes.remove(&uri_key(uri));
+    index.usage.remove(&uri_key(uri));
+    index.uris.remove(&uri_key(uri));
+}
+
+/// The uris of every indexed file, templates, stylesheets and route
+/// sources alike.
+pub fn indexed_uris() -> Vec<Url> {
+    let index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+
+    let mut uris: Vec<&String> = index
+        .files
+        .keys()
+        .chain(index.stylesheets.keys())
+        .chain(index.routes.keys())
+        .collect();
+    uris.sort();
+    uris.dedup();
+    return uris.into_iter().filter_map(|uri| index.uri(uri)).collect();
+}
+
+/// Text of every known document of the workspace folder `uri` is in, the
+/// open ones first since their content is newer than what is on disk, with
+/// the uri the client knows it by. Documents outside of every folder go
+/// together.
+pub fn workspace_documents(uri: &Url) -> Vec<(Url, String)> {
+    let text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+    let open: Vec<String> = text_store.texts.keys().cloned().collect();
+    let mut documents: Vec<(Url, String)> = text_store
+        .texts
+        .keys()
+        .filter_map(|key| Some((text_store.uri(key)?, text_store.html(key)?)))
+        .collect();
+    drop(text_store);
+
+    let index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+
+    for (key, text) in index.files.iter() {
+        if open.contains(key) {
+            continue;
+        }
+        if let Some(uri) = index.uri(key) {
+            documents.push((uri, text.clone()));
+        }
+    }
+
+    // Unrelated projects of a monorepo can use the same ids
+    let Some(workspace) = WORKSPACE.get() else {
+        return documents;
+    };
+    let workspace = workspace.lock().expect("workspace mutex poisoned");
+    let root = workspace.folder_for(uri).map(|folder| &folder.uri);
+    documents
+        .retain(|(document, _)| workspace.folder_for(document).map(|folder| &folder.uri) == root);
+
+    return documents;
+}
+
+/// The class names of the stylesheets of the workspace, with the uri of the
+/// stylesheet defining them.
+pub fn workspace_classes() -> Vec<(String, String)> {
+    let index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+
+    let mut classes: Vec<(String, String)> = index
+        .stylesheets
+        .iter()
+        .flat_map(|(key, classes)| {
+            let uri = index.uri(key).map_or(key.clone(), |uri| uri.to_string());
+            return classes
+                .iter()
+                .map(move |class| (class.clone(), uri.clone()));
+        })
+        .collect();
+    classes.sort();
+    return classes;
+}
+
+/// Text of the document at `uri`, as open in the editor or as on disk.
+pub fn document_text(uri: &Url) -> Option<String> {
+    if let Some(text) = get_text_document(uri.clone()) {
+        return Some(text);
+    }
+
+    return fs::read_to_string(uri.to_file_path().ok()?).ok();
+}
+
+pub fn index_workspace(folders: Vec<WorkspaceFolder>, report_progress: bool) {
+    let files: Vec<_> = folders.iter().flat_map(workspace_files).collect();
+    debug!("indexing {} workspace files", files.len());
+
+    let mut progress = report_progress.then(|| Progress::begin("index", "indexing", files.len()));
+
+    for (done, path) in files.iter().enumerate() {
+        index_file(path);
+
+        if let Some(progress) = progress.as_mut() {
+            progress.report(done + 1);
+        }
+    }
+
+    if let Some(progress) = progress {
+        progress.end(format!("indexed {} files", files.len()));
+    }
+}
+
+/// Asks the client to tell us about tracked files changing on disk.
+pub fn register_file_watchers() {
+    let extensions: Vec<&str> = INDEXED_EXTENSIONS
+        .iter()
+        .chain(STYLESHEET_EXTENSIONS.iter())
+        .chain(ROUTE_SOURCE_EXTENSIONS.iter())
+        .copied()
+        .collect();
+    let glob_pattern = format!("**/*.{{{}}}", extensions.join(","));
+    let options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String(glob_pattern),
+            kind: None,
+        }],
+    };
+
+    send_request::<RegisterCapability>(RegistrationParams {
+        registrations: vec![Registration {
+            id: "htmx-lsp/watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(options).ok(),
+        }],
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use lsp_types::{Url, WorkspaceFolder};
+
+    use super::{
+        init_index, is_indexable, is_stylesheet, is_tracked, workspace_documents, workspace_files,
+    };
+    use crate::{
+        text_store::{init_text_store, TEXT_STORE},
+        uri::uri_key,
+        workspace::{init_workspace, Workspace, WORKSPACE},
+    };
+
+    #[test]
+    fn test_indexable_extensions() {
+        assert!(is_indexable(Path::new("templates/index.html")));
+        assert!(is_indexable(Path::new("templates/base.jinja2")));
+        assert!(!is_indexable(Path::new("src/main.rs")));
+        assert!(!is_indexable(Path::new("Makefile")));
+
+        assert!(is_stylesheet(Path::new("static/app.css")));
+        assert!(!is_indexable(Path::new("static/app.css")));
+
+        assert!(is_tracked(Path::new("app/views.py")));
+        assert!(!is_tracked(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn test_lists_workspace_files_skipping_ignored_directories() {
+        let root = std::env::temp_dir().join(format!("htmx-lsp-index-{}", std::process::id()));
+        fs::create_dir_all(root.join("templates")).expect("create templates");
+        fs::create_dir_all(root.join("node_modules/pkg")).expect("create node_modules");
+        fs::write(root.join("templates/index.html"), "<div></div>").expect("write");
+        fs::write(root.join("templates/notes.txt"), "").expect("write");
+        fs::write(root.join("node_modules/pkg/index.html"), "").expect("write");
+
+        let folder = WorkspaceFolder {
+            uri: Url::from_directory_path(&root).expect("valid path"),
+            name: "root".to_string(),
+        };
+        let files = workspace_files(&folder);
+        fs::remove_dir_all(&root).expect("cleanup");
+
+        assert_eq!(files, vec![root.join("templates/index.html")]);
+    }
+
+    #[test]
+    fn test_documents_keep_the_uri_the_client_sent() {
+        init_text_store();
+        init_index();
+        let uri = Url::parse("file:///C:/site/Keyed%20Page.html").expect("valid uri");
+        let key = uri_key(&uri);
+        assert_ne!(key, uri.to_string());
+
+        let mut text_store = TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex");
+        text_store.uris.insert(key.clone(), uri.clone());
+        text_store.texts.insert(key, "<div></div>".to_string());
+        drop(text_store);
+
+        let documents = workspace_documents(&uri);
+        assert!(documents.iter().any(|(document, _)| *document == uri));
+    }
+
+    #[test]
+    fn test_open_scripts_are_read_for_their_html() {
+        init_text_store();
+        init_index();
+        let uri = Url::parse("file:///app/rows.js").expect("valid uri");
+        let source = r#"el.innerHTML = '<a id="row">';"#;
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .texts
+            .insert(uri_key(&uri), source.to_string());
+
+        let documents = workspace_documents(&uri);
+        let (_, html) = documents
+            .iter()
+            .find(|(document, _)| *document == uri)
+            .expect("open script");
+        assert_eq!(html.trim(), r#"<a id="row">"#);
+    }
+
+    #[test]
+    fn test_documents_stay_within_their_workspace_folder() {
+        init_text_store();
+        init_index();
+        init_workspace(Workspace::default());
+        let folder = |uri: &str| WorkspaceFolder {
+            uri: Url::parse(uri).expect("valid uri"),
+            name: uri.to_string(),
+        };
+        WORKSPACE
+            .get()
+            .expect("workspace initialized")
+            .lock()
+            .expect("workspace mutex")
+            .folders
+            .extend([folder("file:///mono/shop"), folder("file:///mono/blog")]);
+
+        let shop = Url::parse("file:///mono/shop/index.html").expect("valid uri");
+        let blog = Url::parse("file:///mono/blog/index.html").expect("valid uri");
+        let mut text_store = TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex");
+        for uri in [&shop, &blog] {
+            text_store
+                .texts
+                .insert(uri_key(uri), r#"<div id="main"></div>"#.to_string());
+        }
+        drop(text_store);
+
+        let documents: Vec<Url> = workspace_documents(&shop)
+            .into_iter()
+            .map(|(uri, _)| uri)
+            .collect();
+        assert!(documents.contains(&shop));
+        assert!(!documents.contains(&blog));
+    }
+}
@@ -0,0 +1,151 @@
+use lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, Range};
+
+use crate::{dom::Dom, encoding::byte_offset_to_position, links::REQUEST_ATTRIBUTES};
+
+/// Attributes an element picks up from its ancestors when it issues a request.
+pub const INHERITED_ATTRIBUTES: [&str; 17] = [
+    "hx-target",
+    "hx-swap",
+    "hx-select",
+    "hx-confirm",
+    "hx-boost",
+    "hx-indicator",
+    "hx-include",
+    "hx-params",
+    "hx-headers",
+    "hx-vals",
+    "hx-push-url",
+    "hx-replace-url",
+    "hx-sync",
+    "hx-ext",
+    "hx-encoding",
+    "hx-prompt",
+    "hx-disabled-elt",
+];
+
+fn hint(source: &str, byte: usize, label: String) -> InlayHint {
+    return InlayHint {
+        position: byte_offset_to_position(source, byte),
+        label: InlayHintLabel::String(label),
+        kind: Some(InlayHintKind::PARAMETER),
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(true),
+        padding_right: Some(false),
+        data: None,
+    };
+}
+
+/// Annotates the elements issuing requests with the attributes they
+/// inherit, eg. `⇐ hx-target=#list from <div id=page>`.
+fn inherited_hints(dom: &Dom, source: &str) -> Vec<InlayHint> {
+    let mut hints = vec![];
+
+    for (idx, element) in dom.elements.iter().enumerate() {
+        let issues_request = REQUEST_ATTRIBUTES
+            .iter()
+            .any(|name| element.attribute(name).is_some());
+        if !issues_request {
+            continue;
+        }
+
+        for attribute in INHERITED_ATTRIBUTES {
+            if element.attribute(attribute).is_some() {
+                continue;
+            }
+
+            if let Some((ancestor, value)) = dom.inherited(idx, attribute) {
+                let label = format!(
+                    "⇐ {}={} from {}",
+                    attribute,
+                    value,
+                    dom.elements[ancestor].opening()
+                );
+                hints.push(hint(source, element.tag_range.end_byte, label));
+            }
+        }
+    }
+
+    return hints;
+}
+
+/// Shows where the swap of an element with hx-target lands, eg. `→ div#results`.
+fn target_hints(dom: &Dom, source: &str) -> Vec<InlayHint> {
+    return dom
+        .elements
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, element)| {
+            let attribute = element.attribute("hx-target")?;
+            let target = dom.resolve_target(idx, attribute.value.as_deref()?)?;
+            let label = format!("→ {}", dom.elements[target].selector());
+
+            // Past the closing quote of the value
+            let value_end = attribute.value_range?.end_byte;
+            let end = match source[value_end..].starts_with(['"', '\'']) {
+                true => value_end + 1,
+                false => value_end,
+            };
+            return Some(hint(source, end, label));
+        })
+        .collect();
+}
+
+pub fn inlay_hints(source: &str, range: Range) -> Vec<InlayHint> {
+    let dom = Dom::parse(source);
+
+    return inherited_hints(&dom, source)
+        .into_iter()
+        .chain(target_hints(&dom, source))
+        .filter(|hint| range.start <= hint.position && hint.position <= range.end)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inlay_hints;
+    use lsp_types::{InlayHintLabel, Position, Range};
+
+    fn labels(text: &str) -> Vec<(Position, String)> {
+        let everything = Range::new(Position::new(0, 0), Position::new(u32::MAX, 0));
+
+        return inlay_hints(text, everything)
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("expected a plain label"),
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_hints_inherited_attributes_and_targets() {
+        let text = r##"<div id="page" hx-target="#list" hx-swap="outerHTML">
+  <ul id="list"></ul>
+  <section hx-swap="innerHTML">
+    <button hx-get="/more">more</button>
+    <button hx-get="/less" hx-target="this">less</button>
+  </section>
+</div>"##;
+
+        assert_eq!(
+            labels(text),
+            vec![
+                (
+                    Position::new(3, 11),
+                    "⇐ hx-target=#list from <div id=page>".to_string()
+                ),
+                (
+                    Position::new(3, 11),
+                    "⇐ hx-swap=innerHTML from <section>".to_string()
+                ),
+                (
+                    Position::new(4, 11),
+                    "⇐ hx-swap=innerHTML from <section>".to_string()
+                ),
+                (Position::new(0, 32), "→ ul#list".to_string()),
+                (Position::new(4, 43), "→ button".to_string()),
+            ]
+        );
+    }
+}
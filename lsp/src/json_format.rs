@@ -0,0 +1,176 @@
+use lsp_types::{FormattingOptions, Range, TextEdit};
+
+use crate::{
+    config::JsonStyle,
+    encoding::{byte_offset_to_position, position_to_byte_offset},
+    htmx::canonical_name,
+    tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+};
+
+/// Attributes whose value is a JSON object.
+const JSON_ATTRIBUTES: [&str; 2] = ["hx-vals", "hx-headers"];
+
+/// Re-lays out valid JSON without reordering its keys, `indent` is None to
+/// put everything on one line. Continuation lines start with `margin`.
+fn layout(json: &str, indent: Option<&str>, margin: &str) -> String {
+    let mut out = String::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = json.trim().chars().peekable();
+
+    let newline = |out: &mut String, depth: usize| {
+        if let Some(indent) = indent {
+            out.push('\n');
+            out.push_str(margin);
+            out.push_str(&indent.repeat(depth));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '{' | '[' => {
+                out.push(c);
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if chars
+                    .peek()
+                    .is_some_and(|next| *next == '}' || *next == ']')
+                {
+                    continue;
+                }
+                depth += 1;
+                newline(&mut out, depth);
+            }
+            '}' | ']' => {
+                if !out.ends_with(['{', '[']) {
+                    depth = depth.saturating_sub(1);
+                    newline(&mut out, depth);
+                }
+                out.push(c);
+            }
+            ',' => {
+                out.push(c);
+                newline(&mut out, depth);
+            }
+            ':' => out.push_str(if indent.is_some() { ": " } else { ":" }),
+            c if c.is_whitespace() => {}
+            c => out.push(c),
+        }
+    }
+
+    return out;
+}
+
+/// Formats the JSON of the hx-vals and hx-headers values covered by `range`.
+pub fn format_json_values(
+    source: &str,
+    range: Range,
+    options: &FormattingOptions,
+    style: JsonStyle,
+) -> Vec<TextEdit> {
+    let (Some(start), Some(end)) = (
+        position_to_byte_offset(source, range.start),
+        position_to_byte_offset(source, range.end),
+    ) else {
+        return vec![];
+    };
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+
+    let indent = match options.insert_spaces {
+        true => " ".repeat(options.tab_size as usize),
+        false => "\t".to_string(),
+    };
+
+    return query_attributes(tree.root_node(), source)
+        .into_iter()
+        .filter(|attribute| JSON_ATTRIBUTES.contains(&canonical_name(&attribute.name)))
+        .filter_map(|attribute| {
+            let value = attribute.value.as_deref()?;
+            let value_range = attribute.value_range?;
+            if value_range.start_byte < start || value_range.end_byte > end {
+                return None;
+            }
+
+            // Values like "js:{...}" are javascript, not JSON
+            serde_json::from_str::<serde_json::Value>(value).ok()?;
+
+            // A double quoted attribute can't hold the quotes of JSON strings
+            let quote = source[..value_range.start_byte].chars().last();
+            if quote != Some('\'') && value.contains('"') {
+                return None;
+            }
+
+            let line_start = source[..attribute.range.start_byte]
+                .rfind('\n')
+                .map_or(0, |idx| idx + 1);
+            let margin: String = source[line_start..]
+                .chars()
+                .take_while(|c| *c == ' ' || *c == '\t')
+                .collect();
+
+            let formatted = match style {
+                JsonStyle::Pretty => layout(value, Some(&indent), &margin),
+                JsonStyle::Compact => layout(value, None, ""),
+            };
+            if formatted == value {
+                return None;
+            }
+
+            return Some(TextEdit {
+                range: Range {
+                    start: byte_offset_to_position(source, value_range.start_byte),
+                    end: byte_offset_to_position(source, value_range.end_byte),
+                },
+                new_text: formatted,
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::layout;
+
+    #[test]
+    fn test_pretty_layout_keeps_key_order() {
+        let json = r#"{"z": 1, "a": [1, 2], "e": {}, "s": "a, b: {c}"}"#;
+
+        assert_eq!(
+            layout(json, Some("  "), "    "),
+            r#"{
+      "z": 1,
+      "a": [
+        1,
+        2
+      ],
+      "e": {},
+      "s": "a, b: {c}"
+    }"#
+        );
+    }
+
+    #[test]
+    fn test_compact_layout() {
+        let json = "{\n  \"a\": \"x \\\" y\",\n  \"b\": [ 1, 2 ]\n}";
+
+        assert_eq!(layout(json, None, ""), r#"{"a":"x \" y","b":[1,2]}"#);
+    }
+}
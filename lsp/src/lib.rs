@@ -1,95 +1,243 @@
+mod client;
+mod code_lens;
+mod commands;
+mod completion;
+mod config;
+mod css;
+mod definition;
+mod diagnostics;
+mod dom;
+mod embedded;
+mod encoding;
+mod extensions;
+mod folding;
 mod handle;
+mod hover;
 mod htmx;
+mod http;
+mod ids;
+mod index;
+mod inlay_hints;
+mod json_format;
+mod links;
+mod message_queue;
+mod normalize;
+mod on_type;
+mod progress;
+mod references;
+mod rename;
+mod routes;
+mod scaffolds;
+mod selection;
+mod semantic_tokens;
+mod symbols;
 mod text_store;
 mod tree_sitter;
 mod tree_sitter_querier;
+mod uri;
+mod usage;
+mod workspace;
 
 use anyhow::Result;
-use htmx::HxCompletion;
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use lsp_types::{
-    CompletionItem, CompletionItemKind, CompletionList, InitializeParams, ServerCapabilities,
-    TextDocumentSyncCapability, TextDocumentSyncKind, WorkDoneProgressOptions,
+    CodeLensOptions, DocumentLinkOptions, DocumentOnTypeFormattingOptions, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, InitializeParams,
+    LinkedEditingRangeServerCapabilities, OneOf, RenameOptions, SaveOptions,
+    SelectionRangeProviderCapability, SemanticTokensFullOptions, SemanticTokensOptions,
+    SemanticTokensServerCapabilities, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions,
+    WorkDoneProgressOptions, WorkspaceFoldersServerCapabilities, WorkspaceServerCapabilities,
 };
 
-use lsp_server::{Connection, Message, Response};
+use lsp_server::{Connection, ErrorCode, Message, RequestId, Response};
+
+pub use client::ClientLogWriter;
 
 use crate::{
+    client::{close_client, init_client},
+    commands::command_names,
+    completion::{init_completion_support, to_completion_list, TRIGGER_CHARACTERS},
+    config::{init_config, HtmxConfig},
+    diagnostics::{init_diagnostic_refresh, init_push_diagnostics, DIAGNOSTIC_SOURCE},
+    encoding::init_position_encoding,
     handle::{handle_notification, handle_other, handle_request, HtmxResult},
     htmx::init_hx_tags,
-    text_store::init_text_store,
+    index::{index_workspace, init_index, register_file_watchers},
+    message_queue::MessageQueue,
+    text_store::{init_text_store, TEXT_STORE},
+    usage::workspace_usage,
+    workspace::{init_workspace, Workspace},
 };
 
-fn to_completion_list(items: Vec<HxCompletion>) -> CompletionList {
-    return CompletionList {
-        is_incomplete: true,
-        items: items
-            .iter()
-            .map(|x| {
-                return CompletionItem {
-                    label: x.name.clone(),
-                    label_details: None,
-                    kind: Some(CompletionItemKind::TEXT),
-                    detail: Some(x.desc.clone()),
-                    documentation: None,
-                    deprecated: Some(false),
-                    preselect: None,
-                    sort_text: None,
-                    filter_text: None,
-                    insert_text: None,
-                    insert_text_format: None,
-                    insert_text_mode: None,
-                    text_edit: None,
-                    additional_text_edits: None,
-                    command: None,
-                    commit_characters: None,
-                    data: None,
-                    tags: None,
-                };
-            })
-            .collect(),
+fn response<T: serde::Serialize>(id: RequestId, result: T) -> Option<Response> {
+    let str = match serde_json::to_value(result) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("failed to serialize response: {:?}", e);
+            return None;
+        }
     };
+
+    return Some(Response {
+        id,
+        result: Some(str),
+        error: None,
+    });
 }
 
-fn main_loop(connection: Connection, params: serde_json::Value) -> Result<()> {
-    let _params: InitializeParams = serde_json::from_value(params).unwrap();
+fn to_response(result: HtmxResult) -> Option<Response> {
+    return match result {
+        HtmxResult::AttributeCompletion(c) => {
+            let list = to_completion_list(
+                c.items,
+                c.attribute,
+                c.is_incomplete,
+                c.token,
+                c.tag.as_deref(),
+                &workspace_usage(),
+            );
+            response(c.id, list)
+        }
+        HtmxResult::CompletionResolve(c) => response(c.id, c.item),
+        HtmxResult::Diagnostic(d) => response(d.id, d.report),
+        HtmxResult::Hover(h) => response(h.id, h.hover),
+        HtmxResult::DocumentLink(l) => response(l.id, l.links),
+        HtmxResult::Definition(d) => response(d.id, d.definition),
+        HtmxResult::References(r) => response(r.id, r.locations),
+        HtmxResult::DocumentHighlight(h) => response(h.id, h.highlights),
+        HtmxResult::DocumentSymbol(s) => response(s.id, s.symbols),
+        HtmxResult::WorkspaceSymbol(s) => response(s.id, s.symbols),
+        HtmxResult::SemanticTokens(t) => response(t.id, t.tokens),
+        HtmxResult::InlayHint(h) => response(h.id, h.hints),
+        HtmxResult::CodeLens(l) => response(l.id, l.lenses),
+        HtmxResult::LinkedEditingRange(l) => response(l.id, l.ranges),
+        HtmxResult::SelectionRange(r) => response(r.id, r.ranges),
+        HtmxResult::FoldingRange(r) => response(r.id, r.ranges),
+        HtmxResult::ExecuteCommand(c) => response(c.id, c.result),
+        HtmxResult::Error(e) => Some(Response::new_err(e.id, e.code as i32, e.message)),
+        HtmxResult::TextEdits(e) => response(e.id, e.edits),
+        HtmxResult::ResolveTarget(r) => response(r.id, r.result),
+        HtmxResult::RequestFlow(r) => response(r.id, r.flow),
+        HtmxResult::Status(s) => response(s.id, s.status),
+        HtmxResult::PrepareRename(r) => response(r.id, r.response),
+        HtmxResult::Rename(r) => response(r.id, r.edit),
+    };
+}
+
+fn main_loop(connection: Connection, params: InitializeParams) -> Result<()> {
+    init_client(connection.sender.clone());
+
+    let workspace = Workspace::from_initialize_params(&params);
+    let folders = workspace.folders.clone();
+    init_workspace(workspace);
+    init_config(HtmxConfig::from_initialization_options(
+        params.initialization_options,
+    ));
+
+    let can_watch_files = params
+        .capabilities
+        .workspace
+        .and_then(|w| w.did_change_watched_files)
+        .and_then(|w| w.dynamic_registration)
+        .unwrap_or(false);
+    if can_watch_files {
+        register_file_watchers();
+    }
+
+    let can_report_progress = params
+        .capabilities
+        .window
+        .and_then(|w| w.work_done_progress)
+        .unwrap_or(false);
+
+    // Reading the whole workspace can take a while, don't hold up requests
+    std::thread::spawn(move || index_workspace(folders, can_report_progress));
 
     info!("STARTING EXAMPLE MAIN LOOP");
 
-    for msg in &connection.receiver {
-        error!("connection received message: {:?}", msg);
+    let mut queue = MessageQueue::default();
+
+    while let Some(msg) = queue.next(&connection.receiver) {
+        debug!("connection received message: {:?}", msg);
         let result = match msg {
-            Message::Notification(not) => handle_notification(not),
-            Message::Request(req) => handle_request(req),
-            _ => handle_other(msg),
-        };
+            Message::Request(req) if queue.is_cancelled(&connection.receiver, &req.id) => {
+                let response = Response::new_err(
+                    req.id,
+                    ErrorCode::RequestCanceled as i32,
+                    "request cancelled by the client".to_string(),
+                );
 
-        match match result {
-            Some(HtmxResult::AttributeCompletion(c)) => {
-                let str = match serde_json::to_value(&to_completion_list(c.items)) {
-                    Ok(s) => s,
-                    Err(_) => continue,
-                };
+                if let Err(e) = connection.sender.send(Message::Response(response)) {
+                    error!("failed to send response: {:?}", e);
+                }
+                continue;
+            }
+            Message::Request(req) if req.method == "shutdown" => {
+                // The exit may already sit in the queue, where the connection
+                // would never see it
+                let response = Response::new_ok(req.id, serde_json::Value::Null);
+                if let Err(e) = connection.sender.send(Message::Response(response)) {
+                    error!("failed to send response: {:?}", e);
+                }
 
-                // TODO: block requests that have been cancelled
-                connection.sender.send(Message::Response(Response {
-                    id: c.id,
-                    result: Some(str),
-                    error: None,
-                }))
+                let exited = queue.wait_for_exit(&connection.receiver, &connection.sender);
+                shutdown();
+                return match exited {
+                    true => Ok(()),
+                    false => Err(anyhow::anyhow!(
+                        "client hung up before the exit notification"
+                    )),
+                };
             }
+            Message::Notification(not) if not.method == "exit" => {
+                shutdown();
+                return Err(anyhow::anyhow!(
+                    "exit notification received before shutdown"
+                ));
+            }
+            Message::Notification(not) => handle_notification(not).and_then(to_response),
+            Message::Request(req) => {
+                // Every request needs an answer, even when there is nothing
+                // to offer at the position
+                let id = req.id.clone();
+                handle_request(req)
+                    .and_then(to_response)
+                    .or_else(|| response(id, serde_json::Value::Null))
+            }
+            msg => handle_other(msg).and_then(to_response),
+        };
+
+        let response = match result {
+            Some(response) => response,
             None => continue,
-        } {
-            Ok(_) => {}
-            Err(e) => error!("failed to send response: {:?}", e),
         };
+
+        if let Err(e) = connection.sender.send(Message::Response(response)) {
+            error!("failed to send response: {:?}", e);
+        }
     }
 
     return Ok(());
 }
 
+fn shutdown() {
+    info!("shutting down, releasing documents");
+
+    close_client();
+    if let Some(text_store) = TEXT_STORE.get() {
+        let mut text_store = text_store.lock().expect("text store mutex poisoned");
+        text_store.texts.clear();
+        text_store.language_ids.clear();
+        text_store.uris.clear();
+    }
+
+    log::logger().flush();
+}
+
 pub fn start_lsp() -> Result<()> {
     init_text_store();
+    init_index();
     init_hx_tags();
 
     // Note that  we must have our logging only write out to stderr.
@@ -99,29 +247,122 @@ pub fn start_lsp() -> Result<()> {
     // also be implemented to use sockets or HTTP.
     let (connection, io_threads) = Connection::stdio();
 
+    let (initialize_id, initialization_params) = connection.initialize_start()?;
+    // Clients able to pull diagnostics would get every report twice
+    let supports_pull_diagnostics = initialization_params
+        .pointer("/capabilities/textDocument/diagnostic")
+        .is_some();
+    init_diagnostic_refresh(
+        initialization_params
+            .pointer("/capabilities/workspace/diagnostics/refreshSupport")
+            .and_then(|supported| supported.as_bool())
+            .unwrap_or(false),
+    );
+    let params: InitializeParams = serde_json::from_value(initialization_params)?;
+    init_completion_support(&params.capabilities);
+
     // Run the server and wait for the two threads to end (typically by trigger LSP Exit event).
-    let server_capabilities = serde_json::to_value(&ServerCapabilities {
-        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+    let mut server_capabilities = serde_json::to_value(&ServerCapabilities {
+        position_encoding: Some(init_position_encoding(&params.capabilities)),
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::INCREMENTAL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(true),
+                })),
+                will_save_wait_until: Some(true),
+                ..Default::default()
+            },
+        )),
         completion_provider: Some(lsp_types::CompletionOptions {
-            resolve_provider: Some(false),
-            trigger_characters: Some(vec!["-".to_string(), "\"".to_string(), " ".to_string()]),
+            resolve_provider: Some(true),
+            trigger_characters: Some(TRIGGER_CHARACTERS.map(String::from).to_vec()),
             work_done_progress_options: WorkDoneProgressOptions {
                 work_done_progress: None,
             },
             all_commit_characters: None,
             completion_item: None,
         }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        document_highlight_provider: Some(OneOf::Left(true)),
+        document_range_formatting_provider: Some(OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+            first_trigger_character: "=".to_string(),
+            more_trigger_character: None,
+        }),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        workspace_symbol_provider: Some(OneOf::Left(true)),
+        inlay_hint_provider: Some(OneOf::Left(true)),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(false),
+        }),
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: command_names(),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                legend: semantic_tokens::legend(),
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+                range: None,
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+            },
+        )),
+        rename_provider: Some(OneOf::Right(RenameOptions {
+            prepare_provider: Some(true),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        })),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: WorkDoneProgressOptions {
+                work_done_progress: None,
+            },
+        }),
+        workspace: Some(WorkspaceServerCapabilities {
+            workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                supported: Some(true),
+                change_notifications: Some(OneOf::Left(true)),
+            }),
+            file_operations: None,
+        }),
         ..Default::default()
     })
     .unwrap();
 
-    let initialization_params = connection.initialize(server_capabilities)?;
-    main_loop(connection, initialization_params)?;
+    // Pull diagnostics are newer than our lsp-types
+    server_capabilities["diagnosticProvider"] = serde_json::json!({
+        "identifier": DIAGNOSTIC_SOURCE,
+        "interFileDependencies": false,
+        "workspaceDiagnostics": false,
+    });
+
+    connection.initialize_finish(
+        initialize_id,
+        serde_json::json!({ "capabilities": server_capabilities }),
+    )?;
+
+    if !supports_pull_diagnostics {
+        init_push_diagnostics();
+    }
+
+    let result = main_loop(connection, params);
     io_threads.join()?;
 
     // Shut down gracefully.
     warn!("shutting down server");
-    Ok(())
+    result
 }
 
 #[cfg(test)]
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use lsp_types::{DocumentLink, Range, Url};
+
+use crate::{
+    config::get_config, encoding::byte_offset_to_position, htmx::canonical_name,
+    index::INDEXED_EXTENSIONS, text_store::get_text_document, tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes, workspace::WORKSPACE,
+};
+
+pub const REQUEST_ATTRIBUTES: [&str; 5] = ["hx-get", "hx-post", "hx-put", "hx-delete", "hx-patch"];
+
+/// Where the request paths of a document resolve to.
+#[derive(Debug, Default)]
+pub struct LinkBase {
+    pub base_url: Option<Url>,
+    pub routes_root: Option<PathBuf>,
+}
+
+/// Finds the template serving `path` below `root`, trying the path as is,
+/// with a template extension and as a directory index.
+fn route_file(root: &Path, path: &str) -> Option<PathBuf> {
+    let path = path.split(['?', '#']).next().unwrap_or("");
+    let file = root.join(path.trim_start_matches('/'));
+
+    if file.is_file() {
+        return Some(file);
+    }
+
+    return INDEXED_EXTENSIONS.iter().find_map(|ext| {
+        return [
+            file.with_extension(ext),
+            file.join("index").with_extension(ext),
+        ]
+        .into_iter()
+        .find(|candidate| candidate.is_file());
+    });
+}
+
+fn link_target(value: &str, base: &LinkBase) -> Option<Url> {
+    if let Ok(url) = Url::parse(value) {
+        return Some(url);
+    }
+
+    if let Some(base_url) = &base.base_url {
+        return base_url.join(value).ok();
+    }
+
+    let file = route_file(base.routes_root.as_ref()?, value)?;
+    return Url::from_file_path(file).ok();
+}
+
+pub fn document_links(source: &str, base: &LinkBase) -> Vec<DocumentLink> {
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+
+    return query_attributes(tree.root_node(), source)
+        .into_iter()
+        .filter(|attribute| REQUEST_ATTRIBUTES.contains(&canonical_name(&attribute.name)))
+        .filter_map(|attribute| {
+            let value = attribute.value.as_deref()?.trim();
+            let range = attribute.value_range?;
+            if value.is_empty() {
+                return None;
+            }
+
+            return Some(DocumentLink {
+                range: Range {
+                    start: byte_offset_to_position(source, range.start_byte),
+                    end: byte_offset_to_position(source, range.end_byte),
+                },
+                target: Some(link_target(value, base)?),
+                tooltip: None,
+                data: None,
+            });
+        })
+        .collect();
+}
+
+/// Request paths resolve against the configured base url, otherwise against
+/// the routes directory of the workspace folder holding the document.
+fn link_base(uri: &Url) -> LinkBase {
+    let config = get_config();
+    let base_url = config
+        .base_url
+        .as_deref()
+        .and_then(|url| Url::parse(url).ok());
+
+    let routes_root = config.routes_directory.and_then(|directory| {
+        let workspace = WORKSPACE.get()?.lock().ok()?;
+        let folder = workspace.folder_for(uri)?.uri.to_file_path().ok()?;
+        return Some(folder.join(directory));
+    });
+
+    return LinkBase {
+        base_url,
+        routes_root,
+    };
+}
+
+/// The template answering `path` in the routes directory of the workspace
+/// folder holding `uri`.
+pub fn route_template(uri: &Url, path: &str) -> Option<Url> {
+    let routes_root = link_base(uri).routes_root?;
+    return Url::from_file_path(route_file(&routes_root, path)?).ok();
+}
+
+pub fn hx_document_links(uri: Url) -> Vec<DocumentLink> {
+    let base = link_base(&uri);
+    let Some(text) = get_text_document(uri) else {
+        return vec![];
+    };
+
+    return document_links(&text, &base);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{document_links, LinkBase};
+    use lsp_types::{Position, Range, Url};
+
+    fn targets(source: &str, base: &LinkBase) -> Vec<String> {
+        return document_links(source, base)
+            .into_iter()
+            .filter_map(|link| link.target.map(|t| t.to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn test_links_request_paths_against_base_url() {
+        let text = r##"<button hx-get="/items?page=2" hx-target="#list">
+<form hx-post="https://example.com/submit" hx-put="">"##;
+        let base = LinkBase {
+            base_url: Url::parse("http://localhost:8000/app/").ok(),
+            routes_root: None,
+        };
+
+        let links = document_links(text, &base);
+
+        assert_eq!(
+            targets(text, &base),
+            vec![
+                "http://localhost:8000/items?page=2",
+                "https://example.com/submit",
+            ]
+        );
+        assert_eq!(
+            links[0].range,
+            Range::new(Position::new(0, 16), Position::new(0, 29))
+        );
+    }
+
+    #[test]
+    fn test_links_request_paths_to_route_files() {
+        let root = std::env::temp_dir().join(format!("htmx-lsp-links-{}", std::process::id()));
+        fs::create_dir_all(root.join("users")).expect("creates routes");
+        fs::write(root.join("items.html"), "").expect("writes route");
+        fs::write(root.join("users/index.html"), "").expect("writes route");
+
+        let text = r##"<a hx-get="/items"></a><a hx-get="/users"></a><a hx-get="/missing"></a>"##;
+        let base = LinkBase {
+            base_url: None,
+            routes_root: Some(root.clone()),
+        };
+
+        let targets = targets(text, &base);
+        fs::remove_dir_all(&root).expect("cleanup");
+
+        assert_eq!(
+            targets,
+            vec![
+                Url::from_file_path(root.join("items.html"))
+                    .unwrap()
+                    .to_string(),
+                Url::from_file_path(root.join("users/index.html"))
+                    .unwrap()
+                    .to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,318 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    time::{Duration, Instant},
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use log::debug;
+use lsp_server::{ErrorCode, Message, Notification, RequestId, Response};
+use lsp_types::{CancelParams, DidChangeTextDocumentParams, NumberOrString};
+
+const CANCEL_REQUEST: &str = "$/cancelRequest";
+const DID_CHANGE: &str = "textDocument/didChange";
+const EXIT: &str = "exit";
+
+// A lone change waits this long for the next keystroke, for at most
+// MAX_CHANGE_DELAY while the user keeps typing
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(15);
+const MAX_CHANGE_DELAY: Duration = Duration::from_millis(100);
+
+fn is_did_change(msg: &Message) -> bool {
+    return matches!(msg, Message::Notification(not) if not.method == DID_CHANGE);
+}
+
+/// Folds `next` into `previous` when both change the same document, giving
+/// `next` back when they can't be merged.
+fn coalesce(previous: &mut Notification, next: Notification) -> Option<Notification> {
+    if previous.method != DID_CHANGE || next.method != DID_CHANGE {
+        return Some(next);
+    }
+
+    let merged = serde_json::from_value::<DidChangeTextDocumentParams>(previous.params.clone());
+    let changes = serde_json::from_value::<DidChangeTextDocumentParams>(next.params.clone());
+    let (Ok(mut merged), Ok(changes)) = (merged, changes) else {
+        return Some(next);
+    };
+    if merged.text_document.uri != changes.text_document.uri {
+        return Some(next);
+    }
+
+    merged.text_document.version = changes.text_document.version;
+    // A change without a range replaces the whole text, whatever came
+    // before it is gone anyway
+    match changes
+        .content_changes
+        .iter()
+        .rposition(|c| c.range.is_none())
+    {
+        Some(idx) => merged.content_changes = changes.content_changes[idx..].to_vec(),
+        None => merged.content_changes.extend(changes.content_changes),
+    }
+
+    let Ok(params) = serde_json::to_value(merged) else {
+        return Some(next);
+    };
+    previous.params = params;
+
+    return None;
+}
+
+/// Buffers the messages already sent by the client so a `$/cancelRequest`
+/// queued behind a request is known before we start working on it.
+#[derive(Default)]
+pub struct MessageQueue {
+    pending: VecDeque<Message>,
+    cancelled: HashSet<RequestId>,
+}
+
+impl MessageQueue {
+    fn push(&mut self, msg: Message) {
+        match msg {
+            Message::Notification(not) if not.method == CANCEL_REQUEST => {
+                let Ok(params) = serde_json::from_value::<CancelParams>(not.params) else {
+                    return;
+                };
+
+                let id = match params.id {
+                    NumberOrString::Number(id) => RequestId::from(id),
+                    NumberOrString::String(id) => RequestId::from(id),
+                };
+
+                debug!("request cancelled: {:?}", id);
+                self.cancelled.insert(id);
+            }
+            Message::Notification(not) => {
+                let not = match self.pending.back_mut() {
+                    Some(Message::Notification(previous)) => match coalesce(previous, not) {
+                        Some(not) => not,
+                        None => {
+                            debug!("coalesced didChange");
+                            return;
+                        }
+                    },
+                    _ => not,
+                };
+                self.pending.push_back(Message::Notification(not));
+            }
+            msg => self.pending.push_back(msg),
+        }
+    }
+
+    /// Gives a change nothing else is queued behind a moment to be merged
+    /// with the following ones, anything else the client sends ends the wait.
+    fn settle(&mut self, receiver: &Receiver<Message>) {
+        let started = Instant::now();
+
+        while self.pending.len() == 1 && started.elapsed() < MAX_CHANGE_DELAY {
+            match receiver.recv_timeout(CHANGE_DEBOUNCE) {
+                Ok(msg) => self.push(msg),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn fill(&mut self, receiver: &Receiver<Message>) {
+        while let Ok(msg) = receiver.try_recv() {
+            self.push(msg);
+        }
+    }
+
+    /// Blocks until the next message that isn't a cancellation, `None` once
+    /// the client hung up.
+    pub fn next(&mut self, receiver: &Receiver<Message>) -> Option<Message> {
+        loop {
+            self.fill(receiver);
+
+            if self.pending.len() == 1 && self.pending.front().is_some_and(is_did_change) {
+                self.settle(receiver);
+            }
+
+            if let Some(msg) = self.pending.pop_front() {
+                return Some(msg);
+            }
+
+            // Nothing is queued, so whatever was cancelled has been answered
+            // already and the ids will never show up again
+            self.cancelled.clear();
+
+            let msg = receiver.recv().ok()?;
+            self.push(msg);
+        }
+    }
+
+    /// Reads up to the `exit` that follows an answered `shutdown`, which may
+    /// be buffered already. Requests sent in between are refused, `false`
+    /// when the client hung up without exiting.
+    pub fn wait_for_exit(
+        &mut self,
+        receiver: &Receiver<Message>,
+        sender: &Sender<Message>,
+    ) -> bool {
+        while let Some(msg) = self.next(receiver) {
+            match msg {
+                Message::Notification(not) if not.method == EXIT => return true,
+                Message::Request(req) => {
+                    let response = Response::new_err(
+                        req.id,
+                        ErrorCode::InvalidRequest as i32,
+                        "the server is shutting down".to_string(),
+                    );
+                    _ = sender.send(Message::Response(response));
+                }
+                _ => {}
+            }
+        }
+
+        return false;
+    }
+
+    pub fn is_cancelled(&mut self, receiver: &Receiver<Message>, id: &RequestId) -> bool {
+        self.fill(receiver);
+        return self.cancelled.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MessageQueue;
+    use lsp_server::{Message, Notification, Request, RequestId};
+
+    fn completion(id: i32) -> Message {
+        return Message::Request(Request::new(
+            RequestId::from(id),
+            "textDocument/completion".to_string(),
+            serde_json::Value::Null,
+        ));
+    }
+
+    fn cancel(id: i32) -> Message {
+        return Message::Notification(Notification::new(
+            "$/cancelRequest".to_string(),
+            serde_json::json!({ "id": id }),
+        ));
+    }
+
+    fn change(uri: &str, text: &str, ranged: bool) -> Message {
+        let range = ranged.then(|| {
+            serde_json::json!({
+                "start": { "line": 0, "character": 0 },
+                "end": { "line": 0, "character": 0 },
+            })
+        });
+
+        return Message::Notification(Notification::new(
+            "textDocument/didChange".to_string(),
+            serde_json::json!({
+                "textDocument": { "uri": uri, "version": 1 },
+                "contentChanges": [{ "range": range, "text": text }],
+            }),
+        ));
+    }
+
+    fn changed_texts(msg: Option<Message>) -> Vec<String> {
+        let Some(Message::Notification(not)) = msg else {
+            return vec![];
+        };
+
+        return not.params["contentChanges"]
+            .as_array()
+            .expect("has changes")
+            .iter()
+            .map(|change| change["text"].as_str().unwrap_or_default().to_string())
+            .collect();
+    }
+
+    fn request_id(msg: Option<Message>) -> Option<RequestId> {
+        return match msg {
+            Some(Message::Request(req)) => Some(req.id),
+            _ => None,
+        };
+    }
+
+    #[test]
+    fn test_sees_cancellation_queued_behind_request() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let mut queue = MessageQueue::default();
+
+        sender.send(completion(1)).expect("send");
+        sender.send(cancel(1)).expect("send");
+        sender.send(completion(2)).expect("send");
+
+        assert_eq!(request_id(queue.next(&receiver)), Some(RequestId::from(1)));
+        assert!(queue.is_cancelled(&receiver, &RequestId::from(1)));
+
+        assert_eq!(request_id(queue.next(&receiver)), Some(RequestId::from(2)));
+        assert!(!queue.is_cancelled(&receiver, &RequestId::from(2)));
+    }
+
+    #[test]
+    fn test_stops_when_client_hangs_up() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let mut queue = MessageQueue::default();
+
+        sender.send(cancel(3)).expect("send");
+        drop(sender);
+
+        assert!(queue.next(&receiver).is_none());
+    }
+
+    #[test]
+    fn test_takes_the_buffered_exit_after_shutdown() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let (responses, answered) = crossbeam_channel::unbounded();
+        let mut queue = MessageQueue::default();
+
+        sender.send(completion(1)).expect("send");
+        sender.send(completion(2)).expect("send");
+        sender
+            .send(Message::Notification(Notification::new(
+                "exit".to_string(),
+                serde_json::Value::Null,
+            )))
+            .expect("send");
+
+        // Reading the first request buffers the rest, the exit included
+        assert_eq!(request_id(queue.next(&receiver)), Some(RequestId::from(1)));
+        assert!(!queue.is_cancelled(&receiver, &RequestId::from(1)));
+        assert!(receiver.is_empty());
+
+        assert!(queue.wait_for_exit(&receiver, &responses));
+        let Ok(Message::Response(refused)) = answered.try_recv() else {
+            panic!("the request after shutdown is answered");
+        };
+        assert_eq!(refused.id, RequestId::from(2));
+        assert!(refused.error.is_some());
+
+        drop(sender);
+        assert!(!queue.wait_for_exit(&receiver, &responses));
+    }
+
+    #[test]
+    fn test_coalesces_changes_to_the_same_document() {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let mut queue = MessageQueue::default();
+
+        sender
+            .send(change("file:///a.html", "a", true))
+            .expect("send");
+        sender
+            .send(change("file:///a.html", "b", true))
+            .expect("send");
+        sender
+            .send(change("file:///b.html", "c", true))
+            .expect("send");
+        sender
+            .send(change("file:///b.html", "d", false))
+            .expect("send");
+        sender
+            .send(change("file:///b.html", "e", true))
+            .expect("send");
+        sender.send(completion(1)).expect("send");
+
+        assert_eq!(changed_texts(queue.next(&receiver)), vec!["a", "b"]);
+        // The full text replacement makes the change before it moot
+        assert_eq!(changed_texts(queue.next(&receiver)), vec!["d", "e"]);
+        // Requests come after the changes sent before them
+        assert_eq!(request_id(queue.next(&receiver)), Some(RequestId::from(1)));
+    }
+}
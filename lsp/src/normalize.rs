@@ -0,0 +1,141 @@
+use lsp_types::{Range, TextEdit};
+
+use crate::{
+    config::{DataPrefix, NormalizeConfig},
+    dom::Dom,
+    encoding::byte_offset_to_position,
+    htmx::htmx_name,
+    tree_sitter_querier::AttributeNode,
+};
+
+/// The order htmx attributes are sorted in, the request first and then what
+/// shapes it, attributes not listed here follow alphabetically.
+const CANONICAL_ORDER: [&str; 12] = [
+    "hx-get",
+    "hx-post",
+    "hx-put",
+    "hx-patch",
+    "hx-delete",
+    "hx-trigger",
+    "hx-target",
+    "hx-swap",
+    "hx-select",
+    "hx-include",
+    "hx-vals",
+    "hx-indicator",
+];
+
+fn sort_key(name: &str) -> (usize, String) {
+    let Some(name) = htmx_name(name) else {
+        // Plain attributes keep their place ahead of the htmx ones
+        return (0, String::new());
+    };
+
+    return match CANONICAL_ORDER.iter().position(|n| *n == name) {
+        Some(idx) => (idx + 1, String::new()),
+        None => (CANONICAL_ORDER.len() + 1, name.to_string()),
+    };
+}
+
+fn normalize_attribute(
+    source: &str,
+    attribute: &AttributeNode,
+    config: &NormalizeConfig,
+) -> String {
+    let text = &source[attribute.range.start_byte..attribute.range.end_byte];
+
+    let name = match (htmx_name(&attribute.name), config.data_prefix) {
+        (Some(name), Some(DataPrefix::Always)) => format!("data-{}", name),
+        (Some(name), Some(DataPrefix::Never)) => name.to_string(),
+        _ => attribute.name.clone(),
+    };
+
+    let Some(value) = &attribute.value else {
+        return format!("{}{}", name, &text[attribute.name.len()..]);
+    };
+
+    if config.quotes && !value.contains('"') {
+        return format!("{}=\"{}\"", name, value);
+    }
+
+    return format!("{}{}", name, &text[attribute.name.len()..]);
+}
+
+/// Edits rewriting the attributes of every element that isn't normalized,
+/// the whitespace between attributes is kept so the layout doesn't change.
+pub fn normalize_edits(source: &str, config: &NormalizeConfig) -> Vec<TextEdit> {
+    let mut edits = vec![];
+
+    for element in Dom::parse(source).elements {
+        let mut ordered: Vec<&AttributeNode> = element.attributes.iter().collect();
+        if config.order_attributes {
+            ordered.sort_by_key(|attribute| sort_key(&attribute.name));
+        }
+
+        for (slot, attribute) in element.attributes.iter().zip(ordered) {
+            let current = &source[slot.range.start_byte..slot.range.end_byte];
+            let normalized = normalize_attribute(source, attribute, config);
+            if current == normalized {
+                continue;
+            }
+
+            edits.push(TextEdit {
+                range: Range {
+                    start: byte_offset_to_position(source, slot.range.start_byte),
+                    end: byte_offset_to_position(source, slot.range.end_byte),
+                },
+                new_text: normalized,
+            });
+        }
+    }
+
+    return edits;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_edits;
+    use crate::config::{DataPrefix, NormalizeConfig};
+
+    fn normalize(source: &str, config: &NormalizeConfig) -> String {
+        let mut lines: Vec<String> = source.lines().map(str::to_string).collect();
+
+        // Edits don't overlap, applying them backwards keeps offsets valid
+        for edit in normalize_edits(source, config).into_iter().rev() {
+            let line = &mut lines[edit.range.start.line as usize];
+            let (start, end) = (edit.range.start.character, edit.range.end.character);
+            line.replace_range(start as usize..end as usize, &edit.new_text);
+        }
+
+        return lines.join("\n");
+    }
+
+    #[test]
+    fn test_normalizes_quotes_order_and_prefix() {
+        let text = r##"<form class=box hx-swap='outerHTML' data-hx-target="#r"
+      hx-post=/search id="f">"##;
+        let config = NormalizeConfig {
+            quotes: true,
+            order_attributes: true,
+            data_prefix: Some(DataPrefix::Never),
+        };
+
+        assert_eq!(
+            normalize(text, &config),
+            r##"<form class="box" id="f" hx-post="/search"
+      hx-target="#r" hx-swap="outerHTML">"##
+        );
+    }
+
+    #[test]
+    fn test_keeps_normalized_documents_untouched() {
+        let text = r##"<div id="a" hx-get="/a" hx-target="this"></div>"##;
+        let config = NormalizeConfig {
+            quotes: true,
+            order_attributes: true,
+            data_prefix: None,
+        };
+
+        assert!(normalize_edits(text, &config).is_empty());
+    }
+}
@@ -0,0 +1,53 @@
+use lsp_types::{Position, Range, TextEdit};
+
+use crate::encoding::position_to_byte_offset;
+
+fn is_attribute_name_char(c: char) -> bool {
+    return c.is_alphanumeric() || matches!(c, '-' | '_' | ':' | '.');
+}
+
+/// Typing `=` right after an hx-* attribute name adds the quotes of its
+/// value, for clients completing attribute names without snippets.
+pub fn on_type_edits(source: &str, position: Position, ch: &str) -> Vec<TextEdit> {
+    if ch != "=" {
+        return vec![];
+    }
+    let Some(byte) = position_to_byte_offset(source, position) else {
+        return vec![];
+    };
+
+    let Some(before) = source[..byte].strip_suffix('=') else {
+        return vec![];
+    };
+    let name_start = before
+        .rfind(|c: char| !is_attribute_name_char(c))
+        .map_or(0, |idx| idx + 1);
+    let name = &before[name_start..];
+
+    let is_htmx = name.starts_with("hx-") || name.starts_with("data-hx-");
+    let has_value = source[byte..].starts_with(['"', '\'']);
+    if !is_htmx || has_value {
+        return vec![];
+    }
+
+    return vec![TextEdit {
+        range: Range::new(position, position),
+        new_text: "\"\"".to_string(),
+    }];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::on_type_edits;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_quotes_values_of_htmx_attributes() {
+        let edits = on_type_edits("<div hx-get=>", Position::new(0, 12), "=");
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, "\"\"");
+
+        assert!(on_type_edits("<div class=>", Position::new(0, 11), "=").is_empty());
+        assert!(on_type_edits("<div hx-get=\"\">", Position::new(0, 12), "=").is_empty());
+    }
+}
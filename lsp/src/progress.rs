@@ -0,0 +1,69 @@
+use lsp_types::{
+    notification::Progress as ProgressNotification, request::WorkDoneProgressCreate,
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+
+use crate::client::{send_notification, send_request};
+
+/// Server initiated work done progress, shows up in the editor status line
+/// as "htmx-lsp: <title> <done>/<total>".
+pub struct Progress {
+    token: NumberOrString,
+    total: usize,
+    last_percentage: u32,
+}
+
+impl Progress {
+    pub fn begin(token: &str, title: &str, total: usize) -> Self {
+        let token = NumberOrString::String(format!("htmx-lsp/{}", token));
+
+        send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+            token: token.clone(),
+        });
+
+        let progress = Self {
+            token,
+            total,
+            last_percentage: 0,
+        };
+
+        progress.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: format!("htmx-lsp: {}", title),
+            cancellable: Some(false),
+            message: Some(format!("0/{}", total)),
+            percentage: Some(0),
+        }));
+
+        return progress;
+    }
+
+    fn send(&self, value: WorkDoneProgress) {
+        send_notification::<ProgressNotification>(ProgressParams {
+            token: self.token.clone(),
+            value: ProgressParamsValue::WorkDone(value),
+        });
+    }
+
+    /// Only reports when the percentage moved, large workspaces would
+    /// otherwise flood the client with a notification per file.
+    pub fn report(&mut self, done: usize) {
+        let percentage = (done * 100 / self.total.max(1)) as u32;
+        if percentage == self.last_percentage {
+            return;
+        }
+        self.last_percentage = percentage;
+
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: Some(false),
+            message: Some(format!("{}/{}", done, self.total)),
+            percentage: Some(percentage),
+        }));
+    }
+
+    pub fn end(self, message: String) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd {
+            message: Some(message),
+        }));
+    }
+}
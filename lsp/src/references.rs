@@ -0,0 +1,108 @@
+use lsp_types::{
+    DocumentHighlight, DocumentHighlightKind, LinkedEditingRanges, Location, ReferenceParams,
+    TextDocumentPositionParams, Url,
+};
+
+use crate::{
+    encoding::position_to_byte_offset, ids::document_ids, index::workspace_documents,
+    text_store::get_text_document,
+};
+
+/// Every selector referencing `id` across the open documents and the
+/// workspace index of the folder of `uri`, optionally with the elements
+/// declaring it.
+pub fn id_references(uri: &Url, id: &str, include_declaration: bool) -> Vec<Location> {
+    return workspace_documents(uri)
+        .into_iter()
+        .flat_map(|(uri, text)| {
+            let ids = document_ids(&text);
+            let declarations = include_declaration.then_some(ids.definitions);
+
+            return ids
+                .references
+                .into_iter()
+                .chain(declarations.into_iter().flatten())
+                .filter(|occurrence| occurrence.id == id)
+                .map(|occurrence| occurrence.location(uri.clone(), &text))
+                .collect::<Vec<_>>();
+        })
+        .collect();
+}
+
+pub fn hx_references(params: ReferenceParams) -> Option<Vec<Location>> {
+    let text_params = params.text_document_position;
+    let uri = text_params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    let ids = document_ids(&text);
+    let id = &ids.id_at(byte)?.id;
+
+    return Some(id_references(&uri, id, params.context.include_declaration));
+}
+
+/// Highlights the element declaring the id under the cursor together with
+/// the selectors of the same document pointing at it.
+pub fn hx_document_highlight(
+    text_params: TextDocumentPositionParams,
+) -> Option<Vec<DocumentHighlight>> {
+    let uri = text_params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    let ids = document_ids(&text);
+    let id = &ids.id_at(byte)?.id;
+
+    let declarations = ids
+        .definitions
+        .iter()
+        .map(|occurrence| (occurrence, DocumentHighlightKind::WRITE));
+    let references = ids
+        .references
+        .iter()
+        .map(|occurrence| (occurrence, DocumentHighlightKind::READ));
+
+    return Some(
+        declarations
+            .chain(references)
+            .filter(|(occurrence, _)| &occurrence.id == id)
+            .map(|(occurrence, kind)| DocumentHighlight {
+                range: occurrence.location(uri.clone(), &text).range,
+                kind: Some(kind),
+            })
+            .collect(),
+    );
+}
+
+/// Selectors edited along with the id they point at.
+const LINKED_ATTRIBUTES: [&str; 3] = ["id", "hx-target", "hx-include"];
+
+/// Ties an element id to the `#id` selectors of the same document so
+/// editing one edits all of them.
+pub fn hx_linked_editing_range(
+    text_params: TextDocumentPositionParams,
+) -> Option<LinkedEditingRanges> {
+    let uri = text_params.text_document.uri;
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    let ids = document_ids(&text);
+    let current = ids.id_at(byte)?;
+    if !LINKED_ATTRIBUTES.contains(&current.attribute.as_str()) {
+        return None;
+    }
+
+    let ranges: Vec<_> = ids
+        .definitions
+        .iter()
+        .chain(ids.references.iter())
+        .filter(|occurrence| occurrence.id == current.id)
+        .filter(|occurrence| LINKED_ATTRIBUTES.contains(&occurrence.attribute.as_str()))
+        .map(|occurrence| occurrence.location(uri.clone(), &text).range)
+        .collect();
+
+    return Some(LinkedEditingRanges {
+        ranges,
+        word_pattern: Some("[A-Za-z0-9_-]+".to_string()),
+    });
+}
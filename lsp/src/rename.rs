@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use log::warn;
+use lsp_types::{
+    PrepareRenameResponse, RenameParams, TextDocumentPositionParams, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{
+    encoding::position_to_byte_offset,
+    ids::{document_ids, is_id_char},
+    references::id_references,
+    text_store::{get_text_document, is_script_document},
+};
+
+fn is_valid_id(id: &str) -> bool {
+    return !id.is_empty() && id.chars().all(is_id_char);
+}
+
+pub fn hx_prepare_rename(text_params: TextDocumentPositionParams) -> Option<PrepareRenameResponse> {
+    let uri = text_params.text_document.uri;
+    if is_script_document(&uri) {
+        return None;
+    }
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    let ids = document_ids(&text);
+    let occurrence = ids.id_at(byte)?;
+
+    return Some(PrepareRenameResponse::RangeWithPlaceholder {
+        range: occurrence.location(uri, &text).range,
+        placeholder: occurrence.id.clone(),
+    });
+}
+
+/// Renames an element id together with every selector referencing it, so
+/// swaps keep pointing at the same element.
+pub fn hx_rename(params: RenameParams) -> Option<WorkspaceEdit> {
+    let text_params = params.text_document_position;
+    let uri = text_params.text_document.uri;
+    if is_script_document(&uri) {
+        return None;
+    }
+    let text = get_text_document(uri.clone())?;
+    let byte = position_to_byte_offset(&text, text_params.position)?;
+
+    let new_name = params.new_name.trim_start_matches('#');
+    if !is_valid_id(new_name) {
+        warn!("refusing to rename to invalid id {:?}", params.new_name);
+        return None;
+    }
+
+    let ids = document_ids(&text);
+    let id = &ids.id_at(byte)?.id;
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    // The markup of scripts is read through their strings, an edit there
+    // could break the script around it
+    let locations = id_references(&uri, id, true)
+        .into_iter()
+        .filter(|location| !is_script_document(&location.uri));
+    for location in locations {
+        changes.entry(location.uri).or_default().push(TextEdit {
+            range: location.range,
+            new_text: new_name.to_string(),
+        });
+    }
+
+    return Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        Position, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+    };
+
+    use super::{hx_rename, is_valid_id};
+    use crate::{
+        index::init_index,
+        text_store::{init_text_store, TEXT_STORE},
+        uri::uri_key,
+    };
+
+    #[test]
+    fn test_valid_ids() {
+        assert!(is_valid_id("search-results_2"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("two words"));
+        assert!(!is_valid_id("a.b"));
+    }
+
+    #[test]
+    fn test_renames_leave_scripts_alone() {
+        init_text_store();
+        init_index();
+        let template = Url::parse("file:///rename/index.html").expect("valid uri");
+        let script = Url::parse("file:///rename/rows.js").expect("valid uri");
+        let mut text_store = TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex");
+        text_store.texts.insert(
+            uri_key(&template),
+            r##"<div id="rows"></div><a hx-target="#rows"></a>"##.to_string(),
+        );
+        text_store.texts.insert(
+            uri_key(&script),
+            r##"el.innerHTML = '<a hx-target="#rows"></a>';"##.to_string(),
+        );
+        drop(text_store);
+
+        let rename = |uri: &Url, character: u32| {
+            return hx_rename(RenameParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(0, character),
+                },
+                new_name: "items".to_string(),
+                work_done_progress_params: Default::default(),
+            });
+        };
+
+        let changes = rename(&template, 10)
+            .and_then(|edit| edit.changes)
+            .expect("renamed");
+        assert_eq!(changes.get(&template).map(Vec::len), Some(2));
+        assert!(!changes.contains_key(&script));
+
+        assert!(rename(&script, 32).is_none());
+    }
+}
@@ -0,0 +1,269 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::{config::get_config, index::INDEX, symbols::workspace_endpoints, workspace::WORKSPACE};
+
+/// An url the backend answers, `method` is unset for routes answering any
+/// of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    pub method: Option<String>,
+    pub path: String,
+    /// Where the route comes from, eg. "declared in `app.py`"
+    pub origin: String,
+}
+
+impl Route {
+    pub fn answers(&self, method: &str) -> bool {
+        return self
+            .method
+            .as_deref()
+            .is_none_or(|own| own.eq_ignore_ascii_case(method));
+    }
+}
+
+/// The method, if any, and the path of a route found in a source file.
+pub type ScannedRoute = (Option<String>, String);
+
+/// Backend source files scanned for the routes they declare.
+pub const ROUTE_SOURCE_EXTENSIONS: [&str; 10] = [
+    "py", "js", "ts", "go", "rb", "php", "java", "kt", "rs", "ex",
+];
+
+pub fn is_route_source(path: &Path) -> bool {
+    return path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ROUTE_SOURCE_EXTENSIONS.contains(&ext))
+        .unwrap_or(false);
+}
+
+/// The method a route declaring call answers, `Some(None)` for the calls
+/// answering any method, eg. `Some(Some("GET"))` for `app.get` and
+/// `@GetMapping`.
+fn route_method(call: &str) -> Option<Option<&'static str>> {
+    let call = call.to_lowercase();
+    let call = call.strip_suffix("mapping").unwrap_or(&call);
+
+    return match call {
+        "get" => Some(Some("GET")),
+        "post" => Some(Some("POST")),
+        "put" => Some(Some("PUT")),
+        "delete" => Some(Some("DELETE")),
+        "patch" => Some(Some("PATCH")),
+        "route" | "handlefunc" | "handle" | "path" | "request" | "all" | "any" | "match" => {
+            Some(None)
+        }
+        _ => None,
+    };
+}
+
+/// Routes declared in backend code, like `@app.get("/users")`,
+/// `router.post('/users', ...)`, `r.HandleFunc("/users", ...)` or Django's
+/// `path("users/", ...)`, the first argument has to be a string literal.
+pub fn scan_routes(text: &str) -> Vec<ScannedRoute> {
+    let mut routes = vec![];
+
+    for (idx, _) in text.match_indices('(') {
+        let before = &text[..idx];
+        let call_start = before
+            .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+            .map_or(0, |start| start + 1);
+        let Some(method) = route_method(&before[call_start..]) else {
+            continue;
+        };
+
+        let argument = text[idx + 1..].trim_start();
+        let Some(quote) = argument
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '"' | '\'' | '`'))
+        else {
+            continue;
+        };
+        let Some(end) = argument[1..].find(quote) else {
+            continue;
+        };
+
+        let path = &argument[1..end + 1];
+        let path = match path.starts_with('/') {
+            true => path.to_string(),
+            // Django paths are written without their leading slash
+            false if &before[call_start..] == "path" && !path.contains(' ') => {
+                format!("/{}", path)
+            }
+            false => continue,
+        };
+        routes.push((method.map(str::to_string), path));
+    }
+
+    return routes;
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ManifestRoute {
+    /// `"/users"` or `"GET /users"`
+    Line(String),
+    Entry {
+        method: Option<String>,
+        path: String,
+    },
+}
+
+/// The routes of a manifest file, a JSON array of `"/users"`,
+/// `"GET /users"` or `{ "method": "GET", "path": "/users" }` entries.
+pub fn parse_manifest(text: &str) -> Vec<ScannedRoute> {
+    let Ok(entries) = serde_json::from_str::<Vec<ManifestRoute>>(text) else {
+        return vec![];
+    };
+
+    return entries
+        .into_iter()
+        .map(|entry| {
+            return match entry {
+                ManifestRoute::Line(line) => match line.trim().split_once(char::is_whitespace) {
+                    Some((method, path)) => (Some(method.to_uppercase()), path.trim().to_string()),
+                    None => (None, line.trim().to_string()),
+                },
+                ManifestRoute::Entry { method, path } => {
+                    (method.map(|method| method.to_uppercase()), path)
+                }
+            };
+        })
+        .filter(|(_, path)| !path.is_empty())
+        .collect();
+}
+
+/// The urls requested across the workspace, the most requested first.
+/// Urls made by template tags are left out, they only mean something once
+/// rendered.
+pub fn requested_urls() -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, endpoints) in workspace_endpoints() {
+        for endpoint in endpoints {
+            let templated = endpoint.url.contains(['{', '}', '<', '>', ' ']);
+            if !endpoint.url.is_empty() && !templated {
+                *counts.entry(endpoint.url).or_default() += 1;
+            }
+        }
+    }
+
+    let mut urls: Vec<(String, usize)> = counts.into_iter().collect();
+    urls.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    return urls;
+}
+
+fn file_name(uri: &str) -> String {
+    return uri.rsplit('/').next().unwrap_or(uri).to_string();
+}
+
+/// Every known route: the ones of the `routesManifest` of each workspace
+/// folder, then the ones declared in backend code and last the urls the
+/// workspace already requests.
+pub fn workspace_routes() -> Vec<Route> {
+    let mut routes: Vec<Route> = vec![];
+    let mut add = |method: Option<String>, path: String, origin: String| {
+        let known = routes
+            .iter()
+            .any(|route| route.path == path && (route.method.is_none() || route.method == method));
+        if !known {
+            routes.push(Route {
+                method,
+                path,
+                origin,
+            });
+        }
+    };
+
+    if let Some(manifest) = get_config().routes_manifest {
+        let folders = WORKSPACE
+            .get()
+            .and_then(|workspace| Some(workspace.lock().ok()?.folders.clone()))
+            .unwrap_or_default();
+        for folder in folders {
+            let Ok(root) = folder.uri.to_file_path() else {
+                continue;
+            };
+            let Ok(text) = fs::read_to_string(root.join(&manifest)) else {
+                continue;
+            };
+            for (method, path) in parse_manifest(&text) {
+                add(method, path, format!("listed in `{}`", manifest));
+            }
+        }
+    }
+
+    let index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+    let scanned: Vec<(String, Vec<ScannedRoute>)> = index
+        .routes
+        .iter()
+        .map(|(key, routes)| {
+            let uri = index.uri(key).map_or(key.clone(), |uri| uri.to_string());
+            return (uri, routes.clone());
+        })
+        .collect();
+    drop(index);
+    for (uri, routes) in scanned {
+        for (method, path) in routes {
+            add(method, path, format!("declared in `{}`", file_name(&uri)));
+        }
+    }
+
+    for (url, count) in requested_urls() {
+        let elements = if count == 1 { "element" } else { "elements" };
+        let origin = format!("requested by {} {} of the workspace", count, elements);
+        add(None, url, origin);
+    }
+
+    return routes;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_manifest, scan_routes};
+
+    #[test]
+    fn test_scans_routes_of_backend_code() {
+        let text = r#"
+@app.get("/users")
+def users(): ...
+
+router.post('/users/:id', handler)
+r.HandleFunc("/health", health)
+urlpatterns = [path("contacts/", views.contacts)]
+print("/not-a-route")
+app.get(url)
+"#;
+
+        assert_eq!(
+            scan_routes(text),
+            vec![
+                (Some("GET".to_string()), "/users".to_string()),
+                (Some("POST".to_string()), "/users/:id".to_string()),
+                (None, "/health".to_string()),
+                (None, "/contacts/".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parses_manifest_entries() {
+        let text = r#"["/users", "post /users", { "method": "delete", "path": "/users/1" }]"#;
+
+        assert_eq!(
+            parse_manifest(text),
+            vec![
+                (None, "/users".to_string()),
+                (Some("POST".to_string()), "/users".to_string()),
+                (Some("DELETE".to_string()), "/users/1".to_string()),
+            ]
+        );
+        assert!(parse_manifest("not json").is_empty());
+    }
+}
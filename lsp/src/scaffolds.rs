@@ -0,0 +1,189 @@
+//! Whole elements wired together for the usual htmx patterns of the
+//! examples at htmx.org, inserted with `workspace/executeCommand`.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use lsp_types::{
+    request::ApplyWorkspaceEdit, ApplyWorkspaceEditParams, Position, Range, TextEdit, Url,
+    WorkspaceEdit,
+};
+use serde_json::Value;
+
+use crate::{
+    client::send_request,
+    text_store::{get_text_document, is_script_document},
+};
+
+pub const INSERT_SCAFFOLD_COMMAND: &str = "htmx-lsp.insertScaffold";
+
+pub struct Scaffold {
+    pub name: &'static str,
+    pub description: &'static str,
+    body: &'static str,
+}
+
+pub const SCAFFOLDS: [Scaffold; 3] = [
+    Scaffold {
+        name: "active-search",
+        description: "Search as the user types, the results replace the rows of the table",
+        body: r##"<input type="search" name="search" placeholder="Begin typing to search..."
+       hx-post="/search"
+       hx-trigger="input changed delay:500ms, search"
+       hx-target="#search-results"
+       hx-indicator=".htmx-indicator">
+<span class="htmx-indicator">Searching...</span>
+<table>
+  <tbody id="search-results"></tbody>
+</table>"##,
+    },
+    Scaffold {
+        name: "infinite-scroll",
+        description: "Load the next page once the last row scrolls into view",
+        body: r##"<tr hx-get="/items?page=2"
+    hx-trigger="revealed"
+    hx-swap="afterend">
+  <td>...</td>
+</tr>"##,
+    },
+    Scaffold {
+        name: "click-to-edit",
+        description: "Swap a read only view for its edit form, and back once saved",
+        body: r##"<div hx-target="this" hx-swap="outerHTML">
+  <div><label>Name</label>: Joe</div>
+  <button hx-get="/contact/1/edit">Click To Edit</button>
+</div>"##,
+    },
+];
+
+/// The edit inserting the scaffold `name` at `position` of `text`, its
+/// lines indented like the line of the position.
+pub fn scaffold_edit(name: &str, text: &str, position: Position) -> Option<TextEdit> {
+    let scaffold = SCAFFOLDS.iter().find(|scaffold| scaffold.name == name)?;
+    let line = text.lines().nth(position.line as usize).unwrap_or("");
+    let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+
+    let new_text = scaffold
+        .body
+        .lines()
+        .enumerate()
+        .map(|(idx, body_line)| match idx {
+            0 => body_line.to_string(),
+            _ => format!("{}{}", indent, body_line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    return Some(TextEdit {
+        range: Range::new(position, position),
+        new_text,
+    });
+}
+
+/// Asks the editor to insert the scaffold named by the first argument into
+/// the document of the second at the position of the third. Without
+/// arguments it returns the scaffolds, for editors to offer a pick list.
+pub fn execute_insert_scaffold(arguments: Vec<Value>) -> Result<Value> {
+    if arguments.is_empty() {
+        let scaffolds: Vec<Value> = SCAFFOLDS
+            .iter()
+            .map(|scaffold| {
+                return serde_json::json!({
+                    "name": scaffold.name,
+                    "description": scaffold.description,
+                });
+            })
+            .collect();
+        return Ok(Value::Array(scaffolds));
+    }
+
+    let names = || {
+        return SCAFFOLDS
+            .iter()
+            .map(|scaffold| scaffold.name)
+            .collect::<Vec<_>>()
+            .join(", ");
+    };
+    let name = arguments.first().and_then(|name| name.as_str());
+    let uri = arguments
+        .get(1)
+        .and_then(|uri| uri.as_str())
+        .and_then(|uri| Url::parse(uri).ok());
+    let position = arguments
+        .get(2)
+        .and_then(|position| serde_json::from_value::<Position>(position.clone()).ok());
+
+    let (Some(name), Some(uri), Some(position)) = (name, uri, position) else {
+        return Err(anyhow!(
+            "{} expects a scaffold ({}), a document uri and a position",
+            INSERT_SCAFFOLD_COMMAND,
+            names()
+        ));
+    };
+    if !SCAFFOLDS.iter().any(|scaffold| scaffold.name == name) {
+        return Err(anyhow!("unknown scaffold {}, use one of {}", name, names()));
+    }
+
+    if is_script_document(&uri) {
+        return Err(anyhow!(
+            "scaffolds can't be inserted in the strings of a script"
+        ));
+    }
+
+    let text = get_text_document(uri.clone()).unwrap_or_default();
+    let edit =
+        scaffold_edit(name, &text, position).ok_or_else(|| anyhow!("unknown scaffold {}", name))?;
+
+    send_request::<ApplyWorkspaceEdit>(ApplyWorkspaceEditParams {
+        label: Some(format!("Insert the {} scaffold", name)),
+        edit: WorkspaceEdit {
+            changes: Some(HashMap::from([(uri, vec![edit])])),
+            ..Default::default()
+        },
+    });
+
+    return Ok(Value::Null);
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Position;
+
+    use super::{execute_insert_scaffold, scaffold_edit, SCAFFOLDS};
+
+    #[test]
+    fn test_scaffolds_follow_the_indentation_of_the_line() {
+        let text = "<body>\n    \n</body>";
+        let edit = scaffold_edit("click-to-edit", text, Position::new(1, 4)).expect("known");
+
+        assert_eq!(edit.range.start, Position::new(1, 4));
+        assert_eq!(edit.range.end, Position::new(1, 4));
+        assert!(edit
+            .new_text
+            .starts_with("<div hx-target=\"this\" hx-swap=\"outerHTML\">\n      <div>"));
+        assert!(edit.new_text.ends_with("\n    </div>"));
+
+        assert!(scaffold_edit("modal", text, Position::new(0, 0)).is_none());
+        for scaffold in SCAFFOLDS.iter() {
+            assert!(scaffold.body.contains("hx-"), "{}", scaffold.name);
+        }
+    }
+
+    #[test]
+    fn test_rejects_unknown_scaffolds() {
+        let arguments = vec![
+            "modal".into(),
+            "file:///page.html".into(),
+            serde_json::json!({ "line": 0, "character": 0 }),
+        ];
+
+        assert_eq!(
+            execute_insert_scaffold(arguments).unwrap_err().to_string(),
+            "unknown scaffold modal, use one of active-search, infinite-scroll, click-to-edit"
+        );
+        assert!(execute_insert_scaffold(vec!["modal".into()]).is_err());
+
+        let listed = execute_insert_scaffold(vec![]).expect("lists the scaffolds");
+        assert_eq!(listed[0]["name"], "active-search");
+        assert_eq!(listed.as_array().map(Vec::len), Some(SCAFFOLDS.len()));
+    }
+}
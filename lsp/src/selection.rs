@@ -0,0 +1,87 @@
+use lsp_types::{Position, Range, SelectionRange};
+
+use crate::{
+    encoding::{byte_offset_to_position, position_to_byte_offset},
+    tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+};
+
+fn lsp_range(source: &str, start: usize, end: usize) -> Range {
+    return Range {
+        start: byte_offset_to_position(source, start),
+        end: byte_offset_to_position(source, end),
+    };
+}
+
+/// Byte ranges around `byte` from the innermost outwards: value token,
+/// quoted value, attribute, tag, element and then the enclosing elements.
+fn enclosing_ranges(source: &str, byte: usize) -> Vec<(usize, usize)> {
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+
+    let mut ranges = vec![];
+
+    let token = query_attributes(tree.root_node(), source)
+        .into_iter()
+        .find_map(|attribute| {
+            let (start, token) = attribute.value_token_at(byte)?;
+            return Some((start, start + token.len()));
+        });
+    ranges.extend(token);
+
+    let mut node = tree.root_node().descendant_for_byte_range(byte, byte);
+    while let Some(current) = node {
+        // Anonymous nodes are punctuation like the quotes or "="
+        if current.is_named() {
+            ranges.push((current.start_byte(), current.end_byte()));
+        }
+        node = current.parent();
+    }
+
+    ranges.dedup();
+    return ranges;
+}
+
+pub fn selection_range(source: &str, position: Position) -> Option<SelectionRange> {
+    let byte = position_to_byte_offset(source, position)?;
+
+    return enclosing_ranges(source, byte)
+        .into_iter()
+        .rev()
+        .fold(None, |parent, (start, end)| {
+            return Some(SelectionRange {
+                range: lsp_range(source, start, end),
+                parent: parent.map(Box::new),
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::enclosing_ranges;
+
+    #[test]
+    fn test_expands_from_token_to_element() {
+        let text = r##"<body><div hx-swap="outerHTML swap:1s">x</div></body>"##;
+        let byte = text.find("swap:").expect("has modifier") + 2;
+
+        let selections: Vec<_> = enclosing_ranges(text, byte)
+            .into_iter()
+            .map(|(start, end)| &text[start..end])
+            .collect();
+
+        assert_eq!(
+            selections,
+            vec![
+                "swap:1s",
+                "outerHTML swap:1s",
+                "\"outerHTML swap:1s\"",
+                "hx-swap=\"outerHTML swap:1s\"",
+                "<div hx-swap=\"outerHTML swap:1s\">",
+                "<div hx-swap=\"outerHTML swap:1s\">x</div>",
+                "<body><div hx-swap=\"outerHTML swap:1s\">x</div></body>",
+            ]
+        );
+    }
+}
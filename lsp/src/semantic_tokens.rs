@@ -0,0 +1,203 @@
+use lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens, SemanticTokensLegend,
+};
+
+use crate::{
+    encoding::byte_offset_to_position,
+    htmx::{canonical_name, htmx_name, HX_ATTRIBUTE_VALUES, HX_TAGS},
+    tree_sitter::parse_html,
+    tree_sitter_querier::{query_attributes, AttributeNode},
+};
+
+const ATTRIBUTE: u32 = 0;
+const SWAP_STRATEGY: u32 = 1;
+const TRIGGER_EVENT: u32 = 2;
+const MODIFIER: u32 = 3;
+
+/// Set on the attributes and values that are part of htmx itself
+const BUNDLED: u32 = 1;
+
+pub fn legend() -> SemanticTokensLegend {
+    return SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::PROPERTY,
+            SemanticTokenType::ENUM_MEMBER,
+            SemanticTokenType::EVENT,
+            SemanticTokenType::MODIFIER,
+        ],
+        token_modifiers: vec![SemanticTokenModifier::DEFAULT_LIBRARY],
+    };
+}
+
+/// A token before it gets delta encoded, with byte offsets into the source.
+struct RawToken {
+    start: usize,
+    end: usize,
+    token_type: u32,
+    modifiers: u32,
+}
+
+fn is_bundled_value(attribute: &str, token: &str) -> bool {
+    return HX_ATTRIBUTE_VALUES
+        .get()
+        .and_then(|values| values.get(attribute))
+        .map(|values| values.iter().any(|value| value.name == token))
+        .unwrap_or(false);
+}
+
+fn value_tokens(attribute: &AttributeNode, source: &str) -> Vec<RawToken> {
+    let tokens = attribute.value_tokens();
+
+    return match canonical_name(&attribute.name) {
+        "hx-swap" => tokens
+            .into_iter()
+            .map(|(start, token)| {
+                let token_type = if is_bundled_value("hx-swap", token) {
+                    SWAP_STRATEGY
+                } else {
+                    MODIFIER
+                };
+
+                return RawToken {
+                    start,
+                    end: start + token.len(),
+                    token_type,
+                    modifiers: 0,
+                };
+            })
+            .collect(),
+        "hx-trigger" => tokens
+            .into_iter()
+            .map(|(start, token)| {
+                // Each comma separated trigger starts with its event
+                let before = source[..start].trim_end();
+                let is_event = before.ends_with(',') || before.ends_with(['"', '\'', '=']);
+                let event = token.split('[').next().unwrap_or(token);
+
+                return match is_event {
+                    true => RawToken {
+                        start,
+                        end: start + event.len(),
+                        token_type: TRIGGER_EVENT,
+                        modifiers: 0,
+                    },
+                    false => RawToken {
+                        start,
+                        end: start + token.len(),
+                        token_type: MODIFIER,
+                        modifiers: 0,
+                    },
+                };
+            })
+            .collect(),
+        _ => vec![],
+    };
+}
+
+fn raw_tokens(source: &str) -> Vec<RawToken> {
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+
+    let mut tokens = vec![];
+    for attribute in query_attributes(tree.root_node(), source) {
+        let Some(name) = htmx_name(&attribute.name) else {
+            continue;
+        };
+
+        let bundled = HX_TAGS
+            .get()
+            .map(|tags| tags.iter().any(|tag| tag.name == name))
+            .unwrap_or(false);
+
+        tokens.push(RawToken {
+            start: attribute.name_range.start_byte,
+            end: attribute.name_range.end_byte,
+            token_type: ATTRIBUTE,
+            modifiers: if bundled { BUNDLED } else { 0 },
+        });
+        tokens.extend(value_tokens(&attribute, source));
+    }
+
+    return tokens;
+}
+
+/// Encodes the tokens relative to each other as the protocol expects.
+pub fn semantic_tokens(source: &str) -> SemanticTokens {
+    let mut data = vec![];
+    let (mut line, mut character) = (0, 0);
+
+    for token in raw_tokens(source) {
+        let start = byte_offset_to_position(source, token.start);
+        let end = byte_offset_to_position(source, token.end);
+        if start.line != end.line || end.character <= start.character {
+            continue;
+        }
+
+        let delta_line = start.line - line;
+        let delta_start = match delta_line {
+            0 => start.character - character,
+            _ => start.character,
+        };
+
+        data.push(SemanticToken {
+            delta_line,
+            delta_start,
+            length: end.character - start.character,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+        (line, character) = (start.line, start.character);
+    }
+
+    return SemanticTokens {
+        result_id: None,
+        data,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::semantic_tokens;
+    use crate::htmx::init_hx_tags;
+    use lsp_types::SemanticToken;
+
+    fn token(delta_line: u32, delta_start: u32, length: u32, token_type: u32) -> SemanticToken {
+        return SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        };
+    }
+
+    #[test]
+    fn test_tokens_for_swap_and_trigger() {
+        init_hx_tags();
+        let text = r##"<div class="a" hx-swap="outerHTML swap:1s"
+  hx-trigger="click[ctrlKey] once, keyup delay:1s"></div>"##;
+
+        let data = semantic_tokens(text).data;
+
+        assert_eq!(
+            data,
+            vec![
+                SemanticToken {
+                    token_modifiers_bitset: 1,
+                    ..token(0, 15, 7, 0)
+                },
+                token(0, 9, 9, 1),
+                token(0, 10, 7, 3),
+                SemanticToken {
+                    token_modifiers_bitset: 1,
+                    ..token(1, 2, 10, 0)
+                },
+                token(0, 12, 5, 2),
+                token(0, 15, 4, 3),
+                token(0, 6, 5, 2),
+                token(0, 6, 8, 3),
+            ]
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use lsp_types::{DocumentSymbol, Location, Range, SymbolInformation, SymbolKind, Url};
+use tree_sitter::{Node, Query, QueryCursor};
+
+use crate::{
+    encoding::ts_range_to_lsp,
+    htmx::{canonical_name, htmx_name},
+    index::INDEX,
+    links::REQUEST_ATTRIBUTES,
+    text_store::TEXT_STORE,
+    tree_sitter::parse_html,
+    tree_sitter_querier::{query_attributes, AttributeNode},
+};
+
+/// An element issuing a request, kept in the workspace index so endpoint
+/// searches don't need to parse every template again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    pub tag: String,
+    pub method: String,
+    pub url: String,
+    pub range: Range,
+}
+
+impl Endpoint {
+    pub fn label(&self) -> String {
+        return format!("{} {} {}", self.tag, self.method, self.url)
+            .trim_end()
+            .to_string();
+    }
+}
+
+/// The request an element issues, eg. ("POST", "/search") for
+/// `hx-post="/search"`.
+pub fn element_request(attributes: &[AttributeNode]) -> Option<(String, String)> {
+    return attributes.iter().find_map(|attribute| {
+        let name = canonical_name(&attribute.name);
+        if !REQUEST_ATTRIBUTES.contains(&name) {
+            return None;
+        }
+
+        let method = name.trim_start_matches("hx-").to_uppercase();
+        let url = attribute.value.clone().unwrap_or_default();
+        return Some((method, url.trim().to_string()));
+    });
+}
+
+fn element_symbol(element: Node<'_>, source: &str) -> Option<DocumentSymbol> {
+    let tag = (0..element.child_count())
+        .filter_map(|idx| element.child(idx))
+        .find(|n| n.kind() == "start_tag" || n.kind() == "self_closing_tag")?;
+    let tag_name = tag.child(1).filter(|n| n.kind() == "tag_name")?;
+
+    let attributes = query_attributes(tag, source);
+    let hx_attributes: Vec<&str> = attributes
+        .iter()
+        .map(|attribute| attribute.name.as_str())
+        .filter(|name| htmx_name(name).is_some())
+        .collect();
+    if hx_attributes.is_empty() {
+        return None;
+    }
+
+    let tag_name_text = &source[tag_name.byte_range()];
+    let (name, kind) = match element_request(&attributes) {
+        Some((method, url)) => (
+            format!("{} {} {}", tag_name_text, method, url),
+            SymbolKind::FUNCTION,
+        ),
+        None => (tag_name_text.to_string(), SymbolKind::OBJECT),
+    };
+
+    #[allow(deprecated)]
+    return Some(DocumentSymbol {
+        name: name.trim_end().to_string(),
+        detail: Some(hx_attributes.join(" ")),
+        kind,
+        tags: None,
+        deprecated: None,
+        range: ts_range_to_lsp(source, element.range()),
+        selection_range: ts_range_to_lsp(source, tag_name.range()),
+        children: None,
+    });
+}
+
+/// Elements without hx-* attributes don't show up, their htmx descendants
+/// are lifted to the closest htmx ancestor.
+fn collect_symbols(node: Node<'_>, source: &str) -> Vec<DocumentSymbol> {
+    let mut symbols = vec![];
+    let mut cursor = node.walk();
+
+    for child in node.named_children(&mut cursor) {
+        let children = collect_symbols(child, source);
+        if child.kind() != "element" {
+            symbols.extend(children);
+            continue;
+        }
+
+        match element_symbol(child, source) {
+            Some(mut symbol) => {
+                symbol.children = (!children.is_empty()).then_some(children);
+                symbols.push(symbol);
+            }
+            None => symbols.extend(children),
+        }
+    }
+
+    return symbols;
+}
+
+pub fn document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    return match parse_html(source) {
+        Some(tree) => collect_symbols(tree.root_node(), source),
+        None => vec![],
+    };
+}
+
+pub fn document_endpoints(source: &str) -> Vec<Endpoint> {
+    let Some(tree) = parse_html(source) else {
+        return vec![];
+    };
+
+    let query = Query::new(
+        tree_sitter_html::language(),
+        "[(start_tag) (self_closing_tag)] @tag",
+    )
+    .expect("tag query is valid");
+    let mut cursor_qry = QueryCursor::new();
+
+    return cursor_qry
+        .matches(&query, tree.root_node(), source.as_bytes())
+        .flat_map(|match_| match_.captures)
+        .filter_map(|capture| {
+            let tag = capture.node;
+            let tag_name = tag.child(1).filter(|n| n.kind() == "tag_name")?;
+            let (method, url) = element_request(&query_attributes(tag, source))?;
+
+            return Some(Endpoint {
+                tag: source[tag_name.byte_range()].to_string(),
+                method,
+                url,
+                range: ts_range_to_lsp(source, tag.range()),
+            });
+        })
+        .collect();
+}
+
+/// The endpoints of every known document, open documents are parsed as
+/// they are and the rest comes from the index.
+pub fn workspace_endpoints() -> Vec<(Url, Vec<Endpoint>)> {
+    let text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+    let open: Vec<String> = text_store.texts.keys().cloned().collect();
+    let mut endpoints: Vec<(Url, Vec<Endpoint>)> = text_store
+        .texts
+        .keys()
+        .filter_map(|key| {
+            let endpoints = document_endpoints(&text_store.html(key)?);
+            return Some((text_store.uri(key)?, endpoints));
+        })
+        .collect();
+    drop(text_store);
+
+    let index = INDEX
+        .get()
+        .expect("index not initialized")
+        .lock()
+        .expect("index mutex poisoned");
+
+    for (key, indexed) in index.endpoints.iter() {
+        if open.contains(key) {
+            continue;
+        }
+        if let Some(uri) = index.uri(key) {
+            endpoints.push((uri, indexed.clone()));
+        }
+    }
+
+    return endpoints;
+}
+
+/// Elements of the workspace requesting an url containing `query`.
+pub fn workspace_symbols(query: &str) -> Vec<SymbolInformation> {
+    let query = query.to_lowercase();
+
+    return workspace_endpoints()
+        .into_iter()
+        .flat_map(|(uri, endpoints)| {
+            return endpoints
+                .into_iter()
+                .filter(|endpoint| endpoint.url.to_lowercase().contains(&query))
+                .map(|endpoint| {
+                    #[allow(deprecated)]
+                    return SymbolInformation {
+                        name: endpoint.label(),
+                        kind: SymbolKind::FUNCTION,
+                        tags: None,
+                        deprecated: None,
+                        location: Location {
+                            uri: uri.clone(),
+                            range: endpoint.range,
+                        },
+                        container_name: None,
+                    };
+                })
+                .collect::<Vec<_>>();
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{document_endpoints, document_symbols};
+    use lsp_types::SymbolKind;
+
+    #[test]
+    fn test_outlines_htmx_elements() {
+        let text = r##"<main>
+  <form hx-post="/search" hx-target="#results">
+    <input name="q" hx-get="/suggest" hx-trigger="keyup">
+  </form>
+  <div id="results" hx-swap-oob="true"></div>
+  <p>plain</p>
+</main>"##;
+
+        let symbols = document_symbols(text);
+
+        let names: Vec<_> = symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["form POST /search", "div"]);
+        assert_eq!(symbols[0].kind, SymbolKind::FUNCTION);
+        assert_eq!(symbols[0].detail.as_deref(), Some("hx-post hx-target"));
+
+        let children = symbols[0].children.as_ref().expect("form has children");
+        assert_eq!(children[0].name, "input GET /suggest");
+    }
+
+    #[test]
+    fn test_lists_request_endpoints() {
+        let text = r##"<form hx-post="/users">
+  <button hx-delete="/users/1"/>
+  <div hx-target="#x"></div>
+</form>"##;
+
+        let labels: Vec<_> = document_endpoints(text)
+            .iter()
+            .map(|endpoint| endpoint.label())
+            .collect();
+
+        assert_eq!(labels, vec!["form POST /users", "button DELETE /users/1"]);
+    }
+}
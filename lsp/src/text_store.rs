@@ -1,25 +1,212 @@
-use std::{collections::HashMap, sync::{OnceLock, Arc, Mutex}};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
 
-use lsp_types::Url;
+use lsp_types::{TextDocumentContentChangeEvent, Url};
+
+use crate::{
+    embedded::{embedded_html, is_script},
+    encoding::position_to_byte_offset,
+    uri::uri_key,
+};
 
 pub struct TextStore {
     pub texts: HashMap<String, String>,
+    pub language_ids: HashMap<String, String>,
+    pub versions: HashMap<String, i32>,
+    /// The uri each document was opened under, keys are for lookups only
+    pub uris: HashMap<String, Url>,
+}
+
+impl TextStore {
+    /// The uri to hand back to the client for the document stored under
+    /// `key`.
+    pub fn uri(&self, key: &str) -> Option<Url> {
+        return self.uris.get(key).cloned().or_else(|| Url::parse(key).ok());
+    }
+
+    /// The text stored under `key`, for scripts the HTML of their strings.
+    pub fn html(&self, key: &str) -> Option<String> {
+        let text = self.texts.get(key)?;
+        let script = self.uri(key).is_some_and(|uri| {
+            return is_script(&uri, self.language_ids.get(key).map(String::as_str));
+        });
+        if script {
+            return Some(embedded_html(text));
+        }
+
+        return Some(text.clone());
+    }
 }
 
 pub static TEXT_STORE: OnceLock<Arc<Mutex<TextStore>>> = OnceLock::new();
 pub fn init_text_store() {
     _ = TEXT_STORE.set(Arc::new(Mutex::new(TextStore {
         texts: HashMap::new(),
+        language_ids: HashMap::new(),
+        versions: HashMap::new(),
+        uris: HashMap::new(),
     })));
 }
 
+/// The text of an open document, for scripts the HTML of their strings.
 pub fn get_text_document(uri: Url) -> Option<String> {
+    let text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+
+    return text_store.html(&uri_key(&uri));
+}
+
+/// Whether the open document is a script, whose HTML can be read but not
+/// written to: an edit of the markup lands in a string unescaped.
+pub fn is_script_document(uri: &Url) -> bool {
+    let text_store = TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned");
+
+    let language_id = text_store.language_ids.get(&uri_key(uri));
+    return is_script(uri, language_id.map(String::as_str));
+}
+
+pub fn get_version(uri: &Url) -> Option<i32> {
+    return TEXT_STORE
+        .get()
+        .expect("text store not initialized")
+        .lock()
+        .expect("text store mutex poisoned")
+        .versions
+        .get(&uri_key(uri))
+        .copied();
+}
+
+pub fn get_language_id(uri: &Url) -> Option<String> {
     return TEXT_STORE
         .get()
         .expect("text store not initialized")
         .lock()
         .expect("text store mutex poisoned")
-        .texts
-        .get(&uri.to_string())
+        .language_ids
+        .get(&uri_key(uri))
         .cloned();
 }
+
+/// Applies a didChange content change to `text`. Changes without a range
+/// replace the whole document, ranged ones splice the new text in place.
+pub fn apply_content_change(text: &mut String, change: TextDocumentContentChangeEvent) {
+    let range = match change.range {
+        Some(range) => range,
+        None => {
+            *text = change.text;
+            return;
+        }
+    };
+
+    let start = position_to_byte_offset(text, range.start);
+    let end = position_to_byte_offset(text, range.end);
+
+    // Positions past the last line point at the end of the document
+    let start = start.unwrap_or(text.len());
+    let end = end.unwrap_or(text.len()).max(start);
+
+    text.replace_range(start..end, &change.text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_content_change, init_text_store, is_script_document, TEXT_STORE};
+    use crate::uri::uri_key;
+    use lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        return TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            }),
+            range_length: None,
+            text: text.to_string(),
+        };
+    }
+
+    #[test]
+    fn test_full_change_replaces_text() {
+        let mut text = String::from("<div></div>");
+
+        apply_content_change(
+            &mut text,
+            TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "<span></span>".to_string(),
+            },
+        );
+
+        assert_eq!(text, "<span></span>");
+    }
+
+    #[test]
+    fn test_incremental_insert() {
+        let mut text = String::from("<div ></div>");
+
+        apply_content_change(&mut text, change((0, 5), (0, 5), "hx-get=\"/\""));
+
+        assert_eq!(text, "<div hx-get=\"/\"></div>");
+    }
+
+    #[test]
+    fn test_incremental_replace_across_lines() {
+        let mut text = String::from("<div>\n  <span>\n</div>");
+
+        apply_content_change(&mut text, change((0, 4), (1, 7), " hx-swap=\"none\""));
+
+        assert_eq!(text, "<div hx-swap=\"none\">\n</div>");
+    }
+
+    #[test]
+    fn test_batched_changes_apply_in_order() {
+        let mut text = String::from("<div></div>");
+
+        for c in [
+            change((0, 4), (0, 4), " hx-get=\"/\""),
+            change((0, 15), (0, 15), " hx-swap=\"none\""),
+        ] {
+            apply_content_change(&mut text, c);
+        }
+
+        assert_eq!(text, "<div hx-get=\"/\" hx-swap=\"none\"></div>");
+    }
+
+    #[test]
+    fn test_incremental_delete() {
+        let mut text = String::from("<div hx-boost=\"true\"></div>");
+
+        apply_content_change(&mut text, change((0, 4), (0, 20), ""));
+
+        assert_eq!(text, "<div></div>");
+    }
+
+    #[test]
+    fn test_scripts_are_told_apart_for_edits() {
+        init_text_store();
+        let script = Url::parse("file:///app/rows.js").expect("valid uri");
+        let template = Url::parse("file:///app/rows.html").expect("valid uri");
+        let renamed = Url::parse("file:///app/widget.js").expect("valid uri");
+        TEXT_STORE
+            .get()
+            .expect("text store initialized")
+            .lock()
+            .expect("text store mutex")
+            .language_ids
+            .insert(uri_key(&renamed), "html".to_string());
+
+        assert!(is_script_document(&script));
+        assert!(!is_script_document(&template));
+        assert!(!is_script_document(&renamed));
+    }
+}
@@ -1,12 +1,11 @@
 use crate::tree_sitter_querier::{
     query_attr_keys_for_completion, query_attr_values_for_completion,
 };
-use log::{debug, error};
+use log::debug;
 use lsp_types::TextDocumentPositionParams;
-use std::collections::HashMap;
-use tree_sitter::{Node, Parser, Point, Query, QueryCursor};
+use tree_sitter::{Node, Parser, Point, Tree};
 
-use crate::text_store::get_text_document;
+use crate::{encoding::position_to_point, text_store::get_text_document};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Position {
@@ -14,6 +13,7 @@ pub enum Position {
     AttributeValue { name: String, value: String },
 }
 
+#[cfg(test)]
 fn get_text(node: Node<'_>, source: &str) -> String {
     return node
         .utf8_text(source.as_bytes())
@@ -21,6 +21,7 @@ fn get_text(node: Node<'_>, source: &str) -> String {
         .to_string();
 }
 
+#[cfg(test)]
 fn get_attribute_name_and_value(node: Node<'_>, source: &str) -> Option<Position> {
     let value = get_text(node, source);
     let name = get_text(node.prev_named_sibling()?, source);
@@ -28,6 +29,7 @@ fn get_attribute_name_and_value(node: Node<'_>, source: &str) -> Option<Position
     return Some(Position::AttributeValue { name, value });
 }
 
+#[cfg(test)]
 fn create_attribute(node: Node<'_>, source: &str) -> Option<Position> {
     match node.kind() {
         "\"" => return create_attribute(node.parent()?, source),
@@ -51,7 +53,7 @@ fn create_attribute(node: Node<'_>, source: &str) -> Option<Position> {
                     return get_attribute_name_and_value(node, source);
                 }
             } else {
-                error!("why is there no parent??");
+                log::error!("why is there no parent??");
                 todo!("should fix this issue");
             }
         }
@@ -69,11 +71,39 @@ fn find_element_referent_to_current_node(node: Node<'_>) -> Option<Node<'_>> {
     return find_element_referent_to_current_node(node.parent()?);
 }
 
+/// Whether the node is javascript, css or a comment, where text can look
+/// like attributes without being any. The tags of script and style
+/// elements still take attributes.
+fn is_in_raw_text(node: Node<'_>) -> bool {
+    return match node.kind() {
+        "start_tag" | "self_closing_tag" | "end_tag" => false,
+        "comment" | "script_element" | "style_element" => true,
+        _ => node.parent().is_some_and(is_in_raw_text),
+    };
+}
+
+/// Whether the position is in javascript, css or a comment of the text.
+pub fn is_raw_text_at(text: &str, position: lsp_types::Position) -> bool {
+    let Some(tree) = parse_html(text) else {
+        return false;
+    };
+    let point = position_to_point(text, position);
+
+    return tree
+        .root_node()
+        .descendant_for_point_range(point, point)
+        .is_some_and(is_in_raw_text);
+}
+
 fn query_position(root: Node<'_>, source: &str, trigger_point: Point) -> Option<Position> {
     debug!("query_position root {:?}", root.to_sexp());
     let closest_node = root.descendant_for_point_range(trigger_point, trigger_point)?;
     debug!("query_position closest_node {:?}", closest_node.to_sexp());
 
+    if is_in_raw_text(closest_node) {
+        return None;
+    }
+
     let element = find_element_referent_to_current_node(closest_node)?;
 
     let attr_completion = query_attr_keys_for_completion(element, source, trigger_point);
@@ -87,25 +117,19 @@ fn query_position(root: Node<'_>, source: &str, trigger_point: Point) -> Option<
     return value_completion;
 }
 
+// Point-based lookup the tests check the query based resolver against
+#[cfg(test)]
 fn get_position(root: Node<'_>, source: &str, row: usize, column: usize) -> Option<Position> {
-    error!("get_position");
+    debug!("get_position");
 
     let desc = root.descendant_for_point_range(Point { row, column }, Point { row, column })?;
 
-    error!("get_position: desc {:?}", desc);
+    debug!("get_position: desc {:?}", desc);
 
     return create_attribute(desc, source);
 }
 
-pub fn get_position_from_lsp_completion(
-    text_params: TextDocumentPositionParams,
-) -> Option<Position> {
-    error!("get_position_from_lsp_completion");
-    let text = get_text_document(text_params.text_document.uri)?;
-    error!("get_position_from_lsp_completion: text {}", text);
-    let pos = text_params.position;
-    error!("get_position_from_lsp_completion: pos {:?}", pos);
-
+pub fn parse_html(text: &str) -> Option<Tree> {
     // TODO: Gallons of perf work can be done starting here
     let mut parser = Parser::new();
 
@@ -113,9 +137,21 @@ pub fn get_position_from_lsp_completion(
         .set_language(tree_sitter_html::language())
         .expect("could not load html grammer");
 
-    let tree = parser.parse(&text, None)?;
+    return parser.parse(text, None);
+}
+
+pub fn get_position_from_lsp_completion(
+    text_params: TextDocumentPositionParams,
+) -> Option<Position> {
+    debug!("get_position_from_lsp_completion");
+    let text = get_text_document(text_params.text_document.uri)?;
+    debug!("get_position_from_lsp_completion: text {}", text);
+    let pos = text_params.position;
+    debug!("get_position_from_lsp_completion: pos {:?}", pos);
+
+    let tree = parse_html(&text)?;
     let root_node = tree.root_node();
-    let trigger_point = Point::new(pos.line as usize, pos.character as usize);
+    let trigger_point = position_to_point(&text, pos);
 
     return query_position(root_node, text.as_str(), trigger_point);
 }
@@ -151,6 +187,36 @@ mod tests {
         assert_eq!(matches, Some(Position::AttributeName("hx-".to_string())));
     }
 
+    #[test]
+    fn test_does_not_match_in_scripts_styles_and_comments() {
+        // The empty hx-swap used to be taken as the one being completed
+        let cases = [
+            (
+                r##"<div hx-swap=""><script>let html = "<b hx- "</script></div>"##,
+                42,
+            ),
+            (
+                r##"<div hx-swap=""><style>/* <b hx- > */</style></div>"##,
+                32,
+            ),
+            (r##"<div hx-swap=""><!-- <b hx- > --></div>"##, 27),
+        ];
+
+        for (text, column) in cases {
+            let tree = prepare_tree(text);
+
+            assert_eq!(
+                query_position(tree.root_node(), text, Point::new(0, column)),
+                None
+            );
+        }
+
+        let text = r##"<script hx- ></script>"##;
+        let tree = prepare_tree(text);
+        let matches = query_position(tree.root_node(), text, Point::new(0, 11));
+        assert_eq!(matches, Some(Position::AttributeName("hx-".to_string())));
+    }
+
     #[test]
     fn test_does_not_match_when_quote_not_initiated() {
         let text = r##"<div hx-swap= ></div>"##;
@@ -184,6 +250,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_it_matches_data_prefixed_values() {
+        let text = r##"<div data-hx-swap=""></div>"##;
+
+        let tree = prepare_tree(text);
+
+        let matches = query_position(tree.root_node(), text, Point::new(0, 19));
+
+        assert_eq!(
+            matches,
+            Some(Position::AttributeValue {
+                name: "data-hx-swap".to_string(),
+                value: "".to_string()
+            })
+        );
+    }
+
     #[test]
     fn test_it_matches_when_open_and_closed_quotes() {
         let text = r##"<div hx-swap=""></div>"##;
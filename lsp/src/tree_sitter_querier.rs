@@ -5,7 +5,10 @@ use std::collections::HashMap;
 use log::{debug, error};
 use tree_sitter::{Node, Point, Query, QueryCursor, Range};
 
-use crate::tree_sitter::Position;
+use crate::{
+    htmx::{extension_attribute_names, HYPERSCRIPT_ATTRIBUTES},
+    tree_sitter::Position,
+};
 
 // If error char is "=" means the key name is completed and the cursor is
 // at the "=" but no quote, so we shouldn't suggest yet eg <div hx-foo=|>
@@ -23,9 +26,8 @@ fn query_props(
     source: &str,
     trigger_point: Point,
 ) -> Option<HashMap<String, CaptureDetails>> {
-    let query = Query::new(tree_sitter_html::language(), query_string).expect(&format!(
-        "get_position_by_query invalid query {query_string}"
-    ));
+    let query = Query::new(tree_sitter_html::language(), query_string)
+        .unwrap_or_else(|_| panic!("get_position_by_query invalid query {query_string}"));
     let mut cursor_qry = QueryCursor::new();
 
     let capture_names = query.capture_names();
@@ -92,7 +94,7 @@ pub fn query_attr_keys_for_completion(
     let props = attr_completion?;
     let attr_name = props.get("attr_name")?;
 
-    if props.get("unfinished_tag").is_some() {
+    if props.contains_key("unfinished_tag") {
         return None;
     }
 
@@ -104,8 +106,15 @@ pub fn query_attr_values_for_completion(
     source: &str,
     trigger_point: Point,
 ) -> Option<Position> {
+    // Extension and hyperscript attributes go without the hx- prefix
+    let extension_names: Vec<String> = extension_attribute_names()
+        .chain(HYPERSCRIPT_ATTRIBUTES)
+        .map(|name| format!("|{}$", name))
+        .collect();
+
     // [ means match any of the following
-    let query_string = r#"(
+    let query_string = format!(
+        r#"(
         [
           (ERROR 
             (tag_name) 
@@ -132,7 +141,7 @@ pub fn query_attr_values_for_completion(
               (attribute_name) @attr_name
               (quoted_attribute_value) @quoted_attr_value
 
-              (#eq? @quoted_attr_value "\"\"")
+              (#match? @quoted_attr_value "^(\"\"|'')$")
             ) @empty_attribute
           )
 
@@ -147,17 +156,19 @@ pub fn query_attr_values_for_completion(
           )
         ]
 
-        (#match? @attr_name "hx-.*")
-    )"#;
+        (#match? @attr_name "^((data-)?hx-{})")
+    )"#,
+        extension_names.concat()
+    );
 
-    let value_completion = query_props(query_string, node, source, trigger_point);
+    let value_completion = query_props(&query_string, node, source, trigger_point);
     let props = value_completion?;
 
     let attr_name = props.get("attr_name")?;
 
     debug!("query_attr_values_for_completion attr_name {:?}", attr_name);
 
-    if props.get("open_quote_err").is_some() || props.get("empty_attribute").is_some() {
+    if props.contains_key("open_quote_err") || props.contains_key("empty_attribute") {
         return Some(Position::AttributeValue {
             name: attr_name.value.to_owned(),
             value: "".to_string(),
@@ -182,3 +193,159 @@ pub fn query_attr_values_for_completion(
         value: "".to_string(),
     });
 }
+
+/// An attribute of the document, `value_range` excludes the quotes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeNode {
+    /// The whole attribute, name, "=" and quoted value
+    pub range: Range,
+    pub name: String,
+    pub name_range: Range,
+    pub value: Option<String>,
+    pub value_range: Option<Range>,
+}
+
+impl AttributeNode {
+    /// The whitespace or comma separated tokens of the value, each with its
+    /// absolute start byte.
+    pub fn value_tokens(&self) -> Vec<(usize, &str)> {
+        let (Some(value), Some(range)) = (self.value.as_deref(), self.value_range) else {
+            return vec![];
+        };
+
+        return value
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty())
+            .map(|token| {
+                let offset = token.as_ptr() as usize - value.as_ptr() as usize;
+                return (range.start_byte + offset, token);
+            })
+            .collect();
+    }
+
+    /// Finds the whitespace or comma separated token of the value that
+    /// contains `byte`, returning it with its absolute start byte.
+    pub fn value_token_at(&self, byte: usize) -> Option<(usize, &str)> {
+        let value = self.value.as_deref()?;
+        let range = self.value_range?;
+        if byte < range.start_byte || byte > range.end_byte {
+            return None;
+        }
+
+        let offset = byte - range.start_byte;
+        let is_separator = |c: char| c.is_whitespace() || c == ',';
+        let start = value[..offset].rfind(is_separator).map_or(0, |idx| idx + 1);
+        let end = value[offset..]
+            .find(is_separator)
+            .map_or(value.len(), |idx| offset + idx);
+
+        if start >= end {
+            return None;
+        }
+
+        return Some((range.start_byte + start, &value[start..end]));
+    }
+}
+
+fn unquoted_range(node: Node<'_>, source: &str) -> Range {
+    let mut range = node.range();
+    let text = &source[range.start_byte..range.end_byte];
+
+    if text.starts_with(['"', '\'']) {
+        range.start_byte += 1;
+        range.start_point.column += 1;
+    }
+    if text.len() > 1 && text.ends_with(['"', '\'']) {
+        range.end_byte -= 1;
+        range.end_point.column = range.end_point.column.saturating_sub(1);
+    }
+
+    return range;
+}
+
+pub fn query_attributes(node: Node<'_>, source: &str) -> Vec<AttributeNode> {
+    let query = Query::new(tree_sitter_html::language(), "(attribute) @attr")
+        .expect("attribute query is valid");
+    let mut cursor_qry = QueryCursor::new();
+
+    return cursor_qry
+        .matches(&query, node, source.as_bytes())
+        .flat_map(|match_| match_.captures)
+        .filter_map(|capture| {
+            let attribute = capture.node;
+            let name = attribute
+                .child(0)
+                .filter(|n| n.kind() == "attribute_name")?;
+
+            let value_range = (0..attribute.child_count())
+                .filter_map(|idx| attribute.child(idx))
+                .find(|n| n.kind() == "attribute_value" || n.kind() == "quoted_attribute_value")
+                .map(|n| unquoted_range(n, source));
+
+            return Some(AttributeNode {
+                range: attribute.range(),
+                name: source[name.byte_range()].to_string(),
+                name_range: name.range(),
+                value: value_range.map(|r| source[r.start_byte..r.end_byte].to_string()),
+                value_range,
+            });
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::query_attributes;
+    use crate::tree_sitter::parse_html;
+
+    #[test]
+    fn test_query_attributes_with_and_without_values() {
+        let text = r##"<div hx-boost="true" hidden hx-get=/foo hx-swap=""></div>"##;
+        let tree = parse_html(text).expect("parses");
+
+        let attributes = query_attributes(tree.root_node(), text);
+        let summary: Vec<_> = attributes
+            .iter()
+            .map(|a| (a.name.as_str(), a.value.as_deref()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("hx-boost", Some("true")),
+                ("hidden", None),
+                ("hx-get", Some("/foo")),
+                ("hx-swap", Some("")),
+            ]
+        );
+
+        let boost = attributes[0].value_range.expect("hx-boost has a value");
+        assert_eq!(&text[boost.start_byte..boost.end_byte], "true");
+        assert_eq!(boost.start_point.column, 15);
+        assert_eq!(boost.end_point.column, 19);
+    }
+
+    #[test]
+    fn test_value_token_at() {
+        let text = r##"<div hx-trigger="keyup changed, delay:500ms"></div>"##;
+        let tree = parse_html(text).expect("parses");
+        let attributes = query_attributes(tree.root_node(), text);
+        let trigger = &attributes[0];
+
+        let delay = text.find("delay").expect("has delay");
+        assert_eq!(
+            trigger.value_token_at(delay + 3),
+            Some((delay, "delay:500ms"))
+        );
+
+        let changed = text.find("changed").expect("has changed");
+        assert_eq!(
+            trigger.value_token_at(changed + 7),
+            Some((changed, "changed"))
+        );
+
+        // Between the comma and the space there is no token
+        assert_eq!(trigger.value_token_at(changed + 8), None);
+        assert_eq!(trigger.value_token_at(3), None);
+    }
+}
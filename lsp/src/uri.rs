@@ -0,0 +1,70 @@
+//! Canonical form of document uris, the key of every document map.
+
+use lsp_types::Url;
+use percent_encoding::percent_decode_str;
+
+/// File systems where `Index.html` and `index.html` are the same file
+const CASE_INSENSITIVE: bool = cfg!(any(windows, target_os = "macos"));
+
+/// `/C:/...` and `/c:/...` are the same drive
+fn lowercase_drive(path: &str) -> String {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'/' && bytes[1].is_ascii_alphabetic() && bytes[2] == b':' {
+        return format!("/{}{}", path[1..2].to_ascii_lowercase(), &path[2..]);
+    }
+
+    return path.to_string();
+}
+
+/// The key a document is stored under. Clients encode file uris in
+/// different ways, `file:///C:/a b.html` and `file:///c%3A/a%20b.html` map to
+/// the same key, other schemes like `untitled:` are kept as they are.
+pub fn uri_key(uri: &Url) -> String {
+    if uri.scheme() != "file" {
+        return uri.to_string();
+    }
+
+    let decoded = percent_decode_str(uri.path()).decode_utf8_lossy();
+    let mut path = lowercase_drive(&decoded).replace('%', "%25");
+    if CASE_INSENSITIVE {
+        path = path.to_lowercase();
+    }
+
+    let mut normalized = uri.clone();
+    normalized.set_path(&path);
+    normalized.set_fragment(None);
+
+    return normalized.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::uri_key;
+    use lsp_types::Url;
+
+    fn key(uri: &str) -> String {
+        return uri_key(&Url::parse(uri).expect("valid uri"));
+    }
+
+    #[test]
+    fn test_differently_encoded_uris_share_a_key() {
+        assert_eq!(
+            key("file:///C:/site/a%20b.html"),
+            key("file:///c%3A/site/a b.html")
+        );
+        assert_eq!(
+            key("file:///c%3A/site/index.html"),
+            "file:///c:/site/index.html"
+        );
+        assert_eq!(
+            key("file:///srv/caf%C3%A9.html"),
+            key("file:///srv/café.html")
+        );
+        assert_eq!(key("file:///srv/100%25.html"), "file:///srv/100%25.html");
+    }
+
+    #[test]
+    fn test_keeps_other_schemes() {
+        assert_eq!(key("untitled:Untitled-1"), "untitled:Untitled-1");
+    }
+}
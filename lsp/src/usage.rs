@@ -0,0 +1,124 @@
+//! How often the workspace writes each htmx attribute and value, so the
+//! ones a project keeps using sort before the rest of the completions.
+
+use std::collections::HashMap;
+
+use crate::{
+    htmx::{canonical_name, htmx_name},
+    index::INDEX,
+    text_store::TEXT_STORE,
+    tree_sitter::parse_html,
+    tree_sitter_querier::query_attributes,
+};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Usage {
+    /// Times each attribute is written, by its name without `data-`
+    pub attributes: HashMap<String, usize>,
+    /// Times each word of the values is written, by attribute and word
+    pub values: HashMap<(String, String), usize>,
+}
+
+impl Usage {
+    pub fn attribute(&self, name: &str) -> usize {
+        return self
+            .attributes
+            .get(canonical_name(name))
+            .copied()
+            .unwrap_or(0);
+    }
+
+    pub fn value(&self, attribute: &str, value: &str) -> usize {
+        let key = (canonical_name(attribute).to_string(), value.to_string());
+        return self.values.get(&key).copied().unwrap_or(0);
+    }
+
+    fn add(&mut self, other: &Usage) {
+        for (name, count) in other.attributes.iter() {
+            *self.attributes.entry(name.clone()).or_default() += count;
+        }
+        for (key, count) in other.values.iter() {
+            *self.values.entry(key.clone()).or_default() += count;
+        }
+    }
+}
+
+/// The htmx attributes of `source` and the words of their values, a value
+/// like `outerHTML swap:1s` counting once for each of its words.
+pub fn document_usage(source: &str) -> Usage {
+    let mut usage = Usage::default();
+    let Some(tree) = parse_html(source) else {
+        return usage;
+    };
+
+    for attribute in query_attributes(tree.root_node(), source) {
+        let Some(name) = htmx_name(&attribute.name) else {
+            continue;
+        };
+        *usage.attributes.entry(name.to_string()).or_default() += 1;
+
+        let words = attribute
+            .value
+            .as_deref()
+            .unwrap_or("")
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|word| !word.is_empty());
+        for word in words {
+            *usage
+                .values
+                .entry((name.to_string(), word.to_string()))
+                .or_default() += 1;
+        }
+    }
+
+    return usage;
+}
+
+/// The usage of every known document, open documents are counted as they
+/// are and the rest comes from the index.
+pub fn workspace_usage() -> Usage {
+    let mut usage = Usage::default();
+    let mut counted: Vec<String> = vec![];
+
+    if let Some(store) = TEXT_STORE.get() {
+        let store = store.lock().expect("text store mutex poisoned");
+        for uri in store.texts.keys() {
+            if let Some(html) = store.html(uri) {
+                usage.add(&document_usage(&html));
+            }
+            counted.push(uri.clone());
+        }
+    }
+
+    if let Some(index) = INDEX.get() {
+        let index = index.lock().expect("index mutex poisoned");
+        for (uri, indexed) in index.usage.iter() {
+            if !counted.contains(uri) {
+                usage.add(indexed);
+            }
+        }
+    }
+
+    return usage;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::document_usage;
+
+    #[test]
+    fn test_counts_attributes_and_the_words_of_their_values() {
+        let usage = document_usage(
+            r#"<div hx-get="/a" hx-swap="outerHTML swap:1s"></div>
+<div data-hx-swap="outerHTML" hx-trigger="click, keyup" class="card"></div>"#,
+        );
+
+        assert_eq!(usage.attribute("hx-swap"), 2);
+        assert_eq!(usage.attribute("data-hx-get"), 1);
+        assert_eq!(usage.attribute("class"), 0);
+        assert_eq!(usage.value("hx-swap", "outerHTML"), 2);
+        assert_eq!(usage.value("hx-swap", "swap:1s"), 1);
+        assert_eq!(usage.value("hx-trigger", "keyup"), 1);
+        assert_eq!(usage.value("hx-swap", "innerHTML"), 0);
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex, OnceLock};
+
+use lsp_types::{InitializeParams, Url, WorkspaceFolder, WorkspaceFoldersChangeEvent};
+
+use crate::uri::uri_key;
+
+/// The roots the editor has open, each one is indexed and resolved
+/// independently so monorepos with several template directories work.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    pub folders: Vec<WorkspaceFolder>,
+}
+
+impl Workspace {
+    pub fn from_initialize_params(params: &InitializeParams) -> Self {
+        if let Some(folders) = &params.workspace_folders {
+            return Self {
+                folders: folders.clone(),
+            };
+        }
+
+        // Clients without workspace folder support only send a single root
+        let folders = match &params.root_uri {
+            Some(uri) => vec![WorkspaceFolder {
+                uri: uri.clone(),
+                name: folder_name(uri),
+            }],
+            None => vec![],
+        };
+
+        return Self { folders };
+    }
+
+    pub fn apply_change(&mut self, event: WorkspaceFoldersChangeEvent) {
+        self.folders
+            .retain(|folder| !event.removed.iter().any(|r| r.uri == folder.uri));
+
+        for folder in event.added {
+            if !self.folders.iter().any(|f| f.uri == folder.uri) {
+                self.folders.push(folder);
+            }
+        }
+    }
+
+    /// The innermost workspace folder containing `uri`.
+    pub fn folder_for(&self, uri: &Url) -> Option<&WorkspaceFolder> {
+        let uri = uri_key(uri);
+
+        return self
+            .folders
+            .iter()
+            .filter(|folder| {
+                let root = uri_key(&folder.uri);
+                uri.strip_prefix(root.trim_end_matches('/'))
+                    .map(|rest| rest.is_empty() || rest.starts_with('/'))
+                    .unwrap_or(false)
+            })
+            .max_by_key(|folder| folder.uri.as_str().len());
+    }
+}
+
+fn folder_name(uri: &Url) -> String {
+    return uri
+        .path_segments()
+        .and_then(|mut segments| segments.rfind(|s| !s.is_empty()))
+        .unwrap_or_default()
+        .to_string();
+}
+
+pub static WORKSPACE: OnceLock<Arc<Mutex<Workspace>>> = OnceLock::new();
+pub fn init_workspace(workspace: Workspace) {
+    _ = WORKSPACE.set(Arc::new(Mutex::new(workspace)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Workspace;
+    use lsp_types::{InitializeParams, Url, WorkspaceFolder, WorkspaceFoldersChangeEvent};
+
+    fn folder(uri: &str) -> WorkspaceFolder {
+        let uri = Url::parse(uri).expect("valid uri");
+        return WorkspaceFolder {
+            name: super::folder_name(&uri),
+            uri,
+        };
+    }
+
+    #[test]
+    fn test_falls_back_to_root_uri() {
+        let params = InitializeParams {
+            root_uri: Some(Url::parse("file:///projects/app/").expect("valid uri")),
+            ..Default::default()
+        };
+
+        let workspace = Workspace::from_initialize_params(&params);
+
+        assert_eq!(workspace.folders, vec![folder("file:///projects/app/")]);
+        assert_eq!(workspace.folders[0].name, "app");
+    }
+
+    #[test]
+    fn test_applies_folder_changes() {
+        let mut workspace = Workspace {
+            folders: vec![folder("file:///repo/web"), folder("file:///repo/admin")],
+        };
+
+        workspace.apply_change(WorkspaceFoldersChangeEvent {
+            added: vec![folder("file:///repo/docs"), folder("file:///repo/web")],
+            removed: vec![folder("file:///repo/admin")],
+        });
+
+        assert_eq!(
+            workspace.folders,
+            vec![folder("file:///repo/web"), folder("file:///repo/docs")]
+        );
+    }
+
+    #[test]
+    fn test_finds_innermost_folder() {
+        let workspace = Workspace {
+            folders: vec![folder("file:///repo"), folder("file:///repo/web/")],
+        };
+
+        let uri = |s: &str| Url::parse(s).expect("valid uri");
+
+        assert_eq!(
+            workspace.folder_for(&uri("file:///repo/web/index.html")),
+            Some(&folder("file:///repo/web/"))
+        );
+        assert_eq!(
+            workspace.folder_for(&uri("file:///repo/admin/index.html")),
+            Some(&folder("file:///repo"))
+        );
+        assert_eq!(
+            workspace.folder_for(&uri("file:///repository/a.html")),
+            None
+        );
+    }
+}
@@ -9,24 +9,31 @@ use structured_logger::{json::new_writer, Builder};
 
 use opts::JSPerfLspConfig;
 
-use lsp::start_lsp;
+use lsp::{start_lsp, ClientLogWriter};
 
 fn main() -> Result<()> {
     let config = JSPerfLspConfig::parse();
 
     let mut builder = Builder::with_level(&config.level);
 
-    if let Some(file) = &config.file {
+    let writer = if let Some(file) = &config.file {
         let log_file = File::options()
             .create(true)
             .append(true)
             .open(file)
             .unwrap();
 
-        builder = builder.with_target_writer("*", new_writer(log_file));
+        Some(new_writer(log_file))
+    } else if config.client_log {
+        None
     } else {
-        builder = builder.with_target_writer("*", new_writer(stderr()))
-    }
+        Some(new_writer(stderr()))
+    };
+
+    builder = match writer {
+        Some(writer) if !config.client_log => builder.with_target_writer("*", writer),
+        writer => builder.with_target_writer("*", Box::new(ClientLogWriter::new(writer))),
+    };
 
     builder.init();
     trace!("log options: {:?}", config);
@@ -3,7 +3,6 @@ use clap::Parser;
 #[derive(Parser, Debug)]
 #[clap(name = "waxwing-lsp")]
 pub struct JSPerfLspConfig {
-
     /// The file to pipe logs out to
     #[clap(short, long)]
     pub file: Option<String>,
@@ -12,4 +11,9 @@ pub struct JSPerfLspConfig {
     /// Valid values are: TRACE, DEBUG, INFO, WARN, ERROR
     #[clap(short, long, default_value = "INFO")]
     pub level: String,
+
+    /// Send the logs to the editor as window/logMessage notifications,
+    /// in addition to the log file when one is given
+    #[clap(short, long)]
+    pub client_log: bool,
 }
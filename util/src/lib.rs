@@ -1,48 +1,141 @@
 use lsp_types::Position;
 
-pub fn byte_pos_to_line_col(source: &str, byte_pos: usize) -> Position {
-    let mut line = 0;
-    let mut col: u32 = 0;
+/// Unit the `character` of an LSP position is counted in, negotiated with
+/// the client during initialize.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+}
 
-    for (idx, c) in source.chars().enumerate() {
-        if idx >= byte_pos {
-            break;
-        }
-        if c == '\n' {
-            line += 1;
-            col = 0;
-        } else {
-            col += 1;
-        }
+impl PositionEncoding {
+    fn char_len(&self, c: char) -> usize {
+        return match self {
+            PositionEncoding::Utf8 => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+        };
     }
+}
+
+/// Converts a byte offset of `source` into an LSP position.
+pub fn byte_pos_to_line_col(source: &str, byte_pos: usize, encoding: PositionEncoding) -> Position {
+    // Offsets computed on other text can land inside a character, they
+    // count from its start
+    let mut byte_pos = byte_pos.min(source.len());
+    while !source.is_char_boundary(byte_pos) {
+        byte_pos -= 1;
+    }
+    let before = &source[..byte_pos];
+
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+    let line = before.matches('\n').count();
+    let character: usize = before[line_start..]
+        .chars()
+        .map(|c| encoding.char_len(c))
+        .sum();
 
     return Position {
-        line,
-        character: col.saturating_sub(1),
+        line: line as u32,
+        character: character as u32,
     };
 }
 
-pub fn get_text_byte_offset(source: &str, line: usize, character: usize) -> Option<usize> {
+/// Converts an LSP line/character pair into a byte offset of `source`.
+/// Positions past the end of a line are clamped to the line end, as the LSP
+/// spec requires.
+pub fn get_text_byte_offset(
+    source: &str,
+    line: usize,
+    character: usize,
+    encoding: PositionEncoding,
+) -> Option<usize> {
+    let mut line_start = 0;
 
-    let mut character = character;
-    let mut line = line;
-    let mut byte_offset = 0;
+    for _ in 0..line {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+
+    let line_text = &source[line_start..];
+    let line_text = &line_text[..line_text.find('\n').unwrap_or(line_text.len())];
 
-    for l in source.lines() {
-        if line == 0 {
-            byte_offset += character;
-            character = character.saturating_sub(l.len());
-            break;
-        } else {
-            line -= 1;
-            byte_offset += l.len() + 1;
+    let mut units = 0;
+    for (idx, c) in line_text.char_indices() {
+        if units >= character {
+            return Some(line_start + idx);
         }
+        units += encoding.char_len(c);
     }
 
-    if line != 0 || character != 0 {
-        return None;
+    return Some(line_start + line_text.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{byte_pos_to_line_col, get_text_byte_offset, PositionEncoding};
+    use lsp_types::Position;
+
+    const UTF16: PositionEncoding = PositionEncoding::Utf16;
+    const UTF8: PositionEncoding = PositionEncoding::Utf8;
+
+    #[test]
+    fn test_byte_offset_of_positions() {
+        let source = "<div>\n  <span hx-get=\"/\">\n</div>";
+
+        assert_eq!(get_text_byte_offset(source, 0, 0, UTF16), Some(0));
+        assert_eq!(get_text_byte_offset(source, 0, 5, UTF16), Some(5));
+        assert_eq!(get_text_byte_offset(source, 1, 2, UTF16), Some(8));
+        assert_eq!(
+            get_text_byte_offset(source, 2, 6, UTF16),
+            Some(source.len())
+        );
+        assert_eq!(get_text_byte_offset(source, 3, 0, UTF16), None);
     }
 
-    return Some(byte_offset);
-}
+    #[test]
+    fn test_byte_offset_clamps_to_line_end() {
+        let source = "ab\ncd";
+
+        assert_eq!(get_text_byte_offset(source, 0, 10, UTF16), Some(2));
+    }
+
+    #[test]
+    fn test_byte_offset_counts_encoding_units() {
+        // "é" is one UTF-16 unit but two bytes, "😀" is two units and four bytes
+        let source = "é😀x";
+
+        assert_eq!(get_text_byte_offset(source, 0, 1, UTF16), Some(2));
+        assert_eq!(get_text_byte_offset(source, 0, 3, UTF16), Some(6));
+        assert_eq!(get_text_byte_offset(source, 0, 2, UTF8), Some(2));
+        assert_eq!(get_text_byte_offset(source, 0, 6, UTF8), Some(6));
+    }
+
+    #[test]
+    fn test_byte_pos_to_line_col() {
+        let source = "<p>é</p>\n<div hx-get=\"/😀\" hx-swap=\"none\">";
 
+        assert_eq!(byte_pos_to_line_col(source, 0, UTF16), Position::new(0, 0));
+        assert_eq!(byte_pos_to_line_col(source, 6, UTF16), Position::new(0, 5));
+        assert_eq!(byte_pos_to_line_col(source, 6, UTF8), Position::new(0, 6));
+
+        let swap = source.find("hx-swap").expect("hx-swap in source");
+        assert_eq!(
+            byte_pos_to_line_col(source, swap, UTF16),
+            Position::new(1, 18)
+        );
+        assert_eq!(
+            byte_pos_to_line_col(source, swap, UTF8),
+            Position::new(1, 20)
+        );
+    }
+
+    #[test]
+    fn test_byte_pos_inside_a_character_counts_from_its_start() {
+        // "é" takes the bytes 1 and 2, "😀" the bytes 3 to 6
+        let source = "aé😀b";
+
+        assert_eq!(byte_pos_to_line_col(source, 2, UTF16), Position::new(0, 1));
+        assert_eq!(byte_pos_to_line_col(source, 5, UTF16), Position::new(0, 2));
+        assert_eq!(byte_pos_to_line_col(source, 5, UTF8), Position::new(0, 3));
+    }
+}